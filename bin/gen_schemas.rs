@@ -0,0 +1,18 @@
+use deciduous::json_schema::{to_json, PUBLISHED};
+use std::fs;
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let output_dir = PathBuf::from("schemas");
+    if !output_dir.exists() {
+        fs::create_dir_all(&output_dir)?;
+    }
+
+    // Generate JSON Schemas for the patch, graph-data and git-history formats
+    for (file, generate) in PUBLISHED {
+        let output_file = output_dir.join(file);
+        fs::write(&output_file, to_json(&generate()))?;
+        println!("Generated JSON Schema at {:?}", output_file);
+    }
+    Ok(())
+}
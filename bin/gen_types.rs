@@ -1,6 +1,6 @@
 use deciduous::{
-    CommandLog, DecisionContext, DecisionEdge, DecisionNode, DecisionSession, RoadmapConflict,
-    RoadmapItem, RoadmapSyncState,
+    CommandLog, DecisionContext, DecisionEdge, DecisionNode, DecisionSession, NodeConflict,
+    RoadmapConflict, RoadmapItem, RoadmapSyncState,
 };
 use std::fs;
 use std::path::PathBuf;
@@ -22,6 +22,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ("RoadmapItem", RoadmapItem::decl()),
         ("RoadmapSyncState", RoadmapSyncState::decl()),
         ("RoadmapConflict", RoadmapConflict::decl()),
+        ("NodeConflict", NodeConflict::decl()),
     ];
 
     let mut content = String::from(
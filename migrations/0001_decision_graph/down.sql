@@ -1,7 +1,9 @@
 -- Revert decision graph schema
-DROP INDEX IF EXISTS idx_context_node;
+DROP INDEX IF EXISTS idx_edges_to_change;
+DROP INDEX IF EXISTS idx_edges_from_change;
 DROP INDEX IF EXISTS idx_edges_to;
 DROP INDEX IF EXISTS idx_edges_from;
+DROP INDEX IF EXISTS idx_nodes_change_id;
 DROP INDEX IF EXISTS idx_nodes_status;
 DROP INDEX IF EXISTS idx_nodes_type;
 DROP TABLE IF EXISTS session_nodes;
@@ -1,28 +1,30 @@
 -- Decision Graph Schema (Directed Acyclic Graph)
--- Models decisions, alternatives, and the paths taken during development
+-- Models decisions, alternatives, and the paths taken during development.
+-- Uses IF NOT EXISTS so databases created before versioned migrations adopt it cleanly.
 
--- Node types in the decision graph
 -- Types: 'goal', 'decision', 'option', 'action', 'outcome', 'observation'
-CREATE TABLE decision_nodes (
+CREATE TABLE IF NOT EXISTS decision_nodes (
     id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    change_id TEXT NOT NULL UNIQUE,  -- stable UUID for multi-user sync
     node_type TEXT NOT NULL,
     title TEXT NOT NULL,
     description TEXT,
-    status TEXT NOT NULL DEFAULT 'pending',  -- pending, active, completed, rejected
+    status TEXT NOT NULL DEFAULT 'pending',
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
-    metadata_json TEXT  -- flexible JSON for node-type-specific data
+    metadata_json TEXT
 );
 
--- Edges connecting nodes (directed)
 -- Edge types: 'leads_to', 'requires', 'chosen', 'rejected', 'blocks', 'enables'
-CREATE TABLE decision_edges (
+CREATE TABLE IF NOT EXISTS decision_edges (
     id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     from_node_id INTEGER NOT NULL,
     to_node_id INTEGER NOT NULL,
+    from_change_id TEXT,
+    to_change_id TEXT,
     edge_type TEXT NOT NULL,
-    weight REAL DEFAULT 1.0,  -- for prioritization
-    rationale TEXT,  -- why this edge exists
+    weight REAL DEFAULT 1.0,
+    rationale TEXT,
     created_at TEXT NOT NULL,
     FOREIGN KEY (from_node_id) REFERENCES decision_nodes(id),
     FOREIGN KEY (to_node_id) REFERENCES decision_nodes(id),
@@ -30,17 +32,17 @@ CREATE TABLE decision_edges (
 );
 
 -- Context snapshots at decision points
-CREATE TABLE decision_context (
+CREATE TABLE IF NOT EXISTS decision_context (
     id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     node_id INTEGER NOT NULL,
-    context_type TEXT NOT NULL,  -- 'file_state', 'test_result', 'analysis_result', 'user_input'
+    context_type TEXT NOT NULL,
     content_json TEXT NOT NULL,
     captured_at TEXT NOT NULL,
     FOREIGN KEY (node_id) REFERENCES decision_nodes(id)
 );
 
 -- Sessions group related decisions together
-CREATE TABLE decision_sessions (
+CREATE TABLE IF NOT EXISTS decision_sessions (
     id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     name TEXT,
     started_at TEXT NOT NULL,
@@ -50,8 +52,7 @@ CREATE TABLE decision_sessions (
     FOREIGN KEY (root_node_id) REFERENCES decision_nodes(id)
 );
 
--- Link nodes to sessions
-CREATE TABLE session_nodes (
+CREATE TABLE IF NOT EXISTS session_nodes (
     session_id INTEGER NOT NULL,
     node_id INTEGER NOT NULL,
     added_at TEXT NOT NULL,
@@ -60,8 +61,10 @@ CREATE TABLE session_nodes (
     FOREIGN KEY (node_id) REFERENCES decision_nodes(id)
 );
 
-CREATE INDEX idx_nodes_type ON decision_nodes(node_type);
-CREATE INDEX idx_nodes_status ON decision_nodes(status);
-CREATE INDEX idx_edges_from ON decision_edges(from_node_id);
-CREATE INDEX idx_edges_to ON decision_edges(to_node_id);
-CREATE INDEX idx_context_node ON decision_context(node_id);
+CREATE INDEX IF NOT EXISTS idx_nodes_type ON decision_nodes(node_type);
+CREATE INDEX IF NOT EXISTS idx_nodes_status ON decision_nodes(status);
+CREATE INDEX IF NOT EXISTS idx_nodes_change_id ON decision_nodes(change_id);
+CREATE INDEX IF NOT EXISTS idx_edges_from ON decision_edges(from_node_id);
+CREATE INDEX IF NOT EXISTS idx_edges_to ON decision_edges(to_node_id);
+CREATE INDEX IF NOT EXISTS idx_edges_from_change ON decision_edges(from_change_id);
+CREATE INDEX IF NOT EXISTS idx_edges_to_change ON decision_edges(to_change_id);
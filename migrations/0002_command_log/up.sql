@@ -1,7 +1,7 @@
 -- Command log for tracking shell/bash commands executed
 -- Provides full audit trail of actions taken
 
-CREATE TABLE command_log (
+CREATE TABLE IF NOT EXISTS command_log (
     id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
     command TEXT NOT NULL,
     description TEXT,
@@ -16,5 +16,4 @@ CREATE TABLE command_log (
     FOREIGN KEY (decision_node_id) REFERENCES decision_nodes(id)
 );
 
-CREATE INDEX idx_command_started_at ON command_log(started_at);
-CREATE INDEX idx_command_decision_node ON command_log(decision_node_id);
+CREATE INDEX IF NOT EXISTS idx_command_started_at ON command_log(started_at);
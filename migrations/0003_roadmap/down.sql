@@ -0,0 +1,11 @@
+-- Revert roadmap board
+DROP INDEX IF EXISTS idx_github_issue_cache_repo;
+DROP INDEX IF EXISTS idx_roadmap_conflicts_item;
+DROP INDEX IF EXISTS idx_roadmap_items_outcome;
+DROP INDEX IF EXISTS idx_roadmap_items_github_issue;
+DROP INDEX IF EXISTS idx_roadmap_items_section;
+DROP INDEX IF EXISTS idx_roadmap_items_change_id;
+DROP TABLE IF EXISTS github_issue_cache;
+DROP TABLE IF EXISTS roadmap_conflicts;
+DROP TABLE IF EXISTS roadmap_sync_state;
+DROP TABLE IF EXISTS roadmap_items;
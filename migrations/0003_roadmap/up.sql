@@ -0,0 +1,67 @@
+-- Roadmap board: ROADMAP.md items synced with GitHub issues and outcome nodes
+
+CREATE TABLE IF NOT EXISTS roadmap_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    change_id TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    description TEXT,
+    section TEXT,
+    parent_id INTEGER,
+    checkbox_state TEXT NOT NULL DEFAULT 'none',
+    github_issue_number INTEGER,
+    github_issue_state TEXT,
+    outcome_node_id INTEGER,
+    outcome_change_id TEXT,
+    markdown_line_start INTEGER,
+    markdown_line_end INTEGER,
+    content_hash TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    last_synced_at TEXT,
+    FOREIGN KEY (parent_id) REFERENCES roadmap_items(id),
+    FOREIGN KEY (outcome_node_id) REFERENCES decision_nodes(id)
+);
+
+CREATE TABLE IF NOT EXISTS roadmap_sync_state (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    roadmap_path TEXT NOT NULL,
+    roadmap_content_hash TEXT,
+    github_repo TEXT,
+    last_github_sync TEXT,
+    last_markdown_parse TEXT,
+    conflict_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS roadmap_conflicts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    item_change_id TEXT NOT NULL,
+    conflict_type TEXT NOT NULL,
+    local_value TEXT,
+    remote_value TEXT,
+    resolution TEXT,
+    detected_at TEXT NOT NULL,
+    resolved_at TEXT,
+    FOREIGN KEY (item_change_id) REFERENCES roadmap_items(change_id)
+);
+
+-- GitHub issue cache for TUI/Web display
+CREATE TABLE IF NOT EXISTS github_issue_cache (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    issue_number INTEGER NOT NULL,
+    repo TEXT NOT NULL,
+    title TEXT NOT NULL,
+    body TEXT,
+    state TEXT NOT NULL,
+    html_url TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    cached_at TEXT NOT NULL,
+    UNIQUE(repo, issue_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_roadmap_items_change_id ON roadmap_items(change_id);
+CREATE INDEX IF NOT EXISTS idx_roadmap_items_section ON roadmap_items(section);
+CREATE INDEX IF NOT EXISTS idx_roadmap_items_github_issue ON roadmap_items(github_issue_number);
+CREATE INDEX IF NOT EXISTS idx_roadmap_items_outcome ON roadmap_items(outcome_change_id);
+CREATE INDEX IF NOT EXISTS idx_roadmap_conflicts_item ON roadmap_conflicts(item_change_id);
+CREATE INDEX IF NOT EXISTS idx_github_issue_cache_repo ON github_issue_cache(repo, issue_number);
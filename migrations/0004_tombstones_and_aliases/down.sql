@@ -0,0 +1,3 @@
+-- Revert tombstones and aliases
+DROP TABLE IF EXISTS change_id_aliases;
+DROP TABLE IF EXISTS decision_tombstones;
@@ -0,0 +1,20 @@
+-- Deletions and merges that must propagate through diff patches
+
+-- kind = 'node' (change_id = node change_id) or 'edge' (change_id = "from->to:type")
+CREATE TABLE IF NOT EXISTS decision_tombstones (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    kind TEXT NOT NULL,
+    change_id TEXT NOT NULL,
+    from_change_id TEXT,
+    to_change_id TEXT,
+    edge_type TEXT,
+    deleted_at TEXT NOT NULL,
+    UNIQUE(kind, change_id)
+);
+
+-- change_ids of merged-away duplicates, pointing at the node that was kept
+CREATE TABLE IF NOT EXISTS change_id_aliases (
+    alias TEXT PRIMARY KEY NOT NULL,
+    change_id TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
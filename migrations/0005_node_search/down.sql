@@ -0,0 +1,5 @@
+-- Revert node full-text search
+DROP TRIGGER IF EXISTS decision_nodes_fts_update;
+DROP TRIGGER IF EXISTS decision_nodes_fts_delete;
+DROP TRIGGER IF EXISTS decision_nodes_fts_insert;
+DROP TABLE IF EXISTS decision_nodes_fts;
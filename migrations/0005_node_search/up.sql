@@ -0,0 +1,51 @@
+-- Full-text index over node title, description, prompt and files.
+-- rowid = decision_nodes.id; kept in sync by the triggers below.
+
+CREATE VIRTUAL TABLE IF NOT EXISTS decision_nodes_fts USING fts5(
+    title,
+    description,
+    prompt,
+    files,
+    tokenize = 'porter unicode61'
+);
+
+CREATE TRIGGER IF NOT EXISTS decision_nodes_fts_insert AFTER INSERT ON decision_nodes BEGIN
+    INSERT INTO decision_nodes_fts (rowid, title, description, prompt, files)
+    VALUES (
+        new.id,
+        new.title,
+        new.description,
+        CASE WHEN json_valid(new.metadata_json) THEN json_extract(new.metadata_json, '$.prompt') END,
+        CASE WHEN json_valid(new.metadata_json) THEN CASE WHEN json_type(new.metadata_json, '$.files') = 'array'
+            THEN (SELECT group_concat(value, ' ') FROM json_each(new.metadata_json, '$.files')) END END
+    );
+END;
+
+CREATE TRIGGER IF NOT EXISTS decision_nodes_fts_delete AFTER DELETE ON decision_nodes BEGIN
+    DELETE FROM decision_nodes_fts WHERE rowid = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS decision_nodes_fts_update AFTER UPDATE ON decision_nodes BEGIN
+    DELETE FROM decision_nodes_fts WHERE rowid = old.id;
+    INSERT INTO decision_nodes_fts (rowid, title, description, prompt, files)
+    VALUES (
+        new.id,
+        new.title,
+        new.description,
+        CASE WHEN json_valid(new.metadata_json) THEN json_extract(new.metadata_json, '$.prompt') END,
+        CASE WHEN json_valid(new.metadata_json) THEN CASE WHEN json_type(new.metadata_json, '$.files') = 'array'
+            THEN (SELECT group_concat(value, ' ') FROM json_each(new.metadata_json, '$.files')) END END
+    );
+END;
+
+-- Index nodes that existed before this migration
+DELETE FROM decision_nodes_fts;
+INSERT INTO decision_nodes_fts (rowid, title, description, prompt, files)
+SELECT
+    id,
+    title,
+    description,
+    CASE WHEN json_valid(metadata_json) THEN json_extract(metadata_json, '$.prompt') END,
+    CASE WHEN json_valid(metadata_json) THEN CASE WHEN json_type(metadata_json, '$.files') = 'array'
+        THEN (SELECT group_concat(value, ' ') FROM json_each(metadata_json, '$.files')) END END
+FROM decision_nodes;
@@ -0,0 +1,4 @@
+-- Revert node sync state and conflicts
+DROP INDEX IF EXISTS idx_node_conflicts_node;
+DROP TABLE IF EXISTS node_conflicts;
+DROP TABLE IF EXISTS node_sync_bases;
@@ -0,0 +1,27 @@
+-- Three-way sync of node content through diff patches
+
+-- Last version of each node shared through a patch (exported or applied).
+-- content is the node content JSON, kept so later edits can be merged against it.
+CREATE TABLE IF NOT EXISTS node_sync_bases (
+    change_id TEXT PRIMARY KEY NOT NULL,
+    content_hash TEXT NOT NULL,
+    parent_hash TEXT,
+    content TEXT NOT NULL,
+    synced_at TEXT NOT NULL
+);
+
+-- Concurrent edits to the same node that apply couldn't merge.
+-- Values are node content JSON; resolution = 'ours', 'theirs' or 'edit'.
+CREATE TABLE IF NOT EXISTS node_conflicts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    node_change_id TEXT NOT NULL,
+    base_hash TEXT,
+    base_value TEXT,
+    local_value TEXT NOT NULL,
+    remote_value TEXT NOT NULL,
+    resolution TEXT,
+    detected_at TEXT NOT NULL,
+    resolved_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_conflicts_node ON node_conflicts(node_change_id);
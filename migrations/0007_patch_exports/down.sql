@@ -0,0 +1,2 @@
+-- Revert patch export history
+DROP TABLE IF EXISTS patch_exports;
@@ -0,0 +1,11 @@
+-- History of `deciduous diff export`, so --since-last knows where the previous export stopped
+CREATE TABLE IF NOT EXISTS patch_exports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    path TEXT NOT NULL,
+    -- Patch created_at; changes after this go into the next --since-last export
+    exported_at TEXT NOT NULL,
+    -- Lower bound the export was limited to, if any
+    since TEXT,
+    node_count INTEGER NOT NULL,
+    edge_count INTEGER NOT NULL
+);
@@ -0,0 +1,4 @@
+-- Revert patch application history
+DROP INDEX IF EXISTS idx_applied_patch_items_digest;
+DROP TABLE IF EXISTS applied_patch_items;
+DROP TABLE IF EXISTS applied_patches;
@@ -0,0 +1,29 @@
+-- Patches applied with `deciduous diff apply`, keyed by patch digest
+CREATE TABLE IF NOT EXISTS applied_patches (
+    digest TEXT PRIMARY KEY NOT NULL,
+    path TEXT NOT NULL,
+    author TEXT,
+    -- The patch's own created_at
+    created_at TEXT NOT NULL,
+    applied_at TEXT NOT NULL,
+    nodes_added INTEGER NOT NULL,
+    nodes_updated INTEGER NOT NULL,
+    edges_added INTEGER NOT NULL,
+    -- Non-zero failures mean the patch is only partially applied
+    nodes_failed INTEGER NOT NULL,
+    edges_failed INTEGER NOT NULL
+);
+
+-- Nodes and edges each applied patch created, so `diff revert` removes exactly those
+CREATE TABLE IF NOT EXISTS applied_patch_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    patch_digest TEXT NOT NULL,
+    kind TEXT NOT NULL,  -- 'node' or 'edge'
+    -- Node change_id, or edge_tombstone_key() for edges
+    change_id TEXT NOT NULL,
+    from_change_id TEXT,
+    to_change_id TEXT,
+    edge_type TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_applied_patch_items_digest ON applied_patch_items(patch_digest);
@@ -0,0 +1,3 @@
+-- Revert node and edge authors
+ALTER TABLE decision_edges DROP COLUMN author;
+ALTER TABLE decision_nodes DROP COLUMN author;
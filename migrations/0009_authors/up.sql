@@ -0,0 +1,6 @@
+-- Who created each node and edge
+
+-- "Name <email>" from git config or the [author] section of config.toml;
+-- NULL for rows created before authors were recorded
+ALTER TABLE decision_nodes ADD COLUMN author TEXT;
+ALTER TABLE decision_edges ADD COLUMN author TEXT;
@@ -0,0 +1,2 @@
+-- Revert sync base ancestry
+ALTER TABLE node_sync_bases DROP COLUMN ancestor_hashes;
@@ -0,0 +1,5 @@
+-- Earlier shared versions of each node, so a teammate who missed an export can
+-- still fast-forward from any version they hold
+
+-- JSON array of content hashes before parent_hash, newest first
+ALTER TABLE node_sync_bases ADD COLUMN ancestor_hashes TEXT NOT NULL DEFAULT '[]';
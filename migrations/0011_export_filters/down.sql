@@ -0,0 +1,2 @@
+-- Revert export filters
+ALTER TABLE patch_exports DROP COLUMN filter;
@@ -0,0 +1,5 @@
+-- Remember which nodes a `diff export` covered, so --since-last only continues
+-- from an export that included everything it is about to export
+
+-- JSON of the --nodes/--branch/--tag filters; NULL for an unfiltered export
+ALTER TABLE patch_exports ADD COLUMN filter TEXT;
@@ -2,11 +2,12 @@
 //!
 //! Reads from .deciduous/config.toml
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 /// Configuration structure
-#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone, JsonSchema)]
 pub struct Config {
     /// Branch settings
     #[serde(default)]
@@ -15,10 +16,75 @@ pub struct Config {
     /// GitHub settings for external repository references
     #[serde(default)]
     pub github: GithubConfig,
+
+    /// Project-specific node/edge types and statuses
+    #[serde(default)]
+    pub types: TypesConfig,
+
+    /// Patch sync policy
+    #[serde(default)]
+    pub sync: SyncConfig,
+
+    /// Author recorded on new nodes and edges
+    #[serde(default)]
+    pub author: AuthorConfig,
+}
+
+/// Overrides for the author recorded on new nodes and edges. Unset fields fall
+/// back to `git config user.name` / `user.email`.
+///
+/// ```toml
+/// [author]
+/// name = "Alice"
+/// email = "alice@example.com"
+/// ```
+#[derive(Debug, Deserialize, Serialize, Default, Clone, JsonSchema)]
+pub struct AuthorConfig {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+/// Policy for applying patches from teammates
+///
+/// ```toml
+/// [sync]
+/// require_signed = true
+/// ```
+#[derive(Debug, Deserialize, Serialize, Default, Clone, JsonSchema)]
+pub struct SyncConfig {
+    /// Only accept patches signed by a key in `.deciduous/keys/*.pub`
+    #[serde(default)]
+    pub require_signed: bool,
+}
+
+/// Extra graph vocabulary accepted alongside the built-in types
+///
+/// ```toml
+/// [types]
+/// node = ["hypothesis"]
+/// edge = ["supersedes"]
+/// status = ["blocked"]
+/// ```
+#[derive(Debug, Deserialize, Serialize, Default, Clone, JsonSchema)]
+pub struct TypesConfig {
+    /// Extra node types
+    #[serde(default)]
+    pub node: Vec<String>,
+
+    /// Extra edge types
+    #[serde(default)]
+    pub edge: Vec<String>,
+
+    /// Extra node statuses
+    #[serde(default)]
+    pub status: Vec<String>,
 }
 
 /// GitHub-related configuration for commit/PR links
-#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone, JsonSchema)]
 pub struct GithubConfig {
     /// External repository for commit links (e.g., "phoenixframework/phoenix")
     /// When set, commit hashes in nodes will link to this repo instead of the local one.
@@ -28,7 +94,7 @@ pub struct GithubConfig {
 }
 
 /// Branch-related configuration
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct BranchConfig {
     /// Main/default branch names (nodes on these branches won't trigger special grouping)
     /// Default: ["main", "master"]
@@ -120,5 +186,33 @@ auto_detect = true
         let config: Config = toml::from_str(toml).unwrap();
         assert!(config.is_main_branch("develop"));
         assert!(!config.is_main_branch("feature-x"));
+        assert!(config.types.node.is_empty());
+        assert!(!config.sync.require_signed);
+    }
+
+    #[test]
+    fn test_parse_sync_config() {
+        let config: Config = toml::from_str("[sync]\nrequire_signed = true\n").unwrap();
+        assert!(config.sync.require_signed);
+    }
+
+    #[test]
+    fn test_parse_author_config() {
+        let config: Config = toml::from_str("[author]\nname = \"Alice\"\n").unwrap();
+        assert_eq!(config.author.name.as_deref(), Some("Alice"));
+        assert_eq!(config.author.email, None);
+    }
+
+    #[test]
+    fn test_parse_types_config() {
+        let toml = r#"
+[types]
+node = ["hypothesis"]
+edge = ["supersedes", "refines"]
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.types.node, vec!["hypothesis"]);
+        assert_eq!(config.types.edge, vec!["supersedes", "refines"]);
+        assert!(config.types.status.is_empty());
     }
 }
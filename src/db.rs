@@ -1,8 +1,10 @@
 //! SQLite database with Diesel ORM
 //!
 //! Stores decision graphs and command logs for AI-assisted development.
-//! Uses embedded migrations for schema management.
+//! Schema is managed by the versioned migrations in `migrations.rs`.
 
+use crate::config::{AuthorConfig, TypesConfig};
+use crate::migrations::{self, MigrationStatus, MigrationStep};
 use crate::schema::*;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
@@ -13,6 +15,315 @@ use std::path::Path;
 use ts_rs::TS;
 use uuid::Uuid;
 
+/// Current version of the metadata_json format
+pub const NODE_METADATA_VERSION: u64 = 1;
+
+/// Typed view of a node's `metadata_json`.
+///
+/// Known keys are parsed into fields; everything else (tags, links, custom keys) is
+/// kept in `extra` and written back unchanged. A known key holding an unexpected type
+/// is also left in `extra` rather than dropped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeMetadata {
+    /// Format version the metadata was written with (0 = written before versioning)
+    pub version: u64,
+    /// Confidence score 0-100
+    pub confidence: Option<u8>,
+    /// Git commit hash
+    pub commit: Option<String>,
+    /// User prompt that triggered this decision
+    pub prompt: Option<String>,
+    /// Associated files
+    pub files: Vec<String>,
+    /// Git branch this node was created on
+    pub branch: Option<String>,
+    /// Free-form labels (e.g. "security", "tech-debt")
+    pub tags: Vec<String>,
+    /// Key/value attributes (e.g. ticket=ABC-123)
+    pub attributes: std::collections::BTreeMap<String, String>,
+    /// Unknown keys, preserved verbatim
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl NodeMetadata {
+    /// Parse metadata from a JSON string (invalid JSON yields empty metadata)
+    pub fn from_json(json: &str) -> Self {
+        match serde_json::from_str::<serde_json::Value>(json) {
+            Ok(serde_json::Value::Object(map)) => Self::from_map(map),
+            _ => Self::default(),
+        }
+    }
+
+    /// Parse metadata from an optional JSON string
+    pub fn from_option(json: Option<&str>) -> Self {
+        json.map(Self::from_json).unwrap_or_default()
+    }
+
+    fn from_map(mut map: serde_json::Map<String, serde_json::Value>) -> Self {
+        use serde_json::Value;
+
+        fn take<T>(
+            map: &mut serde_json::Map<String, Value>,
+            key: &str,
+            f: impl Fn(&Value) -> Option<T>,
+        ) -> Option<T> {
+            let parsed = map.get(key).and_then(f);
+            if parsed.is_some() {
+                map.remove(key);
+            }
+            parsed
+        }
+
+        let version = take(&mut map, "metadata_version", Value::as_u64).unwrap_or(0);
+        let confidence = take(&mut map, "confidence", |v| {
+            v.as_u64().map(|c| c.min(100) as u8)
+        });
+        let commit = take(&mut map, "commit", |v| v.as_str().map(String::from));
+        let prompt = take(&mut map, "prompt", |v| v.as_str().map(String::from));
+        let branch = take(&mut map, "branch", |v| v.as_str().map(String::from));
+        let files = take(&mut map, "files", |v| {
+            let arr = v.as_array()?;
+            arr.iter()
+                .map(|f| f.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>()
+        })
+        .unwrap_or_default();
+        let tags = take(&mut map, "tags", |v| {
+            let arr = v.as_array()?;
+            arr.iter()
+                .map(|t| t.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>()
+        })
+        .unwrap_or_default();
+        let attributes = take(&mut map, "attributes", |v| {
+            let obj = v.as_object()?;
+            obj.iter()
+                .map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect::<Option<std::collections::BTreeMap<_, _>>>()
+        })
+        .unwrap_or_default();
+
+        Self {
+            version,
+            confidence,
+            commit,
+            prompt,
+            files,
+            branch,
+            tags,
+            attributes,
+            extra: map,
+        }
+    }
+
+    /// True when there is nothing worth storing
+    pub fn is_empty(&self) -> bool {
+        self.confidence.is_none()
+            && self.commit.is_none()
+            && self.prompt.is_none()
+            && self.files.is_empty()
+            && self.branch.is_none()
+            && self.tags.is_empty()
+            && self.attributes.is_empty()
+            && self.extra.is_empty()
+    }
+
+    /// Serialize to a JSON string stamped with the current format version.
+    /// Returns None when empty so the column stays NULL.
+    pub fn to_json(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut obj = self.extra.clone();
+        obj.insert("metadata_version".to_string(), json!(NODE_METADATA_VERSION));
+        if let Some(c) = self.confidence {
+            obj.insert("confidence".to_string(), json!(c.min(100)));
+        }
+        if let Some(ref h) = self.commit {
+            obj.insert("commit".to_string(), json!(h));
+        }
+        if let Some(ref p) = self.prompt {
+            obj.insert("prompt".to_string(), json!(p));
+        }
+        if !self.files.is_empty() {
+            obj.insert("files".to_string(), json!(self.files));
+        }
+        if let Some(ref b) = self.branch {
+            obj.insert("branch".to_string(), json!(b));
+        }
+        if !self.tags.is_empty() {
+            obj.insert("tags".to_string(), json!(self.tags));
+        }
+        if !self.attributes.is_empty() {
+            obj.insert("attributes".to_string(), json!(self.attributes));
+        }
+
+        Some(serde_json::Value::Object(obj).to_string())
+    }
+
+    /// Check whether the node carries a tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Apply a single tag/attribute edit
+    pub fn apply_label_edit(&mut self, edit: &LabelEdit) {
+        match edit {
+            LabelEdit::AddTag(tag) => {
+                if !self.has_tag(tag) {
+                    self.tags.push(tag.clone());
+                }
+            }
+            LabelEdit::RemoveTag(tag) => self.tags.retain(|t| t != tag),
+            LabelEdit::SetAttribute(key, value) => {
+                self.attributes.insert(key.clone(), value.clone());
+            }
+            LabelEdit::RemoveAttribute(key) => {
+                self.attributes.remove(key);
+            }
+        }
+    }
+
+    /// Replace files from a comma-separated list (an empty list clears them)
+    pub fn set_files_csv(&mut self, files: &str) {
+        self.files = files
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+    }
+}
+
+impl DecisionNode {
+    /// Parsed metadata for this node
+    pub fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::from_option(self.metadata_json.as_deref())
+    }
+
+    /// Confidence score 0-100, if set
+    pub fn confidence(&self) -> Option<u8> {
+        self.metadata().confidence
+    }
+
+    /// Linked git commit, if set
+    pub fn commit(&self) -> Option<String> {
+        self.metadata().commit
+    }
+
+    /// Prompt that triggered this node, if set
+    pub fn prompt(&self) -> Option<String> {
+        self.metadata().prompt
+    }
+
+    /// Associated files
+    pub fn files(&self) -> Vec<String> {
+        self.metadata().files
+    }
+
+    /// Git branch the node was created on, if set
+    pub fn branch(&self) -> Option<String> {
+        self.metadata().branch
+    }
+
+    /// Tags attached to the node
+    pub fn tags(&self) -> Vec<String> {
+        self.metadata().tags
+    }
+
+    /// True if the node carries every one of `tags`
+    pub fn has_all_tags(&self, tags: &[String]) -> bool {
+        let meta = self.metadata();
+        tags.iter().all(|t| meta.has_tag(t))
+    }
+
+    /// Author's name without the email, if the author is known
+    pub fn author_name(&self) -> Option<&str> {
+        self.author.as_deref().map(author_name)
+    }
+
+    /// True if the author's name or email contains `who` (case-insensitive)
+    pub fn is_authored_by(&self, who: &str) -> bool {
+        self.author
+            .as_deref()
+            .is_some_and(|a| a.to_lowercase().contains(&who.to_lowercase()))
+    }
+}
+
+/// Name part of a "Name <email>" author
+pub fn author_name(author: &str) -> &str {
+    match author.split_once(" <") {
+        Some((name, _)) if !name.trim().is_empty() => name.trim(),
+        _ => author.trim(),
+    }
+}
+
+/// A change to a node's tags or attributes, parsed from CLI syntax:
+/// `+tag` or `tag` adds a tag, `-tag` removes it, `key=value` sets an attribute
+/// and `key=` removes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelEdit {
+    AddTag(String),
+    RemoveTag(String),
+    SetAttribute(String, String),
+    RemoveAttribute(String),
+}
+
+impl LabelEdit {
+    /// An edit adding `tag` (no +/- prefix handling)
+    pub fn add_tag(tag: &str) -> Result<Self> {
+        Ok(LabelEdit::AddTag(validate_label(tag.trim(), "Tag")?))
+    }
+
+    /// An edit setting an attribute from `key=value`
+    pub fn set_attribute(spec: &str) -> Result<Self> {
+        match spec.parse()? {
+            edit @ LabelEdit::SetAttribute(..) => Ok(edit),
+            _ => Err(DbError::Validation(format!(
+                "Attribute '{}' must be in key=value form",
+                spec
+            ))),
+        }
+    }
+}
+
+impl std::str::FromStr for LabelEdit {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some((key, value)) = s.split_once('=') {
+            let key = validate_label(key.trim(), "Attribute name")?;
+            let value = value.trim();
+            return Ok(if value.is_empty() {
+                LabelEdit::RemoveAttribute(key)
+            } else {
+                LabelEdit::SetAttribute(key, value.to_string())
+            });
+        }
+        if let Some(tag) = s.strip_prefix('-') {
+            return Ok(LabelEdit::RemoveTag(validate_label(tag, "Tag")?));
+        }
+        let tag = s.strip_prefix('+').unwrap_or(s);
+        Ok(LabelEdit::AddTag(validate_label(tag, "Tag")?))
+    }
+}
+
+/// Tags and attribute names must be non-empty and free of whitespace, commas and '='
+fn validate_label(label: &str, what: &str) -> Result<String> {
+    if label.is_empty()
+        || label.starts_with(['+', '-'])
+        || label.contains(|c: char| c.is_whitespace() || c == ',' || c == '=')
+    {
+        return Err(DbError::Validation(format!(
+            "{} '{}' is invalid: use letters, digits, '-', '_' or ':' (no spaces, commas or '=')",
+            what, label
+        )));
+    }
+    Ok(label.to_string())
+}
+
 /// Build metadata JSON from optional fields (confidence, commit, prompt, files, branch)
 pub fn build_metadata_json(
     confidence: Option<u8>,
@@ -21,37 +332,17 @@ pub fn build_metadata_json(
     files: Option<&str>,
     branch: Option<&str>,
 ) -> Option<String> {
-    // Only create JSON if at least one field is present
-    if confidence.is_none()
-        && commit.is_none()
-        && prompt.is_none()
-        && files.is_none()
-        && branch.is_none()
-    {
-        return None;
-    }
-
-    let mut obj = serde_json::Map::new();
-
-    if let Some(c) = confidence {
-        obj.insert("confidence".to_string(), json!(c.min(100)));
-    }
-    if let Some(h) = commit {
-        obj.insert("commit".to_string(), json!(h));
-    }
-    if let Some(p) = prompt {
-        obj.insert("prompt".to_string(), json!(p));
-    }
+    let mut meta = NodeMetadata {
+        confidence,
+        commit: commit.map(String::from),
+        prompt: prompt.map(String::from),
+        branch: branch.map(String::from),
+        ..Default::default()
+    };
     if let Some(f) = files {
-        // Split comma-separated files into array
-        let file_list: Vec<&str> = f.split(',').map(|s| s.trim()).collect();
-        obj.insert("files".to_string(), json!(file_list));
+        meta.set_files_csv(f);
     }
-    if let Some(b) = branch {
-        obj.insert("branch".to_string(), json!(b));
-    }
-
-    Some(serde_json::Value::Object(obj).to_string())
+    meta.to_json()
 }
 
 /// Get current git branch name
@@ -72,6 +363,36 @@ pub fn get_current_git_branch() -> Option<String> {
         })
 }
 
+/// Read a `git config` value, if set
+fn git_config_value(key: &str) -> Option<String> {
+    std::process::Command::new("git")
+        .args(["config", "--get", key])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Author for new nodes and edges: the `[author]` config section, falling back to
+/// `git config user.name` / `user.email`. Formatted as "Name <email>" when both are
+/// known.
+pub fn resolve_author(config: &AuthorConfig) -> Option<String> {
+    let name = config
+        .name
+        .clone()
+        .or_else(|| git_config_value("user.name"));
+    let email = config
+        .email
+        .clone()
+        .or_else(|| git_config_value("user.email"));
+    match (name, email) {
+        (Some(name), Some(email)) => Some(format!("{} <{}>", name, email)),
+        (name, email) => name.or(email),
+    }
+}
+
 /// Get the current HEAD commit hash (short form, 7 chars)
 pub fn get_current_git_commit() -> Option<String> {
     std::process::Command::new("git")
@@ -119,6 +440,273 @@ fn get_db_path() -> std::path::PathBuf {
     std::path::PathBuf::from(".deciduous/deciduous.db")
 }
 
+// ============================================================================
+// Graph Vocabulary - node types, edge types and statuses
+// ============================================================================
+
+/// Built-in node types in the decision graph
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, clap::ValueEnum,
+)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum NodeType {
+    Goal,
+    Decision,
+    Option,
+    Action,
+    Outcome,
+    Observation,
+}
+
+impl NodeType {
+    pub const ALL: [NodeType; 6] = [
+        NodeType::Goal,
+        NodeType::Decision,
+        NodeType::Option,
+        NodeType::Action,
+        NodeType::Outcome,
+        NodeType::Observation,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeType::Goal => "goal",
+            NodeType::Decision => "decision",
+            NodeType::Option => "option",
+            NodeType::Action => "action",
+            NodeType::Outcome => "outcome",
+            NodeType::Observation => "observation",
+        }
+    }
+}
+
+/// Built-in edge types connecting nodes
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, clap::ValueEnum,
+)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum EdgeType {
+    LeadsTo,
+    Requires,
+    Chosen,
+    Rejected,
+    Blocks,
+    Enables,
+}
+
+impl EdgeType {
+    pub const ALL: [EdgeType; 6] = [
+        EdgeType::LeadsTo,
+        EdgeType::Requires,
+        EdgeType::Chosen,
+        EdgeType::Rejected,
+        EdgeType::Blocks,
+        EdgeType::Enables,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeType::LeadsTo => "leads_to",
+            EdgeType::Requires => "requires",
+            EdgeType::Chosen => "chosen",
+            EdgeType::Rejected => "rejected",
+            EdgeType::Blocks => "blocks",
+            EdgeType::Enables => "enables",
+        }
+    }
+}
+
+/// Built-in node statuses
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, clap::ValueEnum,
+)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum NodeStatus {
+    Pending,
+    Active,
+    Completed,
+    Rejected,
+}
+
+impl NodeStatus {
+    pub const ALL: [NodeStatus; 4] = [
+        NodeStatus::Pending,
+        NodeStatus::Active,
+        NodeStatus::Completed,
+        NodeStatus::Rejected,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeStatus::Pending => "pending",
+            NodeStatus::Active => "active",
+            NodeStatus::Completed => "completed",
+            NodeStatus::Rejected => "rejected",
+        }
+    }
+}
+
+impl std::fmt::Display for NodeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::fmt::Display for EdgeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::fmt::Display for NodeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for NodeType {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| unknown_value_error("node type", s, Self::ALL.map(|t| t.as_str()), &[]))
+    }
+}
+
+impl std::str::FromStr for EdgeType {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| unknown_value_error("edge type", s, Self::ALL.map(|t| t.as_str()), &[]))
+    }
+}
+
+impl std::str::FromStr for NodeStatus {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| unknown_value_error("status", s, Self::ALL.map(|t| t.as_str()), &[]))
+    }
+}
+
+/// Kinds of context that can be attached to a node
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, clap::ValueEnum,
+)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[serde(rename_all = "kebab-case")]
+#[value(rename_all = "kebab-case")]
+pub enum ContextType {
+    FileSnapshot,
+    Error,
+    Benchmark,
+    Url,
+    Snippet,
+}
+
+impl ContextType {
+    pub const ALL: [ContextType; 5] = [
+        ContextType::FileSnapshot,
+        ContextType::Error,
+        ContextType::Benchmark,
+        ContextType::Url,
+        ContextType::Snippet,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContextType::FileSnapshot => "file-snapshot",
+            ContextType::Error => "error",
+            ContextType::Benchmark => "benchmark",
+            ContextType::Url => "url",
+            ContextType::Snippet => "snippet",
+        }
+    }
+}
+
+impl std::fmt::Display for ContextType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ContextType {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| {
+                unknown_value_error("context type", s, Self::ALL.map(|t| t.as_str()), &[])
+            })
+    }
+}
+
+/// Build a validation error for an unknown type/status, suggesting the closest valid value
+fn unknown_value_error<const N: usize>(
+    what: &str,
+    value: &str,
+    builtin: [&str; N],
+    extra: &[String],
+) -> DbError {
+    let valid: Vec<&str> = builtin
+        .into_iter()
+        .chain(extra.iter().map(|s| s.as_str()))
+        .collect();
+
+    let suggestion = valid
+        .iter()
+        .map(|v| (edit_distance(value, v), *v))
+        .filter(|(d, _)| *d <= 2)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, v)| format!(" Did you mean '{}'?", v))
+        .unwrap_or_default();
+
+    DbError::Validation(format!(
+        "Unknown {} '{}'.{} Valid values: {}",
+        what,
+        value,
+        suggestion,
+        valid.join(", ")
+    ))
+}
+
+/// Levenshtein distance, used for "did you mean" suggestions
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b_chars.len() + 1];
+        for (j, cb) in b_chars.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+
+    prev[b_chars.len()]
+}
+
 /// Current schema version for deciduous
 pub const CURRENT_SCHEMA: DecisionSchema = DecisionSchema {
     major: 1,
@@ -209,10 +797,11 @@ pub struct NewDecisionNode<'a> {
     pub created_at: &'a str,
     pub updated_at: &'a str,
     pub metadata_json: Option<&'a str>,
+    pub author: Option<&'a str>,
 }
 
 /// Queryable decision node
-#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
+#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 #[cfg_attr(feature = "ts-rs", derive(TS))]
 #[cfg_attr(feature = "ts-rs", ts(export))]
 #[diesel(table_name = decision_nodes)]
@@ -226,6 +815,8 @@ pub struct DecisionNode {
     pub created_at: String,
     pub updated_at: String,
     pub metadata_json: Option<String>,
+    /// Who created the node ("Name <email>"), if known
+    pub author: Option<String>,
 }
 
 /// Insertable decision edge
@@ -240,10 +831,11 @@ pub struct NewDecisionEdge<'a> {
     pub weight: Option<f64>,
     pub rationale: Option<&'a str>,
     pub created_at: &'a str,
+    pub author: Option<&'a str>,
 }
 
 /// Queryable decision edge
-#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
+#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 #[cfg_attr(feature = "ts-rs", derive(TS))]
 #[cfg_attr(feature = "ts-rs", ts(export))]
 #[diesel(table_name = decision_edges)]
@@ -257,64 +849,352 @@ pub struct DecisionEdge {
     pub weight: Option<f64>,
     pub rationale: Option<String>,
     pub created_at: String,
+    /// Who created the edge ("Name <email>"), if known
+    pub author: Option<String>,
 }
 
-/// Insertable decision context
+/// Insertable tombstone
 #[derive(Insertable)]
-#[diesel(table_name = decision_context)]
-pub struct NewDecisionContext<'a> {
-    pub node_id: i32,
-    pub context_type: &'a str,
-    pub content_json: &'a str,
-    pub captured_at: &'a str,
+#[diesel(table_name = decision_tombstones)]
+pub struct NewTombstone<'a> {
+    pub kind: &'a str,
+    pub change_id: &'a str,
+    pub from_change_id: Option<&'a str>,
+    pub to_change_id: Option<&'a str>,
+    pub edge_type: Option<&'a str>,
+    pub deleted_at: &'a str,
 }
 
-/// Queryable decision context
+/// Queryable tombstone - records a deleted node or edge so patch sync won't resurrect it.
+///
+/// Node tombstones are keyed by the node's change_id. Edge tombstones are keyed by
+/// `edge_tombstone_key(from_change_id, to_change_id, edge_type)`.
 #[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
 #[cfg_attr(feature = "ts-rs", derive(TS))]
 #[cfg_attr(feature = "ts-rs", ts(export))]
-#[diesel(table_name = decision_context)]
-pub struct DecisionContext {
+#[diesel(table_name = decision_tombstones)]
+pub struct Tombstone {
     pub id: i32,
-    pub node_id: i32,
-    pub context_type: String,
-    pub content_json: String,
-    pub captured_at: String,
+    /// "node" or "edge"
+    pub kind: String,
+    pub change_id: String,
+    pub from_change_id: Option<String>,
+    pub to_change_id: Option<String>,
+    pub edge_type: Option<String>,
+    pub deleted_at: String,
 }
 
-/// Insertable session
+/// Insertable change_id alias
 #[derive(Insertable)]
-#[diesel(table_name = decision_sessions)]
-pub struct NewDecisionSession<'a> {
-    pub name: Option<&'a str>,
-    pub started_at: &'a str,
-    pub ended_at: Option<&'a str>,
-    pub root_node_id: Option<i32>,
-    pub summary: Option<&'a str>,
+#[diesel(table_name = change_id_aliases)]
+pub struct NewChangeIdAlias<'a> {
+    pub alias: &'a str,
+    pub change_id: &'a str,
+    pub created_at: &'a str,
 }
 
-/// Queryable session
+/// Queryable change_id alias - maps the change_id of a node merged away to the node it
+/// was merged into, so patches that still reference the old change_id resolve.
 #[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
 #[cfg_attr(feature = "ts-rs", derive(TS))]
 #[cfg_attr(feature = "ts-rs", ts(export))]
-#[diesel(table_name = decision_sessions)]
-pub struct DecisionSession {
-    pub id: i32,
-    pub name: Option<String>,
-    pub started_at: String,
-    pub ended_at: Option<String>,
-    pub root_node_id: Option<i32>,
-    pub summary: Option<String>,
+#[diesel(table_name = change_id_aliases)]
+pub struct ChangeIdAlias {
+    pub alias: String,
+    pub change_id: String,
+    pub created_at: String,
 }
 
-// ============================================================================
-// Command Log Models
-// ============================================================================
+/// Insertable/replaceable node sync base
+#[derive(Insertable)]
+#[diesel(table_name = node_sync_bases)]
+pub struct NewNodeSyncBase<'a> {
+    pub change_id: &'a str,
+    pub content_hash: &'a str,
+    pub parent_hash: Option<&'a str>,
+    pub content: &'a str,
+    pub synced_at: &'a str,
+    pub ancestor_hashes: &'a str,
+}
 
-/// Insertable command log entry
+/// Queryable node sync base - the last version of a node shared through a patch,
+/// used as the common ancestor when both sides have since edited it.
+#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
+#[diesel(table_name = node_sync_bases)]
+pub struct NodeSyncBase {
+    pub change_id: String,
+    /// Hash of the synced content
+    pub content_hash: String,
+    /// Hash of the version the synced one was derived from, if known
+    pub parent_hash: Option<String>,
+    /// Synced node content as JSON
+    pub content: String,
+    pub synced_at: String,
+    /// Earlier shared versions before `parent_hash`, newest first (JSON array)
+    pub ancestor_hashes: String,
+}
+
+impl NodeSyncBase {
+    /// Content hashes this version descends from, newest first
+    pub fn lineage(&self) -> Vec<String> {
+        let earlier: Vec<String> = serde_json::from_str(&self.ancestor_hashes).unwrap_or_default();
+        self.parent_hash.iter().cloned().chain(earlier).collect()
+    }
+}
+
+/// Insertable node conflict
 #[derive(Insertable)]
-#[diesel(table_name = command_log)]
-pub struct NewCommandLog<'a> {
+#[diesel(table_name = node_conflicts)]
+pub struct NewNodeConflict<'a> {
+    pub node_change_id: &'a str,
+    pub base_hash: Option<&'a str>,
+    pub base_value: Option<&'a str>,
+    pub local_value: &'a str,
+    pub remote_value: &'a str,
+    pub resolution: Option<&'a str>,
+    pub detected_at: &'a str,
+    pub resolved_at: Option<&'a str>,
+}
+
+/// Queryable node conflict - a node edited differently here and in an applied patch.
+/// Values are node content JSON; `base_value` is None when the common ancestor isn't known.
+#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[diesel(table_name = node_conflicts)]
+pub struct NodeConflict {
+    pub id: i32,
+    pub node_change_id: String,
+    pub base_hash: Option<String>,
+    pub base_value: Option<String>,
+    pub local_value: String,
+    pub remote_value: String,
+    pub resolution: Option<String>,
+    pub detected_at: String,
+    pub resolved_at: Option<String>,
+}
+
+/// Insertable patch export record
+#[derive(Insertable)]
+#[diesel(table_name = patch_exports)]
+pub struct NewPatchExport<'a> {
+    pub path: &'a str,
+    pub exported_at: &'a str,
+    pub since: Option<&'a str>,
+    pub node_count: i32,
+    pub edge_count: i32,
+    pub filter: Option<&'a str>,
+}
+
+/// Queryable patch export record - one `deciduous diff export` run
+#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
+#[diesel(table_name = patch_exports)]
+pub struct PatchExport {
+    pub id: i32,
+    pub path: String,
+    /// Patch created_at; the next `--since-last` export starts here
+    pub exported_at: String,
+    pub since: Option<String>,
+    pub node_count: i32,
+    pub edge_count: i32,
+    /// Node/branch/tag filters the export used (JSON); None if it exported everything
+    pub filter: Option<String>,
+}
+
+/// Insertable applied patch record
+#[derive(Insertable)]
+#[diesel(table_name = applied_patches)]
+pub struct NewAppliedPatch<'a> {
+    pub digest: &'a str,
+    pub path: &'a str,
+    pub author: Option<&'a str>,
+    pub created_at: &'a str,
+    pub applied_at: &'a str,
+    pub nodes_added: i32,
+    pub nodes_updated: i32,
+    pub edges_added: i32,
+    pub nodes_failed: i32,
+    pub edges_failed: i32,
+}
+
+/// Queryable applied patch record - a patch file applied with `deciduous diff apply`
+#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
+#[diesel(table_name = applied_patches)]
+pub struct AppliedPatch {
+    /// Patch digest (computed for legacy patches that don't carry one)
+    pub digest: String,
+    pub path: String,
+    pub author: Option<String>,
+    /// The patch's own created_at
+    pub created_at: String,
+    /// Last time the patch was applied
+    pub applied_at: String,
+    pub nodes_added: i32,
+    pub nodes_updated: i32,
+    pub edges_added: i32,
+    pub nodes_failed: i32,
+    pub edges_failed: i32,
+}
+
+impl AppliedPatch {
+    /// Some nodes or edges failed the last time the patch was applied
+    pub fn is_partial(&self) -> bool {
+        self.nodes_failed > 0 || self.edges_failed > 0
+    }
+}
+
+/// Insertable record of a node or edge an applied patch created
+#[derive(Insertable)]
+#[diesel(table_name = applied_patch_items)]
+pub struct NewAppliedPatchItem<'a> {
+    pub patch_digest: &'a str,
+    /// "node" or "edge"
+    pub kind: &'a str,
+    /// Node change_id, or `edge_tombstone_key(..)` for edges
+    pub change_id: String,
+    pub from_change_id: Option<&'a str>,
+    pub to_change_id: Option<&'a str>,
+    pub edge_type: Option<&'a str>,
+}
+
+/// Queryable record of a node or edge an applied patch created
+#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
+#[diesel(table_name = applied_patch_items)]
+pub struct AppliedPatchItem {
+    pub id: i32,
+    pub patch_digest: String,
+    pub kind: String,
+    pub change_id: String,
+    pub from_change_id: Option<String>,
+    pub to_change_id: Option<String>,
+    pub edge_type: Option<String>,
+}
+
+/// What `Database::revert_patch` removed
+#[derive(Debug, Default, Clone)]
+pub struct RevertResult {
+    /// Nodes the patch created
+    pub nodes_removed: usize,
+    /// Edges the patch created
+    pub edges_removed: usize,
+    /// Edges added since that pointed at removed nodes
+    pub dependent_edges_removed: usize,
+}
+
+/// Tombstone kind for deleted nodes
+pub const TOMBSTONE_NODE: &str = "node";
+/// Tombstone kind for deleted edges
+pub const TOMBSTONE_EDGE: &str = "edge";
+
+/// Build the tombstone key for an edge (edges have no change_id of their own)
+pub fn edge_tombstone_key(from_change_id: &str, to_change_id: &str, edge_type: &str) -> String {
+    format!("{}->{}:{}", from_change_id, to_change_id, edge_type)
+}
+
+/// Insertable decision context
+#[derive(Insertable)]
+#[diesel(table_name = decision_context)]
+pub struct NewDecisionContext<'a> {
+    pub node_id: i32,
+    pub context_type: &'a str,
+    pub content_json: &'a str,
+    pub captured_at: &'a str,
+}
+
+/// Queryable decision context
+#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[diesel(table_name = decision_context)]
+pub struct DecisionContext {
+    pub id: i32,
+    pub node_id: i32,
+    /// file-snapshot, error, benchmark, url or snippet
+    pub context_type: String,
+    /// JSON object with the captured `content` and, for files, its `source` path
+    pub content_json: String,
+    pub captured_at: String,
+}
+
+impl DecisionContext {
+    fn content_field(&self, key: &str) -> Option<String> {
+        serde_json::from_str::<serde_json::Value>(&self.content_json)
+            .ok()?
+            .get(key)?
+            .as_str()
+            .map(String::from)
+    }
+
+    /// The captured text (error log, benchmark output, URL, ...)
+    pub fn content(&self) -> String {
+        self.content_field("content")
+            .unwrap_or_else(|| self.content_json.clone())
+    }
+
+    /// Where the content was read from, if it came from a file
+    pub fn source(&self) -> Option<String> {
+        self.content_field("source")
+    }
+}
+
+/// Build the JSON stored in `decision_context.content_json`
+pub fn build_context_json(content: &str, source: Option<&str>) -> String {
+    let mut value = json!({ "content": content });
+    if let Some(source) = source {
+        value["source"] = json!(source);
+    }
+    value.to_string()
+}
+
+/// Insertable session
+#[derive(Insertable)]
+#[diesel(table_name = decision_sessions)]
+pub struct NewDecisionSession<'a> {
+    pub name: Option<&'a str>,
+    pub started_at: &'a str,
+    pub ended_at: Option<&'a str>,
+    pub root_node_id: Option<i32>,
+    pub summary: Option<&'a str>,
+}
+
+/// Queryable session
+#[derive(Queryable, Selectable, Debug, Clone, serde::Serialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+#[cfg_attr(feature = "ts-rs", ts(export))]
+#[diesel(table_name = decision_sessions)]
+pub struct DecisionSession {
+    pub id: i32,
+    pub name: Option<String>,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub root_node_id: Option<i32>,
+    pub summary: Option<String>,
+}
+
+impl DecisionSession {
+    /// Whether the session is still collecting new nodes
+    pub fn is_open(&self) -> bool {
+        self.ended_at.is_none()
+    }
+}
+
+/// Insertable session membership
+#[derive(Insertable)]
+#[diesel(table_name = session_nodes)]
+pub struct NewSessionNode<'a> {
+    pub session_id: i32,
+    pub node_id: i32,
+    pub added_at: &'a str,
+}
+
+// ============================================================================
+// Command Log Models
+// ============================================================================
+
+/// Insertable command log entry
+#[derive(Insertable)]
+#[diesel(table_name = command_log)]
+pub struct NewCommandLog<'a> {
     pub command: &'a str,
     pub description: Option<&'a str>,
     pub working_dir: Option<&'a str>,
@@ -346,6 +1226,75 @@ pub struct CommandLog {
     pub decision_node_id: Option<i32>,
 }
 
+impl CommandLog {
+    /// Whether the command finished with exit code 0
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Bytes of stdout/stderr kept per logged command
+pub const COMMAND_OUTPUT_LIMIT: usize = 16 * 1024;
+
+/// Keep the last `limit` bytes of command output, where failures usually
+/// show up, noting how much was dropped
+pub fn truncate_command_output(output: &str, limit: usize) -> String {
+    if output.len() <= limit {
+        return output.to_string();
+    }
+    let mut start = output.len() - limit;
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("[… {} bytes truncated]\n{}", start, &output[start..])
+}
+
+/// The last bytes of a stream of command output, so output of any size is captured
+/// in bounded memory
+#[derive(Debug)]
+pub struct OutputTail {
+    limit: usize,
+    tail: std::collections::VecDeque<u8>,
+    dropped: usize,
+}
+
+impl OutputTail {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            tail: std::collections::VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.tail.extend(bytes);
+        let excess = self.tail.len().saturating_sub(self.limit);
+        self.tail.drain(..excess);
+        self.dropped += excess;
+    }
+
+    /// The captured text, marked like [`truncate_command_output`] if anything was
+    /// dropped. The marker fits within the limit, so storing it doesn't truncate again.
+    pub fn into_string(self) -> String {
+        let bytes = Vec::from(self.tail);
+        if self.dropped == 0 {
+            return String::from_utf8_lossy(&bytes).into_owned();
+        }
+        let marker_len = format!("[… {} bytes truncated]\n", self.dropped + bytes.len()).len();
+        let mut start = (bytes.len() + marker_len).saturating_sub(self.limit);
+        // Don't start in the middle of a character
+        while bytes.get(start).is_some_and(|b| b & 0xC0 == 0x80) {
+            start += 1;
+        }
+        format!(
+            "[… {} bytes truncated]\n{}",
+            self.dropped + start,
+            String::from_utf8_lossy(&bytes[start..])
+        )
+    }
+}
+
 // ============================================================================
 // Roadmap Board Models
 // ============================================================================
@@ -555,47 +1504,6 @@ pub struct GitHubIssueCache {
     pub cached_at: String,
 }
 
-// ============================================================================
-// Helper structs for raw SQL queries
-// ============================================================================
-
-/// Helper for PRAGMA table_info queries
-#[derive(QueryableByName, Debug)]
-struct PragmaTableInfo {
-    #[diesel(sql_type = diesel::sql_types::Integer)]
-    #[allow(dead_code)]
-    cid: i32,
-    #[diesel(sql_type = diesel::sql_types::Text)]
-    name: String,
-    #[diesel(sql_type = diesel::sql_types::Text)]
-    #[allow(dead_code)]
-    r#type: String,
-    #[diesel(sql_type = diesel::sql_types::Integer)]
-    #[allow(dead_code)]
-    notnull: i32,
-    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
-    #[allow(dead_code)]
-    dflt_value: Option<String>,
-    #[diesel(sql_type = diesel::sql_types::Integer)]
-    #[allow(dead_code)]
-    pk: i32,
-}
-
-/// Helper for node ID queries
-#[derive(QueryableByName, Debug)]
-struct NodeIdOnly {
-    #[diesel(sql_type = diesel::sql_types::Integer)]
-    id: i32,
-}
-
-/// Helper for sqlite_master table queries
-#[derive(QueryableByName, Debug)]
-#[allow(dead_code)]
-struct TableInfo {
-    #[diesel(sql_type = diesel::sql_types::Text)]
-    name: String,
-}
-
 // ============================================================================
 // Database Connection
 // ============================================================================
@@ -606,6 +1514,10 @@ type DbConn = PooledConnection<ConnectionManager<SqliteConnection>>;
 /// Database connection wrapper with connection pool
 pub struct Database {
     pool: DbPool,
+    /// Project-specific node/edge types and statuses accepted alongside the built-ins
+    custom_types: TypesConfig,
+    /// Recorded as the author of nodes and edges created through this handle
+    author: Option<String>,
 }
 
 /// Error type for database operations
@@ -655,7 +1567,8 @@ impl Database {
         Self::open_at(path)
     }
 
-    /// Open database at default path (respects DECIDUOUS_DB_PATH env var)
+    /// Open database at default path (respects DECIDUOUS_DB_PATH env var).
+    /// Extra types registered in `.deciduous/config.toml` are accepted.
     pub fn open() -> Result<Self> {
         let path = get_db_path();
         // Create parent directory if it doesn't exist
@@ -664,96 +1577,109 @@ impl Database {
                 std::fs::create_dir_all(parent).ok();
             }
         }
-        Self::open_at(&path)
+        let db = Self::open_at(&path)?;
+        let config = crate::config::Config::load();
+        let author = resolve_author(&config.author);
+        Ok(db.with_custom_types(config.types).with_author(author))
     }
 
-    /// Open database at specified path
-    pub fn open_at<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let path_str = path.as_ref().to_string_lossy().to_string();
-        let manager = ConnectionManager::<SqliteConnection>::new(&path_str);
-        let pool = Pool::builder()
-            .max_size(5)
-            .build(manager)
-            .map_err(|e| DbError::Connection(e.to_string()))?;
-
-        let db = Self { pool };
-        // Auto-migrate FIRST - add change_id columns to existing databases before init_schema creates new tables
-        let _ = db.migrate_add_change_ids_raw();
-        db.init_schema()?;
-        Ok(db)
+    /// Accept project-specific node/edge types and statuses in addition to the built-ins
+    pub fn with_custom_types(mut self, types: TypesConfig) -> Self {
+        self.custom_types = types;
+        self
     }
 
-    /// Raw SQL migration that runs before Diesel ORM is used
-    fn migrate_add_change_ids_raw(&self) -> Result<bool> {
-        let mut conn = self.get_conn()?;
-
-        // Check if decision_nodes table exists
-        let tables: Vec<TableInfo> = diesel::sql_query(
-            "SELECT name FROM sqlite_master WHERE type='table' AND name='decision_nodes'",
-        )
-        .load::<TableInfo>(&mut conn)
-        .unwrap_or_default();
-
-        if tables.is_empty() {
-            return Ok(false); // Table doesn't exist yet, init_schema will create it
-        }
-
-        // Check if change_id column exists in decision_nodes
-        let columns: Vec<PragmaTableInfo> = diesel::sql_query("PRAGMA table_info(decision_nodes)")
-            .load(&mut conn)
-            .unwrap_or_default();
+    /// Record `author` on the nodes and edges this handle creates
+    pub fn with_author(mut self, author: Option<String>) -> Self {
+        self.author = author;
+        self
+    }
 
-        let has_change_id = columns.iter().any(|c| c.name == "change_id");
+    /// Author recorded on new nodes and edges
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
 
-        if !has_change_id {
-            // Add change_id column to decision_nodes
-            diesel::sql_query("ALTER TABLE decision_nodes ADD COLUMN change_id TEXT")
-                .execute(&mut conn)?;
+    /// Check that a node type is built-in or registered in config
+    pub fn validate_node_type(&self, node_type: &str) -> Result<()> {
+        if node_type.parse::<NodeType>().is_ok()
+            || self.custom_types.node.iter().any(|t| t == node_type)
+        {
+            return Ok(());
         }
+        Err(unknown_value_error(
+            "node type",
+            node_type,
+            NodeType::ALL.map(|t| t.as_str()),
+            &self.custom_types.node,
+        ))
+    }
 
-        // Always backfill any NULL change_ids (handles both new columns and stragglers)
-        let nodes: Vec<NodeIdOnly> =
-            diesel::sql_query("SELECT id FROM decision_nodes WHERE change_id IS NULL")
-                .load(&mut conn)
-                .unwrap_or_default();
-
-        if nodes.is_empty() && has_change_id {
-            return Ok(false); // Already fully migrated
+    /// Check that an edge type is built-in or registered in config
+    pub fn validate_edge_type(&self, edge_type: &str) -> Result<()> {
+        if edge_type.parse::<EdgeType>().is_ok()
+            || self.custom_types.edge.iter().any(|t| t == edge_type)
+        {
+            return Ok(());
         }
+        Err(unknown_value_error(
+            "edge type",
+            edge_type,
+            EdgeType::ALL.map(|t| t.as_str()),
+            &self.custom_types.edge,
+        ))
+    }
 
-        for node in nodes {
-            let change_id = Uuid::new_v4().to_string();
-            diesel::sql_query(format!(
-                "UPDATE decision_nodes SET change_id = '{}' WHERE id = {}",
-                change_id, node.id
-            ))
-            .execute(&mut conn)?;
+    /// Check that a status is built-in or registered in config
+    pub fn validate_status(&self, status: &str) -> Result<()> {
+        if status.parse::<NodeStatus>().is_ok()
+            || self.custom_types.status.iter().any(|t| t == status)
+        {
+            return Ok(());
         }
+        Err(unknown_value_error(
+            "status",
+            status,
+            NodeStatus::ALL.map(|t| t.as_str()),
+            &self.custom_types.status,
+        ))
+    }
 
-        // Check if edge columns need migration
-        let edge_columns: Vec<PragmaTableInfo> =
-            diesel::sql_query("PRAGMA table_info(decision_edges)")
-                .load(&mut conn)
-                .unwrap_or_default();
-
-        let has_from_change_id = edge_columns.iter().any(|c| c.name == "from_change_id");
-
-        if !has_from_change_id {
-            diesel::sql_query("ALTER TABLE decision_edges ADD COLUMN from_change_id TEXT")
-                .execute(&mut conn)?;
-            diesel::sql_query("ALTER TABLE decision_edges ADD COLUMN to_change_id TEXT")
-                .execute(&mut conn)?;
+    /// Open database at specified path, applying any pending migrations.
+    /// Fails if the database was migrated by a newer deciduous, or rolled back with
+    /// `deciduous migrate` and not migrated up again.
+    pub fn open_at<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let db = Self::connect_at(path)?;
+        migrations::check_not_pinned(&mut *db.get_conn()?)?;
+        db.migrate_to(migrations::latest_version())?;
+        db.register_schema(&CURRENT_SCHEMA)?;
+        Ok(db)
+    }
 
-            // Backfill edge change_ids
-            diesel::sql_query(
-                "UPDATE decision_edges SET
-                    from_change_id = (SELECT change_id FROM decision_nodes WHERE id = decision_edges.from_node_id),
-                    to_change_id = (SELECT change_id FROM decision_nodes WHERE id = decision_edges.to_node_id)"
-            )
-            .execute(&mut conn)?;
+    /// Open database at default path without running migrations (for `deciduous migrate`)
+    pub fn open_unmigrated() -> Result<Self> {
+        let path = get_db_path();
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                std::fs::create_dir_all(parent).ok();
+            }
         }
+        Self::connect_at(&path)
+    }
+
+    fn connect_at<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path_str = path.as_ref().to_string_lossy().to_string();
+        let manager = ConnectionManager::<SqliteConnection>::new(&path_str);
+        let pool = Pool::builder()
+            .max_size(5)
+            .build(manager)
+            .map_err(|e| DbError::Connection(e.to_string()))?;
 
-        Ok(true) // Migration performed
+        Ok(Self {
+            pool,
+            custom_types: TypesConfig::default(),
+            author: None,
+        })
     }
 
     fn get_conn(&self) -> Result<DbConn> {
@@ -762,342 +1688,45 @@ impl Database {
             .map_err(|e| DbError::Connection(e.to_string()))
     }
 
-    fn init_schema(&self) -> Result<()> {
-        let mut conn = self.get_conn()?;
+    /// Applied and pending schema migrations
+    pub fn migration_status(&self) -> Result<MigrationStatus> {
+        migrations::status(&mut *self.get_conn()?)
+    }
 
-        // Run raw SQL to create tables if they don't exist
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS schema_versions (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                version TEXT NOT NULL UNIQUE,
-                name TEXT NOT NULL,
-                features TEXT NOT NULL,
-                introduced_at TEXT NOT NULL
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
+    /// Apply or revert migrations until the database is at migration `target`
+    pub fn migrate_to(&self, target: u32) -> Result<Vec<MigrationStep>> {
+        migrations::migrate_to(&mut *self.get_conn()?, target)
+    }
 
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS decision_nodes (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                change_id TEXT NOT NULL UNIQUE,
-                node_type TEXT NOT NULL,
-                title TEXT NOT NULL,
-                description TEXT,
-                status TEXT NOT NULL DEFAULT 'pending',
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                metadata_json TEXT
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
+    /// Migrate to `target` for `deciduous migrate --to`. Below the latest migration
+    /// the database stays pinned there instead of being upgraded on the next open.
+    pub fn migrate_pinned(&self, target: u32) -> Result<Vec<MigrationStep>> {
+        migrations::migrate_pinned(&mut *self.get_conn()?, target)
+    }
 
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS decision_edges (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                from_node_id INTEGER NOT NULL,
-                to_node_id INTEGER NOT NULL,
-                from_change_id TEXT,
-                to_change_id TEXT,
-                edge_type TEXT NOT NULL,
-                weight REAL DEFAULT 1.0,
-                rationale TEXT,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY (from_node_id) REFERENCES decision_nodes(id),
-                FOREIGN KEY (to_node_id) REFERENCES decision_nodes(id),
-                UNIQUE(from_node_id, to_node_id, edge_type)
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
+    /// Revert the most recently applied migration and pin the database there
+    pub fn migrate_down(&self) -> Result<Option<MigrationStep>> {
+        migrations::rollback_last(&mut *self.get_conn()?)
+    }
 
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS decision_context (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                node_id INTEGER NOT NULL,
-                context_type TEXT NOT NULL,
-                content_json TEXT NOT NULL,
-                captured_at TEXT NOT NULL,
-                FOREIGN KEY (node_id) REFERENCES decision_nodes(id)
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
+    fn register_schema(&self, schema: &DecisionSchema) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+        let features_json = serde_json::to_string(&schema.features).unwrap_or_default();
 
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS decision_sessions (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                name TEXT,
-                started_at TEXT NOT NULL,
-                ended_at TEXT,
-                root_node_id INTEGER,
-                summary TEXT,
-                FOREIGN KEY (root_node_id) REFERENCES decision_nodes(id)
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
+        let new_schema = NewSchemaVersion {
+            version: &schema.version_string(),
+            name: schema.name,
+            features: &features_json,
+            introduced_at: &now,
+        };
 
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS session_nodes (
-                session_id INTEGER NOT NULL,
-                node_id INTEGER NOT NULL,
-                added_at TEXT NOT NULL,
-                PRIMARY KEY (session_id, node_id),
-                FOREIGN KEY (session_id) REFERENCES decision_sessions(id),
-                FOREIGN KEY (node_id) REFERENCES decision_nodes(id)
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
+        diesel::insert_or_ignore_into(schema_versions::table)
+            .values(&new_schema)
+            .execute(&mut conn)?;
 
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS command_log (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                command TEXT NOT NULL,
-                description TEXT,
-                working_dir TEXT,
-                exit_code INTEGER,
-                stdout TEXT,
-                stderr TEXT,
-                started_at TEXT NOT NULL,
-                completed_at TEXT,
-                duration_ms INTEGER,
-                decision_node_id INTEGER,
-                FOREIGN KEY (decision_node_id) REFERENCES decision_nodes(id)
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
-
-        // Roadmap Board Tables
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS roadmap_items (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                change_id TEXT NOT NULL UNIQUE,
-                title TEXT NOT NULL,
-                description TEXT,
-                section TEXT,
-                parent_id INTEGER,
-                checkbox_state TEXT NOT NULL DEFAULT 'none',
-                github_issue_number INTEGER,
-                github_issue_state TEXT,
-                outcome_node_id INTEGER,
-                outcome_change_id TEXT,
-                markdown_line_start INTEGER,
-                markdown_line_end INTEGER,
-                content_hash TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                last_synced_at TEXT,
-                FOREIGN KEY (parent_id) REFERENCES roadmap_items(id),
-                FOREIGN KEY (outcome_node_id) REFERENCES decision_nodes(id)
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
-
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS roadmap_sync_state (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                roadmap_path TEXT NOT NULL,
-                roadmap_content_hash TEXT,
-                github_repo TEXT,
-                last_github_sync TEXT,
-                last_markdown_parse TEXT,
-                conflict_count INTEGER NOT NULL DEFAULT 0
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
-
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS roadmap_conflicts (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                item_change_id TEXT NOT NULL,
-                conflict_type TEXT NOT NULL,
-                local_value TEXT,
-                remote_value TEXT,
-                resolution TEXT,
-                detected_at TEXT NOT NULL,
-                resolved_at TEXT,
-                FOREIGN KEY (item_change_id) REFERENCES roadmap_items(change_id)
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
-
-        // GitHub issue cache for TUI/Web display
-        diesel::sql_query(
-            r#"
-            CREATE TABLE IF NOT EXISTS github_issue_cache (
-                id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
-                issue_number INTEGER NOT NULL,
-                repo TEXT NOT NULL,
-                title TEXT NOT NULL,
-                body TEXT,
-                state TEXT NOT NULL,
-                html_url TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                cached_at TEXT NOT NULL,
-                UNIQUE(repo, issue_number)
-            )
-        "#,
-        )
-        .execute(&mut conn)?;
-
-        // Create indexes
-        diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_nodes_type ON decision_nodes(node_type)")
-            .execute(&mut conn)?;
-        diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_nodes_status ON decision_nodes(status)")
-            .execute(&mut conn)?;
-        diesel::sql_query(
-            "CREATE INDEX IF NOT EXISTS idx_nodes_change_id ON decision_nodes(change_id)",
-        )
-        .execute(&mut conn)?;
-        diesel::sql_query(
-            "CREATE INDEX IF NOT EXISTS idx_edges_from ON decision_edges(from_node_id)",
-        )
-        .execute(&mut conn)?;
-        diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_edges_to ON decision_edges(to_node_id)")
-            .execute(&mut conn)?;
-        diesel::sql_query(
-            "CREATE INDEX IF NOT EXISTS idx_edges_from_change ON decision_edges(from_change_id)",
-        )
-        .execute(&mut conn)?;
-        diesel::sql_query(
-            "CREATE INDEX IF NOT EXISTS idx_edges_to_change ON decision_edges(to_change_id)",
-        )
-        .execute(&mut conn)?;
-        diesel::sql_query(
-            "CREATE INDEX IF NOT EXISTS idx_command_started_at ON command_log(started_at)",
-        )
-        .execute(&mut conn)?;
-
-        // Roadmap indexes
-        diesel::sql_query(
-            "CREATE INDEX IF NOT EXISTS idx_roadmap_items_change_id ON roadmap_items(change_id)",
-        )
-        .execute(&mut conn)?;
-        diesel::sql_query(
-            "CREATE INDEX IF NOT EXISTS idx_roadmap_items_section ON roadmap_items(section)",
-        )
-        .execute(&mut conn)?;
-        diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_roadmap_items_github_issue ON roadmap_items(github_issue_number)").execute(&mut conn)?;
-        diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_roadmap_items_outcome ON roadmap_items(outcome_change_id)").execute(&mut conn)?;
-        diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_roadmap_conflicts_item ON roadmap_conflicts(item_change_id)").execute(&mut conn)?;
-        diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_github_issue_cache_repo ON github_issue_cache(repo, issue_number)").execute(&mut conn)?;
-
-        // Register current schema
-        self.register_schema(&CURRENT_SCHEMA)?;
-        Ok(())
-    }
-
-    fn register_schema(&self, schema: &DecisionSchema) -> Result<()> {
-        let mut conn = self.get_conn()?;
-        let now = chrono::Local::now().to_rfc3339();
-        let features_json = serde_json::to_string(&schema.features).unwrap_or_default();
-
-        let new_schema = NewSchemaVersion {
-            version: &schema.version_string(),
-            name: schema.name,
-            features: &features_json,
-            introduced_at: &now,
-        };
-
-        diesel::insert_or_ignore_into(schema_versions::table)
-            .values(&new_schema)
-            .execute(&mut conn)?;
-
-        Ok(())
-    }
-
-    /// Migrate existing database to add change_id columns if missing
-    pub fn migrate_add_change_ids(&self) -> Result<bool> {
-        let mut conn = self.get_conn()?;
-
-        // Check if change_id column exists in decision_nodes
-        let columns: Vec<(String,)> = diesel::sql_query("PRAGMA table_info(decision_nodes)")
-            .load::<PragmaTableInfo>(&mut conn)
-            .map(|rows| rows.into_iter().map(|r| (r.name,)).collect())
-            .unwrap_or_default();
-
-        let has_change_id = columns.iter().any(|(name,)| name == "change_id");
-
-        if has_change_id {
-            return Ok(false); // Already migrated
-        }
-
-        // Add change_id column to decision_nodes
-        diesel::sql_query("ALTER TABLE decision_nodes ADD COLUMN change_id TEXT")
-            .execute(&mut conn)?;
-
-        // Backfill change_id with UUIDs for existing nodes
-        let nodes: Vec<(i32,)> =
-            diesel::sql_query("SELECT id FROM decision_nodes WHERE change_id IS NULL")
-                .load::<NodeIdOnly>(&mut conn)
-                .map(|rows| rows.into_iter().map(|r| (r.id,)).collect())
-                .unwrap_or_default();
-
-        for (node_id,) in nodes {
-            let change_id = Uuid::new_v4().to_string();
-            diesel::sql_query(format!(
-                "UPDATE decision_nodes SET change_id = '{}' WHERE id = {}",
-                change_id, node_id
-            ))
-            .execute(&mut conn)?;
-        }
-
-        // Create unique index on change_id
-        diesel::sql_query("CREATE UNIQUE INDEX IF NOT EXISTS idx_nodes_change_id_unique ON decision_nodes(change_id)")
-            .execute(&mut conn)?;
-
-        // Add from_change_id and to_change_id columns to decision_edges
-        let edge_columns: Vec<(String,)> = diesel::sql_query("PRAGMA table_info(decision_edges)")
-            .load::<PragmaTableInfo>(&mut conn)
-            .map(|rows| rows.into_iter().map(|r| (r.name,)).collect())
-            .unwrap_or_default();
-
-        let has_from_change_id = edge_columns.iter().any(|(name,)| name == "from_change_id");
-
-        if !has_from_change_id {
-            diesel::sql_query("ALTER TABLE decision_edges ADD COLUMN from_change_id TEXT")
-                .execute(&mut conn)?;
-            diesel::sql_query("ALTER TABLE decision_edges ADD COLUMN to_change_id TEXT")
-                .execute(&mut conn)?;
-
-            // Backfill edge change_ids from node change_ids
-            diesel::sql_query(
-                "UPDATE decision_edges SET
-                    from_change_id = (SELECT change_id FROM decision_nodes WHERE id = decision_edges.from_node_id),
-                    to_change_id = (SELECT change_id FROM decision_nodes WHERE id = decision_edges.to_node_id)"
-            )
-            .execute(&mut conn)?;
-
-            // Create indexes
-            diesel::sql_query("CREATE INDEX IF NOT EXISTS idx_edges_from_change ON decision_edges(from_change_id)")
-                .execute(&mut conn)?;
-            diesel::sql_query(
-                "CREATE INDEX IF NOT EXISTS idx_edges_to_change ON decision_edges(to_change_id)",
-            )
-            .execute(&mut conn)?;
-        }
-
-        Ok(true) // Migration performed
-    }
+        Ok(())
+    }
 
     // ========================================================================
     // Decision Graph Operations
@@ -1136,6 +1765,7 @@ impl Database {
         files: Option<&str>,
         branch: Option<&str>,
     ) -> Result<i32> {
+        self.validate_node_type(node_type)?;
         let mut conn = self.get_conn()?;
         let now = chrono::Local::now().to_rfc3339();
         let change_id = Uuid::new_v4().to_string();
@@ -1152,6 +1782,7 @@ impl Database {
             created_at: &now,
             updated_at: &now,
             metadata_json: metadata.as_deref(),
+            author: self.author.as_deref(),
         };
 
         diesel::insert_into(decision_nodes::table)
@@ -1163,6 +1794,7 @@ impl Database {
         ))
         .first(&mut conn)?;
 
+        attach_to_open_session(&mut conn, id, &now)?;
         Ok(id)
     }
 
@@ -1178,34 +1810,23 @@ impl Database {
         self.create_node(node_type, title, description, confidence, commit)
     }
 
-    /// Create a node with a specific change_id (for patch application)
-    pub fn create_node_with_change_id(
-        &self,
-        change_id: &str,
-        node_type: &str,
-        title: &str,
-        description: Option<&str>,
-        confidence: Option<u8>,
-        commit: Option<&str>,
-        prompt: Option<&str>,
-        files: Option<&str>,
-        branch: Option<&str>,
-    ) -> Result<i32> {
+    /// Insert a node exactly as given (change_id, status, timestamps and raw metadata),
+    /// e.g. from a patch. `node.id` is ignored; returns the new local ID.
+    pub fn import_node(&self, node: &DecisionNode) -> Result<i32> {
+        self.validate_node_type(&node.node_type)?;
+        self.validate_status(&node.status)?;
         let mut conn = self.get_conn()?;
-        let now = chrono::Local::now().to_rfc3339();
-
-        // Build metadata JSON with all optional fields
-        let metadata = build_metadata_json(confidence, commit, prompt, files, branch);
 
         let new_node = NewDecisionNode {
-            change_id,
-            node_type,
-            title,
-            description,
-            status: "pending",
-            created_at: &now,
-            updated_at: &now,
-            metadata_json: metadata.as_deref(),
+            change_id: &node.change_id,
+            node_type: &node.node_type,
+            title: &node.title,
+            description: node.description.as_deref(),
+            status: &node.status,
+            created_at: &node.created_at,
+            updated_at: &node.updated_at,
+            metadata_json: node.metadata_json.as_deref(),
+            author: node.author.as_deref(),
         };
 
         diesel::insert_into(decision_nodes::table)
@@ -1228,6 +1849,19 @@ impl Database {
         edge_type: &str,
         rationale: Option<&str>,
     ) -> Result<i32> {
+        self.create_edge_by(from_id, to_id, edge_type, rationale, self.author())
+    }
+
+    /// Create an edge recording a specific author (e.g. from a patch)
+    pub fn create_edge_by(
+        &self,
+        from_id: i32,
+        to_id: i32,
+        edge_type: &str,
+        rationale: Option<&str>,
+        author: Option<&str>,
+    ) -> Result<i32> {
+        self.validate_edge_type(edge_type)?;
         let mut conn = self.get_conn()?;
 
         // Validate both nodes exist and get their change_ids
@@ -1271,6 +1905,7 @@ impl Database {
             weight: Some(1.0),
             rationale,
             created_at: &now,
+            author,
         };
 
         diesel::insert_into(decision_edges::table)
@@ -1298,6 +1933,7 @@ impl Database {
 
     /// Update node status
     pub fn update_node_status(&self, node_id: i32, status: &str) -> Result<()> {
+        self.validate_status(status)?;
         let mut conn = self.get_conn()?;
         let now = chrono::Local::now().to_rfc3339();
 
@@ -1311,124 +1947,850 @@ impl Database {
         Ok(())
     }
 
-    /// Update a node's commit hash in metadata_json
-    pub fn update_node_commit(&self, node_id: i32, commit_hash: &str) -> Result<()> {
+    /// Update a node's commit hash in metadata_json
+    pub fn update_node_commit(&self, node_id: i32, commit_hash: &str) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+
+        // Get current metadata
+        let current_meta: Option<String> = decision_nodes::table
+            .filter(decision_nodes::id.eq(node_id))
+            .select(decision_nodes::metadata_json)
+            .first(&mut conn)?;
+
+        let mut meta = NodeMetadata::from_option(current_meta.as_deref());
+        meta.commit = Some(commit_hash.to_string());
+        let new_meta = meta.to_json();
+
+        diesel::update(decision_nodes::table.filter(decision_nodes::id.eq(node_id)))
+            .set((
+                decision_nodes::metadata_json.eq(new_meta),
+                decision_nodes::updated_at.eq(&now),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    /// Update a node's prompt in metadata_json
+    pub fn update_node_prompt(&self, node_id: i32, prompt: &str) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+
+        // Get current metadata
+        let current_meta: Option<String> = decision_nodes::table
+            .filter(decision_nodes::id.eq(node_id))
+            .select(decision_nodes::metadata_json)
+            .first(&mut conn)?;
+
+        let mut meta = NodeMetadata::from_option(current_meta.as_deref());
+        meta.prompt = Some(prompt.to_string());
+        let new_meta = meta.to_json();
+
+        diesel::update(decision_nodes::table.filter(decision_nodes::id.eq(node_id)))
+            .set((
+                decision_nodes::metadata_json.eq(new_meta),
+                decision_nodes::updated_at.eq(&now),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    /// Add/remove tags and set/remove attributes on a node. Returns the updated metadata.
+    pub fn edit_labels(&self, node_id: i32, edits: &[LabelEdit]) -> Result<NodeMetadata> {
+        let node = self.get_node(node_id)?.ok_or_else(|| {
+            DbError::Validation(format!(
+                "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                node_id
+            ))
+        })?;
+
+        let mut meta = node.metadata();
+        for edit in edits {
+            meta.apply_label_edit(edit);
+        }
+
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+        diesel::update(decision_nodes::table.filter(decision_nodes::id.eq(node_id)))
+            .set((
+                decision_nodes::metadata_json.eq(meta.to_json()),
+                decision_nodes::updated_at.eq(&now),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(meta)
+    }
+
+    /// Update any combination of a node's title, description, type and metadata fields.
+    ///
+    /// `None` leaves a field untouched. An empty string clears the description,
+    /// files or branch, and `Some(None)` clears the confidence. Other metadata
+    /// keys (commit, prompt, ...) are preserved.
+    pub fn update_node(
+        &self,
+        node_id: i32,
+        title: Option<&str>,
+        description: Option<&str>,
+        node_type: Option<&str>,
+        confidence: Option<Option<u8>>,
+        files: Option<&str>,
+        branch: Option<&str>,
+    ) -> Result<()> {
+        if let Some(t) = node_type {
+            self.validate_node_type(t)?;
+        }
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+
+        let node = decision_nodes::table
+            .filter(decision_nodes::id.eq(node_id))
+            .first::<DecisionNode>(&mut conn)
+            .optional()?
+            .ok_or_else(|| {
+                DbError::Validation(format!(
+                    "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                    node_id
+                ))
+            })?;
+
+        if title.is_some_and(|t| t.trim().is_empty()) {
+            return Err(DbError::Validation("Title cannot be empty".to_string()));
+        }
+
+        let mut meta = node.metadata();
+        if let Some(c) = confidence {
+            meta.confidence = c.map(|c| c.min(100));
+        }
+        if let Some(f) = files {
+            meta.set_files_csv(f);
+        }
+        if let Some(b) = branch {
+            meta.branch = Some(b.to_string()).filter(|b| !b.is_empty());
+        }
+        let new_meta = meta.to_json();
+
+        let new_description = match description {
+            Some("") => None,
+            Some(d) => Some(d.to_string()),
+            None => node.description,
+        };
+
+        diesel::update(decision_nodes::table.filter(decision_nodes::id.eq(node_id)))
+            .set((
+                decision_nodes::title.eq(title.unwrap_or(&node.title)),
+                decision_nodes::description.eq(new_description),
+                decision_nodes::node_type.eq(node_type.unwrap_or(&node.node_type)),
+                decision_nodes::metadata_json.eq(new_meta),
+                decision_nodes::updated_at.eq(&now),
+            ))
+            .execute(&mut conn)?;
+
+        Ok(())
+    }
+
+    /// Replace a node's content (type, title, description, status, metadata and
+    /// updated_at) with `node`'s, matched by `node.id`. Used when syncing a newer
+    /// copy of the node from a patch, so the timestamp is taken as given.
+    pub fn overwrite_node(&self, node: &DecisionNode) -> Result<()> {
+        self.validate_node_type(&node.node_type)?;
+        self.validate_status(&node.status)?;
+        let mut conn = self.get_conn()?;
+
+        let updated = diesel::update(decision_nodes::table.filter(decision_nodes::id.eq(node.id)))
+            .set((
+                decision_nodes::node_type.eq(&node.node_type),
+                decision_nodes::title.eq(&node.title),
+                decision_nodes::description.eq(&node.description),
+                decision_nodes::status.eq(&node.status),
+                decision_nodes::metadata_json.eq(&node.metadata_json),
+                decision_nodes::updated_at.eq(&node.updated_at),
+            ))
+            .execute(&mut conn)?;
+
+        if updated == 0 {
+            return Err(DbError::Validation(format!(
+                "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                node.id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get a single node by ID
+    pub fn get_node(&self, node_id: i32) -> Result<Option<DecisionNode>> {
+        let mut conn = self.get_conn()?;
+        let node = decision_nodes::table
+            .filter(decision_nodes::id.eq(node_id))
+            .first::<DecisionNode>(&mut conn)
+            .optional()?;
+        Ok(node)
+    }
+
+    /// Get all nodes
+    pub fn get_all_nodes(&self) -> Result<Vec<DecisionNode>> {
+        let mut conn = self.get_conn()?;
+        let nodes = decision_nodes::table
+            .order(decision_nodes::created_at.asc())
+            .load::<DecisionNode>(&mut conn)?;
+        Ok(nodes)
+    }
+
+    /// Full-text search over node title, description, prompt and files.
+    ///
+    /// Each word in `query` must match (prefix matching on the last word, stemmed
+    /// otherwise). Hits are ordered best first; snippets wrap matches in `[` `]`.
+    pub fn search(&self, query: &str, filters: &SearchFilters) -> Result<Vec<SearchHit>> {
+        let Some(fts_query) = fts5_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut conn = self.get_conn()?;
+        let rows: Vec<SearchRow> = diesel::sql_query(
+            "SELECT rowid AS id,
+                    bm25(decision_nodes_fts, 10.0, 4.0, 2.0, 1.0) AS rank,
+                    snippet(decision_nodes_fts, -1, '[', ']', '...', 12) AS snippet
+             FROM decision_nodes_fts
+             WHERE decision_nodes_fts MATCH ?
+             ORDER BY rank",
+        )
+        .bind::<diesel::sql_types::Text, _>(&fts_query)
+        .load(&mut conn)?;
+
+        let ids: Vec<i32> = rows.iter().map(|r| r.id).collect();
+        let mut nodes: std::collections::HashMap<i32, DecisionNode> = decision_nodes::table
+            .filter(decision_nodes::id.eq_any(&ids))
+            .load::<DecisionNode>(&mut conn)?
+            .into_iter()
+            .map(|n| (n.id, n))
+            .collect();
+
+        let hits = rows
+            .into_iter()
+            .filter_map(|row| {
+                let node = nodes.remove(&row.id)?;
+                filters.matches(&node).then(|| SearchHit {
+                    node,
+                    score: -row.rank,
+                    snippet: row.snippet.unwrap_or_default(),
+                })
+            })
+            .take(filters.limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok(hits)
+    }
+
+    /// Get all edges
+    pub fn get_all_edges(&self) -> Result<Vec<DecisionEdge>> {
+        let mut conn = self.get_conn()?;
+        let edges = decision_edges::table
+            .order(decision_edges::created_at.asc())
+            .load::<DecisionEdge>(&mut conn)?;
+        Ok(edges)
+    }
+
+    /// Get children of a node (outgoing edges)
+    pub fn get_node_children(&self, node_id: i32) -> Result<Vec<DecisionNode>> {
+        let mut conn = self.get_conn()?;
+
+        let child_ids: Vec<i32> = decision_edges::table
+            .filter(decision_edges::from_node_id.eq(node_id))
+            .select(decision_edges::to_node_id)
+            .load(&mut conn)?;
+
+        let children = decision_nodes::table
+            .filter(decision_nodes::id.eq_any(child_ids))
+            .load::<DecisionNode>(&mut conn)?;
+
+        Ok(children)
+    }
+
+    /// Get parents of a node (incoming edges)
+    pub fn get_node_parents(&self, node_id: i32) -> Result<Vec<DecisionNode>> {
+        let mut conn = self.get_conn()?;
+
+        let parent_ids: Vec<i32> = decision_edges::table
+            .filter(decision_edges::to_node_id.eq(node_id))
+            .select(decision_edges::from_node_id)
+            .load(&mut conn)?;
+
+        let parents = decision_nodes::table
+            .filter(decision_nodes::id.eq_any(parent_ids))
+            .load::<DecisionNode>(&mut conn)?;
+
+        Ok(parents)
+    }
+
+    // ========================================================================
+    // Deletion and Tombstones
+    // ========================================================================
+
+    /// Delete a node and its incident edges, recording tombstones for sync.
+    ///
+    /// With `cascade`, descendants that are only reachable through the deleted
+    /// node are removed too; descendants with another surviving parent are kept.
+    pub fn delete_node(&self, node_id: i32, cascade: bool) -> Result<DeleteResult> {
+        if self.get_node(node_id)?.is_none() {
+            return Err(DbError::Validation(format!(
+                "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                node_id
+            )));
+        }
+
+        let edges = self.get_all_edges()?;
+        let to_delete = if cascade {
+            cascade_delete_set(node_id, &edges)
+        } else {
+            vec![node_id]
+        };
+
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+        conn.transaction::<_, DbError, _>(|conn| {
+            let mut result = DeleteResult::default();
+
+            // Remove every edge touching a deleted node
+            let doomed_edges: Vec<DecisionEdge> = decision_edges::table
+                .filter(
+                    decision_edges::from_node_id
+                        .eq_any(&to_delete)
+                        .or(decision_edges::to_node_id.eq_any(&to_delete)),
+                )
+                .load(conn)?;
+            for edge in &doomed_edges {
+                insert_edge_tombstone(conn, edge, &now)?;
+            }
+            result.edges_deleted = diesel::delete(decision_edges::table.filter(
+                decision_edges::id.eq_any(doomed_edges.iter().map(|e| e.id).collect::<Vec<_>>()),
+            ))
+            .execute(conn)?;
+
+            let nodes: Vec<DecisionNode> = decision_nodes::table
+                .filter(decision_nodes::id.eq_any(&to_delete))
+                .load(conn)?;
+            for node in &nodes {
+                diesel::insert_or_ignore_into(decision_tombstones::table)
+                    .values(&NewTombstone {
+                        kind: TOMBSTONE_NODE,
+                        change_id: &node.change_id,
+                        from_change_id: None,
+                        to_change_id: None,
+                        edge_type: None,
+                        deleted_at: &now,
+                    })
+                    .execute(conn)?;
+            }
+
+            detach_nodes(conn, &to_delete)?;
+            diesel::delete(decision_nodes::table.filter(decision_nodes::id.eq_any(&to_delete)))
+                .execute(conn)?;
+
+            result.nodes_deleted = nodes.iter().map(|n| n.id).collect();
+            Ok(result)
+        })
+    }
+
+    /// Delete edges between two nodes, recording tombstones for sync.
+    /// If `edge_type` is None, every edge from `from_id` to `to_id` is removed.
+    /// Returns the number of edges deleted.
+    pub fn delete_edges(&self, from_id: i32, to_id: i32, edge_type: Option<&str>) -> Result<usize> {
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+
+        conn.transaction::<_, DbError, _>(|conn| {
+            let mut query = decision_edges::table
+                .filter(decision_edges::from_node_id.eq(from_id))
+                .filter(decision_edges::to_node_id.eq(to_id))
+                .into_boxed();
+            if let Some(t) = edge_type {
+                query = query.filter(decision_edges::edge_type.eq(t));
+            }
+            let edges: Vec<DecisionEdge> = query.load(conn)?;
+
+            for edge in &edges {
+                insert_edge_tombstone(conn, edge, &now)?;
+            }
+
+            let ids: Vec<i32> = edges.iter().map(|e| e.id).collect();
+            let deleted =
+                diesel::delete(decision_edges::table.filter(decision_edges::id.eq_any(ids)))
+                    .execute(conn)?;
+            Ok(deleted)
+        })
+    }
+
+    /// Record a tombstone without deleting anything (for patch application)
+    pub fn record_tombstone(
+        &self,
+        kind: &str,
+        change_id: &str,
+        from_change_id: Option<&str>,
+        to_change_id: Option<&str>,
+        edge_type: Option<&str>,
+        deleted_at: &str,
+    ) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        diesel::insert_or_ignore_into(decision_tombstones::table)
+            .values(&NewTombstone {
+                kind,
+                change_id,
+                from_change_id,
+                to_change_id,
+                edge_type,
+                deleted_at,
+            })
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Get all tombstones
+    pub fn get_tombstones(&self) -> Result<Vec<Tombstone>> {
+        let mut conn = self.get_conn()?;
+        let tombstones = decision_tombstones::table
+            .order(decision_tombstones::deleted_at.asc())
+            .load::<Tombstone>(&mut conn)?;
+        Ok(tombstones)
+    }
+
+    // ========================================================================
+    // Patch Sync State
+    // ========================================================================
+
+    /// Record the version of a node last shared through a patch, along with the
+    /// versions it descends from (newest first; the first is its parent)
+    pub fn set_sync_base(
+        &self,
+        change_id: &str,
+        content_hash: &str,
+        lineage: &[String],
+        content: &str,
+    ) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+        let ancestor_hashes =
+            serde_json::to_string(lineage.get(1..).unwrap_or_default()).unwrap_or_default();
+        diesel::replace_into(node_sync_bases::table)
+            .values(&NewNodeSyncBase {
+                change_id,
+                content_hash,
+                parent_hash: lineage.first().map(String::as_str),
+                content,
+                synced_at: &now,
+                ancestor_hashes: &ancestor_hashes,
+            })
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Get all node sync bases
+    pub fn get_sync_bases(&self) -> Result<Vec<NodeSyncBase>> {
+        let mut conn = self.get_conn()?;
+        let bases = node_sync_bases::table.load::<NodeSyncBase>(&mut conn)?;
+        Ok(bases)
+    }
+
+    /// Record a `diff export` run
+    pub fn record_patch_export(
+        &self,
+        path: &str,
+        exported_at: &str,
+        since: Option<&str>,
+        node_count: usize,
+        edge_count: usize,
+        filter: Option<&str>,
+    ) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        diesel::insert_into(patch_exports::table)
+            .values(&NewPatchExport {
+                path,
+                exported_at,
+                since,
+                node_count: node_count as i32,
+                edge_count: edge_count as i32,
+                filter,
+            })
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// The most recent `diff export` run that covered everything an export with
+    /// `filter` would: an unfiltered one, or one with the same filter
+    pub fn last_patch_export(&self, filter: Option<&str>) -> Result<Option<PatchExport>> {
+        let mut conn = self.get_conn()?;
+        let mut query = patch_exports::table
+            .order(patch_exports::id.desc())
+            .into_boxed();
+        query = match filter {
+            Some(filter) => query.filter(
+                patch_exports::filter
+                    .is_null()
+                    .or(patch_exports::filter.eq(filter)),
+            ),
+            None => query.filter(patch_exports::filter.is_null()),
+        };
+        let last = query.first::<PatchExport>(&mut conn).optional()?;
+        Ok(last)
+    }
+
+    /// Record that a patch was applied, along with the nodes and edges it created.
+    ///
+    /// Re-applying a patch (e.g. after a partial apply) adds to its counts and
+    /// items; failures reflect the latest run.
+    pub fn record_applied_patch(
+        &self,
+        record: &NewAppliedPatch,
+        items: &[NewAppliedPatchItem],
+    ) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        conn.transaction::<_, DbError, _>(|conn| {
+            let previous: Option<AppliedPatch> = applied_patches::table
+                .find(record.digest)
+                .first(conn)
+                .optional()?;
+            let (nodes_added, nodes_updated, edges_added) = match &previous {
+                Some(p) => (
+                    p.nodes_added + record.nodes_added,
+                    p.nodes_updated + record.nodes_updated,
+                    p.edges_added + record.edges_added,
+                ),
+                None => (record.nodes_added, record.nodes_updated, record.edges_added),
+            };
+            diesel::replace_into(applied_patches::table)
+                .values(&NewAppliedPatch {
+                    nodes_added,
+                    nodes_updated,
+                    edges_added,
+                    ..*record
+                })
+                .execute(conn)?;
+            diesel::insert_into(applied_patch_items::table)
+                .values(items)
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    /// All applied patches, most recently applied first
+    pub fn get_applied_patches(&self) -> Result<Vec<AppliedPatch>> {
+        let mut conn = self.get_conn()?;
+        let patches = applied_patches::table
+            .order(applied_patches::applied_at.desc())
+            .load::<AppliedPatch>(&mut conn)?;
+        Ok(patches)
+    }
+
+    /// Nodes and edges an applied patch created
+    pub fn get_applied_patch_items(&self, digest: &str) -> Result<Vec<AppliedPatchItem>> {
+        let mut conn = self.get_conn()?;
+        let items = applied_patch_items::table
+            .filter(applied_patch_items::patch_digest.eq(digest))
+            .order(applied_patch_items::id.asc())
+            .load::<AppliedPatchItem>(&mut conn)?;
+        Ok(items)
+    }
+
+    /// Remove the nodes and edges an applied patch created and forget it was applied.
+    ///
+    /// Nodes the patch only updated are left as they are. No tombstones are written,
+    /// so the removal stays local and the patch can be applied again.
+    pub fn revert_patch(&self, digest: &str) -> Result<RevertResult> {
+        let items = self.get_applied_patch_items(digest)?;
+        let mut conn = self.get_conn()?;
+        conn.transaction::<_, DbError, _>(|conn| {
+            let mut result = RevertResult::default();
+
+            for item in items.iter().filter(|i| i.kind == TOMBSTONE_EDGE) {
+                let (Some(from), Some(to), Some(edge_type)) =
+                    (&item.from_change_id, &item.to_change_id, &item.edge_type)
+                else {
+                    continue;
+                };
+                result.edges_removed += diesel::delete(
+                    decision_edges::table
+                        .filter(decision_edges::from_change_id.eq(from))
+                        .filter(decision_edges::to_change_id.eq(to))
+                        .filter(decision_edges::edge_type.eq(edge_type)),
+                )
+                .execute(conn)?;
+            }
+
+            let change_ids: Vec<&str> = items
+                .iter()
+                .filter(|i| i.kind == TOMBSTONE_NODE)
+                .map(|i| i.change_id.as_str())
+                .collect();
+            let node_ids: Vec<i32> = decision_nodes::table
+                .filter(decision_nodes::change_id.eq_any(&change_ids))
+                .select(decision_nodes::id)
+                .load(conn)?;
+            result.dependent_edges_removed = diesel::delete(
+                decision_edges::table.filter(
+                    decision_edges::from_node_id
+                        .eq_any(&node_ids)
+                        .or(decision_edges::to_node_id.eq_any(&node_ids)),
+                ),
+            )
+            .execute(conn)?;
+            detach_nodes(conn, &node_ids)?;
+            result.nodes_removed =
+                diesel::delete(decision_nodes::table.filter(decision_nodes::id.eq_any(&node_ids)))
+                    .execute(conn)?;
+            diesel::delete(
+                node_sync_bases::table.filter(node_sync_bases::change_id.eq_any(&change_ids)),
+            )
+            .execute(conn)?;
+
+            diesel::delete(
+                applied_patch_items::table.filter(applied_patch_items::patch_digest.eq(digest)),
+            )
+            .execute(conn)?;
+            diesel::delete(applied_patches::table.find(digest)).execute(conn)?;
+            Ok(result)
+        })
+    }
+
+    /// Record a conflict, unless the same unresolved conflict is already recorded.
+    /// Returns the conflict's ID.
+    pub fn create_node_conflict(
+        &self,
+        node_change_id: &str,
+        base_hash: Option<&str>,
+        base_value: Option<&str>,
+        local_value: &str,
+        remote_value: &str,
+    ) -> Result<i32> {
+        let mut conn = self.get_conn()?;
+        let existing: Option<i32> = node_conflicts::table
+            .filter(node_conflicts::node_change_id.eq(node_change_id))
+            .filter(node_conflicts::remote_value.eq(remote_value))
+            .filter(node_conflicts::resolution.is_null())
+            .select(node_conflicts::id)
+            .first(&mut conn)
+            .optional()?;
+        if let Some(id) = existing {
+            return Ok(id);
+        }
+
+        let now = chrono::Local::now().to_rfc3339();
+        diesel::insert_into(node_conflicts::table)
+            .values(&NewNodeConflict {
+                node_change_id,
+                base_hash,
+                base_value,
+                local_value,
+                remote_value,
+                resolution: None,
+                detected_at: &now,
+                resolved_at: None,
+            })
+            .execute(&mut conn)?;
+
+        let id: i32 = diesel::select(diesel::dsl::sql::<diesel::sql_types::Integer>(
+            "last_insert_rowid()",
+        ))
+        .first(&mut conn)?;
+
+        Ok(id)
+    }
+
+    /// Get all unresolved node conflicts, oldest first
+    pub fn get_unresolved_node_conflicts(&self) -> Result<Vec<NodeConflict>> {
+        let mut conn = self.get_conn()?;
+        let conflicts = node_conflicts::table
+            .filter(node_conflicts::resolution.is_null())
+            .order(node_conflicts::id.asc())
+            .load::<NodeConflict>(&mut conn)?;
+        Ok(conflicts)
+    }
+
+    /// Mark a node conflict resolved
+    pub fn mark_node_conflict_resolved(&self, conflict_id: i32, resolution: &str) -> Result<()> {
         let mut conn = self.get_conn()?;
         let now = chrono::Local::now().to_rfc3339();
 
-        // Get current metadata
-        let current_meta: Option<String> = decision_nodes::table
-            .filter(decision_nodes::id.eq(node_id))
-            .select(decision_nodes::metadata_json)
-            .first(&mut conn)?;
-
-        // Parse existing metadata or create new
-        let mut meta: serde_json::Value = current_meta
-            .as_ref()
-            .and_then(|m| serde_json::from_str(m).ok())
-            .unwrap_or_else(|| serde_json::json!({}));
-
-        // Add/update commit field
-        if let Some(obj) = meta.as_object_mut() {
-            obj.insert("commit".to_string(), serde_json::json!(commit_hash));
-        }
-
-        let new_meta = serde_json::to_string(&meta)
-            .map_err(|e| DbError::Validation(format!("JSON serialization error: {}", e)))?;
-
-        diesel::update(decision_nodes::table.filter(decision_nodes::id.eq(node_id)))
+        diesel::update(node_conflicts::table.filter(node_conflicts::id.eq(conflict_id)))
             .set((
-                decision_nodes::metadata_json.eq(Some(new_meta)),
-                decision_nodes::updated_at.eq(&now),
+                node_conflicts::resolution.eq(Some(resolution)),
+                node_conflicts::resolved_at.eq(Some(&now)),
             ))
             .execute(&mut conn)?;
 
         Ok(())
     }
 
-    /// Update a node's prompt in metadata_json
-    pub fn update_node_prompt(&self, node_id: i32, prompt: &str) -> Result<()> {
-        let mut conn = self.get_conn()?;
-        let now = chrono::Local::now().to_rfc3339();
-
-        // Get current metadata
-        let current_meta: Option<String> = decision_nodes::table
-            .filter(decision_nodes::id.eq(node_id))
-            .select(decision_nodes::metadata_json)
-            .first(&mut conn)?;
-
-        // Parse existing metadata or create new
-        let mut meta: serde_json::Value = current_meta
-            .as_ref()
-            .and_then(|m| serde_json::from_str(m).ok())
-            .unwrap_or_else(|| serde_json::json!({}));
+    // ========================================================================
+    // Merging Duplicates
+    // ========================================================================
 
-        // Add/update prompt field
-        if let Some(obj) = meta.as_object_mut() {
-            obj.insert("prompt".to_string(), serde_json::json!(prompt));
+    /// Merge duplicate nodes into `keep_id`.
+    ///
+    /// Edges are moved onto the kept node (self-loops and duplicate edges are dropped),
+    /// metadata is merged, and each duplicate's change_id is kept as an alias of the
+    /// kept node so patches that reference it still resolve.
+    pub fn merge_nodes(&self, keep_id: i32, dup_ids: &[i32]) -> Result<MergeResult> {
+        use std::collections::HashSet;
+
+        if dup_ids.is_empty() {
+            return Err(DbError::Validation(
+                "No duplicate nodes given to merge".to_string(),
+            ));
+        }
+        if dup_ids.contains(&keep_id) {
+            return Err(DbError::Validation(format!(
+                "Cannot merge node {} into itself",
+                keep_id
+            )));
         }
 
-        let new_meta = serde_json::to_string(&meta)
-            .map_err(|e| DbError::Validation(format!("JSON serialization error: {}", e)))?;
-
-        diesel::update(decision_nodes::table.filter(decision_nodes::id.eq(node_id)))
-            .set((
-                decision_nodes::metadata_json.eq(Some(new_meta)),
-                decision_nodes::updated_at.eq(&now),
+        let keep = self.get_node(keep_id)?.ok_or_else(|| {
+            DbError::Validation(format!(
+                "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                keep_id
             ))
-            .execute(&mut conn)?;
+        })?;
+        let mut dups = Vec::with_capacity(dup_ids.len());
+        for &dup_id in dup_ids {
+            let dup = self.get_node(dup_id)?.ok_or_else(|| {
+                DbError::Validation(format!(
+                    "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                    dup_id
+                ))
+            })?;
+            if !dups.iter().any(|d: &DecisionNode| d.id == dup.id) {
+                dups.push(dup);
+            }
+        }
+        let dup_set: HashSet<i32> = dups.iter().map(|d| d.id).collect();
+        let dup_ids: Vec<i32> = dups.iter().map(|d| d.id).collect();
+        let dup_change_ids: Vec<String> = dups.iter().map(|d| d.change_id.clone()).collect();
 
-        Ok(())
-    }
+        let metadata = merge_metadata_json(&keep, &dups);
+        let edges = self.get_all_edges()?;
 
-    /// Get all nodes
-    pub fn get_all_nodes(&self) -> Result<Vec<DecisionNode>> {
         let mut conn = self.get_conn()?;
-        let nodes = decision_nodes::table
-            .order(decision_nodes::created_at.asc())
-            .load::<DecisionNode>(&mut conn)?;
-        Ok(nodes)
-    }
+        let now = chrono::Local::now().to_rfc3339();
+        conn.transaction::<_, DbError, _>(|conn| {
+            let mut result = MergeResult::default();
+
+            // Rewire edges, dropping any that collapse into self-loops or duplicates
+            let mut edge_keys: HashSet<(i32, i32, String)> = edges
+                .iter()
+                .filter(|e| !dup_set.contains(&e.from_node_id) && !dup_set.contains(&e.to_node_id))
+                .map(|e| (e.from_node_id, e.to_node_id, e.edge_type.clone()))
+                .collect();
+            let remap = |id: i32| if dup_set.contains(&id) { keep_id } else { id };
+
+            for edge in edges
+                .iter()
+                .filter(|e| dup_set.contains(&e.from_node_id) || dup_set.contains(&e.to_node_id))
+            {
+                let from = remap(edge.from_node_id);
+                let to = remap(edge.to_node_id);
+
+                if from == to || !edge_keys.insert((from, to, edge.edge_type.clone())) {
+                    diesel::delete(decision_edges::table.filter(decision_edges::id.eq(edge.id)))
+                        .execute(conn)?;
+                    result.edges_dropped += 1;
+                    continue;
+                }
+
+                let from_cid = if from == keep_id {
+                    Some(keep.change_id.clone())
+                } else {
+                    edge.from_change_id.clone()
+                };
+                let to_cid = if to == keep_id {
+                    Some(keep.change_id.clone())
+                } else {
+                    edge.to_change_id.clone()
+                };
+                diesel::update(decision_edges::table.filter(decision_edges::id.eq(edge.id)))
+                    .set((
+                        decision_edges::from_node_id.eq(from),
+                        decision_edges::to_node_id.eq(to),
+                        decision_edges::from_change_id.eq(from_cid),
+                        decision_edges::to_change_id.eq(to_cid),
+                    ))
+                    .execute(conn)?;
+                result.edges_moved += 1;
+            }
 
-    /// Get all edges
-    pub fn get_all_edges(&self) -> Result<Vec<DecisionEdge>> {
-        let mut conn = self.get_conn()?;
-        let edges = decision_edges::table
-            .order(decision_edges::created_at.asc())
-            .load::<DecisionEdge>(&mut conn)?;
-        Ok(edges)
-    }
+            diesel::update(decision_nodes::table.filter(decision_nodes::id.eq(keep_id)))
+                .set((
+                    decision_nodes::metadata_json.eq(&metadata),
+                    decision_nodes::updated_at.eq(&now),
+                ))
+                .execute(conn)?;
 
-    /// Get children of a node (outgoing edges)
-    pub fn get_node_children(&self, node_id: i32) -> Result<Vec<DecisionNode>> {
-        let mut conn = self.get_conn()?;
+            // Repoint rows in other tables at the kept node
+            diesel::update(
+                decision_context::table.filter(decision_context::node_id.eq_any(&dup_ids)),
+            )
+            .set(decision_context::node_id.eq(keep_id))
+            .execute(conn)?;
+            for &dup_id in &dup_ids {
+                diesel::sql_query(
+                    "INSERT OR IGNORE INTO session_nodes (session_id, node_id, added_at) \
+                     SELECT session_id, ?, added_at FROM session_nodes WHERE node_id = ?",
+                )
+                .bind::<diesel::sql_types::Integer, _>(keep_id)
+                .bind::<diesel::sql_types::Integer, _>(dup_id)
+                .execute(conn)?;
+            }
+            diesel::delete(session_nodes::table.filter(session_nodes::node_id.eq_any(&dup_ids)))
+                .execute(conn)?;
+            diesel::update(
+                decision_sessions::table.filter(decision_sessions::root_node_id.eq_any(&dup_ids)),
+            )
+            .set(decision_sessions::root_node_id.eq(keep_id))
+            .execute(conn)?;
+            diesel::update(
+                command_log::table.filter(command_log::decision_node_id.eq_any(&dup_ids)),
+            )
+            .set(command_log::decision_node_id.eq(keep_id))
+            .execute(conn)?;
+            diesel::update(
+                roadmap_items::table.filter(roadmap_items::outcome_node_id.eq_any(&dup_ids)),
+            )
+            .set((
+                roadmap_items::outcome_node_id.eq(keep_id),
+                roadmap_items::outcome_change_id.eq(&keep.change_id),
+            ))
+            .execute(conn)?;
 
-        let child_ids: Vec<i32> = decision_edges::table
-            .filter(decision_edges::from_node_id.eq(node_id))
-            .select(decision_edges::to_node_id)
-            .load(&mut conn)?;
+            // Keep the duplicates' change_ids (and anything already aliased to them) resolvable
+            diesel::update(
+                change_id_aliases::table
+                    .filter(change_id_aliases::change_id.eq_any(&dup_change_ids)),
+            )
+            .set(change_id_aliases::change_id.eq(&keep.change_id))
+            .execute(conn)?;
+            for dup in &dups {
+                diesel::replace_into(change_id_aliases::table)
+                    .values(&NewChangeIdAlias {
+                        alias: &dup.change_id,
+                        change_id: &keep.change_id,
+                        created_at: &now,
+                    })
+                    .execute(conn)?;
+            }
 
-        let children = decision_nodes::table
-            .filter(decision_nodes::id.eq_any(child_ids))
-            .load::<DecisionNode>(&mut conn)?;
+            diesel::delete(decision_nodes::table.filter(decision_nodes::id.eq_any(&dup_ids)))
+                .execute(conn)?;
 
-        Ok(children)
+            result.aliases = dup_change_ids.clone();
+            Ok(result)
+        })
     }
 
-    /// Get parents of a node (incoming edges)
-    pub fn get_node_parents(&self, node_id: i32) -> Result<Vec<DecisionNode>> {
+    /// Get all change_id aliases left behind by merges
+    pub fn get_change_id_aliases(&self) -> Result<Vec<ChangeIdAlias>> {
         let mut conn = self.get_conn()?;
-
-        let parent_ids: Vec<i32> = decision_edges::table
-            .filter(decision_edges::to_node_id.eq(node_id))
-            .select(decision_edges::from_node_id)
-            .load(&mut conn)?;
-
-        let parents = decision_nodes::table
-            .filter(decision_nodes::id.eq_any(parent_ids))
-            .load::<DecisionNode>(&mut conn)?;
-
-        Ok(parents)
+        let aliases = change_id_aliases::table
+            .order(change_id_aliases::created_at.asc())
+            .load::<ChangeIdAlias>(&mut conn)?;
+        Ok(aliases)
     }
 
     /// Get full graph as JSON-serializable structure
@@ -1439,6 +2801,7 @@ impl Database {
             nodes,
             edges,
             config: None,
+            context: Vec::new(),
         })
     }
 
@@ -1453,20 +2816,222 @@ impl Database {
             nodes,
             edges,
             config,
+            context: Vec::new(),
         })
     }
 
+    // ========================================================================
+    // Sessions
+    // ========================================================================
+
+    /// Open a working session. Nodes created until `end_session` are attached
+    /// to it. Only one session can be open at a time.
+    pub fn start_session(&self, name: Option<&str>) -> Result<DecisionSession> {
+        if let Some(open) = self.get_open_session()? {
+            return Err(DbError::Validation(format!(
+                "Session {} is already open. End it with 'deciduous session end' first.",
+                open.id
+            )));
+        }
+
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+        let new_session = NewDecisionSession {
+            name,
+            started_at: &now,
+            ended_at: None,
+            root_node_id: None,
+            summary: None,
+        };
+
+        diesel::insert_into(decision_sessions::table)
+            .values(&new_session)
+            .execute(&mut conn)?;
+
+        let session = decision_sessions::table
+            .order(decision_sessions::id.desc())
+            .first::<DecisionSession>(&mut conn)?;
+        Ok(session)
+    }
+
+    /// Close the open session, recording an optional summary
+    pub fn end_session(&self, summary: Option<&str>) -> Result<DecisionSession> {
+        let Some(open) = self.get_open_session()? else {
+            return Err(DbError::Validation(
+                "No session is open. Start one with 'deciduous session start'.".to_string(),
+            ));
+        };
+
+        let mut conn = self.get_conn()?;
+        let now = chrono::Local::now().to_rfc3339();
+        diesel::update(decision_sessions::table.filter(decision_sessions::id.eq(open.id)))
+            .set((
+                decision_sessions::ended_at.eq(Some(&now)),
+                decision_sessions::summary.eq(summary),
+            ))
+            .execute(&mut conn)?;
+
+        let session = decision_sessions::table
+            .filter(decision_sessions::id.eq(open.id))
+            .first::<DecisionSession>(&mut conn)?;
+        Ok(session)
+    }
+
+    /// The session new nodes are currently attached to, if any
+    pub fn get_open_session(&self) -> Result<Option<DecisionSession>> {
+        let mut conn = self.get_conn()?;
+        Ok(open_session(&mut conn)?)
+    }
+
+    /// A session by ID
+    pub fn get_session(&self, session_id: i32) -> Result<Option<DecisionSession>> {
+        let mut conn = self.get_conn()?;
+        let session = decision_sessions::table
+            .filter(decision_sessions::id.eq(session_id))
+            .first::<DecisionSession>(&mut conn)
+            .optional()?;
+        Ok(session)
+    }
+
+    /// All sessions, most recent first
+    pub fn get_sessions(&self) -> Result<Vec<DecisionSession>> {
+        let mut conn = self.get_conn()?;
+        let sessions = decision_sessions::table
+            .order(decision_sessions::id.desc())
+            .load::<DecisionSession>(&mut conn)?;
+        Ok(sessions)
+    }
+
+    /// Nodes attached to a session, in the order they were added
+    pub fn get_session_nodes(&self, session_id: i32) -> Result<Vec<DecisionNode>> {
+        let mut conn = self.get_conn()?;
+        let members: Vec<(i32, String)> = session_nodes::table
+            .filter(session_nodes::session_id.eq(session_id))
+            .select((session_nodes::node_id, session_nodes::added_at))
+            .load(&mut conn)?;
+        let ids: Vec<i32> = members.iter().map(|(id, _)| *id).collect();
+
+        let mut nodes = decision_nodes::table
+            .filter(decision_nodes::id.eq_any(&ids))
+            .load::<DecisionNode>(&mut conn)?;
+        let added_at: std::collections::HashMap<i32, String> = members.into_iter().collect();
+        nodes.sort_by(|a, b| (&added_at[&a.id], a.id).cmp(&(&added_at[&b.id], b.id)));
+        Ok(nodes)
+    }
+
+    /// Number of nodes attached to each session
+    pub fn get_session_node_counts(&self) -> Result<std::collections::HashMap<i32, usize>> {
+        let mut conn = self.get_conn()?;
+        let session_ids: Vec<i32> = session_nodes::table
+            .select(session_nodes::session_id)
+            .load(&mut conn)?;
+        let mut counts = std::collections::HashMap::new();
+        for id in session_ids {
+            *counts.entry(id).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+
+    // ========================================================================
+    // Decision Context
+    // ========================================================================
+
+    /// Attach captured context (an error log, benchmark output, ...) to a node
+    pub fn add_context(
+        &self,
+        node_id: i32,
+        context_type: ContextType,
+        content: &str,
+        source: Option<&str>,
+    ) -> Result<i32> {
+        if self.get_node(node_id)?.is_none() {
+            return Err(DbError::Validation(format!(
+                "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                node_id
+            )));
+        }
+        let now = chrono::Local::now().to_rfc3339();
+        self.import_context(
+            node_id,
+            context_type.as_str(),
+            &build_context_json(content, source),
+            &now,
+        )
+    }
+
+    /// Insert a context item exactly as given, e.g. from a patch
+    pub fn import_context(
+        &self,
+        node_id: i32,
+        context_type: &str,
+        content_json: &str,
+        captured_at: &str,
+    ) -> Result<i32> {
+        context_type.parse::<ContextType>()?;
+        let mut conn = self.get_conn()?;
+
+        diesel::insert_into(decision_context::table)
+            .values(&NewDecisionContext {
+                node_id,
+                context_type,
+                content_json,
+                captured_at,
+            })
+            .execute(&mut conn)?;
+
+        let id: i32 = diesel::select(diesel::dsl::sql::<diesel::sql_types::Integer>(
+            "last_insert_rowid()",
+        ))
+        .first(&mut conn)?;
+
+        Ok(id)
+    }
+
+    /// Context attached to a node, oldest first
+    pub fn get_node_context(&self, node_id: i32) -> Result<Vec<DecisionContext>> {
+        let mut conn = self.get_conn()?;
+        let context = decision_context::table
+            .filter(decision_context::node_id.eq(node_id))
+            .order((
+                decision_context::captured_at.asc(),
+                decision_context::id.asc(),
+            ))
+            .load::<DecisionContext>(&mut conn)?;
+        Ok(context)
+    }
+
+    /// All context items, oldest first
+    pub fn get_all_context(&self) -> Result<Vec<DecisionContext>> {
+        let mut conn = self.get_conn()?;
+        let context = decision_context::table
+            .order((
+                decision_context::captured_at.asc(),
+                decision_context::id.asc(),
+            ))
+            .load::<DecisionContext>(&mut conn)?;
+        Ok(context)
+    }
+
     // ========================================================================
     // Command Log Operations
     // ========================================================================
 
-    /// Log a command execution
+    /// Log a command execution, optionally linked to the node it was run for
     pub fn log_command(
         &self,
         command: &str,
         description: Option<&str>,
         working_dir: Option<&str>,
+        node_id: Option<i32>,
     ) -> Result<i32> {
+        if let Some(node_id) = node_id {
+            if self.get_node(node_id)?.is_none() {
+                return Err(DbError::Validation(format!(
+                    "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                    node_id
+                )));
+            }
+        }
         let mut conn = self.get_conn()?;
         let now = chrono::Local::now().to_rfc3339();
 
@@ -1480,7 +3045,7 @@ impl Database {
             started_at: &now,
             completed_at: None,
             duration_ms: None,
-            decision_node_id: None,
+            decision_node_id: node_id,
         };
 
         diesel::insert_into(command_log::table)
@@ -1495,7 +3060,8 @@ impl Database {
         Ok(id)
     }
 
-    /// Complete a command log entry
+    /// Complete a command log entry. Output longer than
+    /// [`COMMAND_OUTPUT_LIMIT`] is cut down to its tail.
     pub fn complete_command(
         &self,
         log_id: i32,
@@ -1506,6 +3072,8 @@ impl Database {
     ) -> Result<()> {
         let mut conn = self.get_conn()?;
         let now = chrono::Local::now().to_rfc3339();
+        let stdout = stdout.map(|s| truncate_command_output(s, COMMAND_OUTPUT_LIMIT));
+        let stderr = stderr.map(|s| truncate_command_output(s, COMMAND_OUTPUT_LIMIT));
 
         diesel::update(command_log::table.filter(command_log::id.eq(log_id)))
             .set((
@@ -1520,6 +3088,21 @@ impl Database {
         Ok(())
     }
 
+    /// Link a logged command to a node
+    pub fn link_command(&self, log_id: i32, node_id: i32) -> Result<()> {
+        if self.get_node(node_id)?.is_none() {
+            return Err(DbError::Validation(format!(
+                "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                node_id
+            )));
+        }
+        let mut conn = self.get_conn()?;
+        diesel::update(command_log::table.filter(command_log::id.eq(log_id)))
+            .set(command_log::decision_node_id.eq(Some(node_id)))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
     /// Get recent commands
     pub fn get_recent_commands(&self, limit: i64) -> Result<Vec<CommandLog>> {
         let mut conn = self.get_conn()?;
@@ -1530,6 +3113,26 @@ impl Database {
         Ok(commands)
     }
 
+    /// Get the commands run for a node, oldest first
+    pub fn get_node_commands(&self, node_id: i32) -> Result<Vec<CommandLog>> {
+        let mut conn = self.get_conn()?;
+        let commands = command_log::table
+            .filter(command_log::decision_node_id.eq(node_id))
+            .order((command_log::started_at.asc(), command_log::id.asc()))
+            .load::<CommandLog>(&mut conn)?;
+        Ok(commands)
+    }
+
+    /// Get every command linked to a node, oldest first
+    pub fn get_linked_commands(&self) -> Result<Vec<CommandLog>> {
+        let mut conn = self.get_conn()?;
+        let commands = command_log::table
+            .filter(command_log::decision_node_id.is_not_null())
+            .order((command_log::started_at.asc(), command_log::id.asc()))
+            .load::<CommandLog>(&mut conn)?;
+        Ok(commands)
+    }
+
     // ========================================================================
     // Roadmap Board Operations
     // ========================================================================
@@ -2069,10 +3672,246 @@ impl Database {
     }
 }
 
+/// Insert a tombstone for an edge (edges without change_ids can't be synced, so are skipped)
+fn insert_edge_tombstone(
+    conn: &mut SqliteConnection,
+    edge: &DecisionEdge,
+    now: &str,
+) -> Result<()> {
+    if let (Some(from), Some(to)) = (&edge.from_change_id, &edge.to_change_id) {
+        let key = edge_tombstone_key(from, to, &edge.edge_type);
+        diesel::insert_or_ignore_into(decision_tombstones::table)
+            .values(&NewTombstone {
+                kind: TOMBSTONE_EDGE,
+                change_id: &key,
+                from_change_id: Some(from),
+                to_change_id: Some(to),
+                edge_type: Some(&edge.edge_type),
+                deleted_at: now,
+            })
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// The session still collecting nodes (the newest, should several be open)
+fn open_session(conn: &mut SqliteConnection) -> QueryResult<Option<DecisionSession>> {
+    decision_sessions::table
+        .filter(decision_sessions::ended_at.is_null())
+        .order(decision_sessions::id.desc())
+        .first::<DecisionSession>(conn)
+        .optional()
+}
+
+/// Attach a newly created node to the open session; the first one becomes its root
+fn attach_to_open_session(conn: &mut SqliteConnection, node_id: i32, now: &str) -> Result<()> {
+    let Some(session) = open_session(conn)? else {
+        return Ok(());
+    };
+
+    diesel::insert_or_ignore_into(session_nodes::table)
+        .values(&NewSessionNode {
+            session_id: session.id,
+            node_id,
+            added_at: now,
+        })
+        .execute(conn)?;
+
+    if session.root_node_id.is_none() {
+        diesel::update(decision_sessions::table.filter(decision_sessions::id.eq(session.id)))
+            .set(decision_sessions::root_node_id.eq(Some(node_id)))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Detach rows in other tables that point at nodes about to be deleted
+fn detach_nodes(conn: &mut SqliteConnection, node_ids: &[i32]) -> Result<()> {
+    diesel::delete(decision_context::table.filter(decision_context::node_id.eq_any(node_ids)))
+        .execute(conn)?;
+    diesel::delete(session_nodes::table.filter(session_nodes::node_id.eq_any(node_ids)))
+        .execute(conn)?;
+    diesel::update(
+        decision_sessions::table.filter(decision_sessions::root_node_id.eq_any(node_ids)),
+    )
+    .set(decision_sessions::root_node_id.eq(None::<i32>))
+    .execute(conn)?;
+    diesel::update(command_log::table.filter(command_log::decision_node_id.eq_any(node_ids)))
+        .set(command_log::decision_node_id.eq(None::<i32>))
+        .execute(conn)?;
+    diesel::update(roadmap_items::table.filter(roadmap_items::outcome_node_id.eq_any(node_ids)))
+        .set((
+            roadmap_items::outcome_node_id.eq(None::<i32>),
+            roadmap_items::outcome_change_id.eq(None::<String>),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Merge metadata from duplicates into the kept node's metadata.
+///
+/// Files and tags are unioned (kept node's first), commit and prompt come from the
+/// earliest-created node that has one, and any other field missing on the kept node
+/// is filled in from the duplicates.
+fn merge_metadata_json(keep: &DecisionNode, dups: &[DecisionNode]) -> Option<String> {
+    let mut merged = keep.metadata();
+
+    for dup in dups {
+        let meta = dup.metadata();
+        merged.confidence = merged.confidence.or(meta.confidence);
+        merged.branch = merged.branch.or(meta.branch);
+        for file in meta.files {
+            if !merged.files.contains(&file) {
+                merged.files.push(file);
+            }
+        }
+        for tag in meta.tags {
+            merged.apply_label_edit(&LabelEdit::AddTag(tag));
+        }
+        for (key, value) in meta.attributes {
+            merged.attributes.entry(key).or_insert(value);
+        }
+        for (key, value) in meta.extra {
+            merged.extra.entry(key).or_insert(value);
+        }
+    }
+
+    // Stable sort keeps the kept node first among equal timestamps
+    let mut all: Vec<&DecisionNode> = std::iter::once(keep).chain(dups.iter()).collect();
+    all.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    if let Some(commit) = all.iter().find_map(|n| n.commit()) {
+        merged.commit = Some(commit);
+    }
+    if let Some(prompt) = all.iter().find_map(|n| n.prompt()) {
+        merged.prompt = Some(prompt);
+    }
+
+    merged.to_json()
+}
+
+/// Compute the nodes removed by a cascading delete of `root_id`.
+///
+/// A descendant is included only when every one of its parents is also being
+/// deleted, so nodes shared with another surviving chain are kept.
+fn cascade_delete_set(root_id: i32, edges: &[DecisionEdge]) -> Vec<i32> {
+    use std::collections::HashSet;
+
+    let mut deleted: Vec<i32> = vec![root_id];
+    let mut deleted_set: HashSet<i32> = HashSet::from([root_id]);
+
+    loop {
+        let mut changed = false;
+        let candidates: HashSet<i32> = edges
+            .iter()
+            .filter(|e| deleted_set.contains(&e.from_node_id))
+            .map(|e| e.to_node_id)
+            .filter(|id| !deleted_set.contains(id))
+            .collect();
+
+        let mut candidates: Vec<i32> = candidates.into_iter().collect();
+        candidates.sort_unstable();
+
+        for candidate in candidates {
+            let all_parents_deleted = edges
+                .iter()
+                .filter(|e| e.to_node_id == candidate)
+                .all(|e| deleted_set.contains(&e.from_node_id));
+            if all_parents_deleted {
+                deleted_set.insert(candidate);
+                deleted.push(candidate);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return deleted;
+        }
+    }
+}
+
 // ============================================================================
 // Additional Types
 // ============================================================================
 
+/// Result of deleting a node
+#[derive(Debug, Default)]
+pub struct DeleteResult {
+    /// IDs of the nodes that were deleted (the target first)
+    pub nodes_deleted: Vec<i32>,
+    /// Number of edges removed along with the nodes
+    pub edges_deleted: usize,
+}
+
+/// Result of merging duplicate nodes
+#[derive(Debug, Default)]
+pub struct MergeResult {
+    /// Number of edges moved onto the kept node
+    pub edges_moved: usize,
+    /// Number of edges dropped as self-loops or duplicates
+    pub edges_dropped: usize,
+    /// change_ids of the merged nodes, now aliases of the kept node
+    pub aliases: Vec<String>,
+}
+
+/// Narrows `Database::search` results
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    pub node_type: Option<String>,
+    pub status: Option<String>,
+    pub branch: Option<String>,
+    /// All of these tags must be present
+    pub tags: Vec<String>,
+    /// Maximum number of hits (all when None)
+    pub limit: Option<usize>,
+}
+
+impl SearchFilters {
+    fn matches(&self, node: &DecisionNode) -> bool {
+        self.node_type
+            .as_ref()
+            .map_or(true, |t| &node.node_type == t)
+            && self.status.as_ref().map_or(true, |s| &node.status == s)
+            && self
+                .branch
+                .as_ref()
+                .map_or(true, |b| node.branch().as_ref() == Some(b))
+            && node.has_all_tags(&self.tags)
+    }
+}
+
+/// A node matched by `Database::search`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SearchHit {
+    pub node: DecisionNode,
+    /// Relevance (higher is better; title matches weigh most)
+    pub score: f64,
+    /// Matching excerpt with matched terms wrapped in `[` `]`
+    pub snippet: String,
+}
+
+#[derive(QueryableByName)]
+struct SearchRow {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    id: i32,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    rank: f64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    snippet: Option<String>,
+}
+
+/// Turn free text into an FTS5 query: every word is quoted (so punctuation such as
+/// `src/db.rs` or `don't` is literal) and the last one is a prefix match.
+fn fts5_query(input: &str) -> Option<String> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|w| format!("\"{}\"", w.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", words.join(" ")))
+}
+
 /// Summary statistics from the database (kept for compatibility)
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct DbSummary {
@@ -2083,14 +3922,17 @@ pub struct DbSummary {
 /// Alias for backwards compatibility
 pub type DbRecord = DecisionNode;
 
-/// Full decision graph for serialization
-#[derive(Debug, Clone, serde::Serialize)]
+/// Full decision graph for serialization (docs/graph-data.json)
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
 pub struct DecisionGraph {
     pub nodes: Vec<DecisionNode>,
     pub edges: Vec<DecisionEdge>,
     /// Optional config from .deciduous/config.toml (for external repo links, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<crate::config::Config>,
+    /// Context attached to the nodes, when requested (`/api/graph?include=context`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<DecisionContext>,
 }
 
 #[cfg(test)]
@@ -2171,6 +4013,217 @@ mod tests {
         assert!(json.get("files").unwrap().as_array().is_some());
     }
 
+    // === NodeMetadata Tests ===
+
+    #[test]
+    fn test_node_metadata_preserves_unknown_keys() {
+        let json = r#"{"confidence": 80, "owners": ["sam"], "links": {"pr": 12}}"#;
+        let meta = NodeMetadata::from_json(json);
+        assert_eq!(meta.version, 0);
+        assert_eq!(meta.confidence, Some(80));
+        assert_eq!(meta.extra.len(), 2);
+
+        let out: serde_json::Value = serde_json::from_str(&meta.to_json().unwrap()).unwrap();
+        assert_eq!(out["owners"], json!(["sam"]));
+        assert_eq!(out["links"]["pr"], 12);
+        assert_eq!(out["metadata_version"], NODE_METADATA_VERSION);
+        assert_eq!(
+            NodeMetadata::from_json(&meta.to_json().unwrap()).extra,
+            meta.extra
+        );
+    }
+
+    #[test]
+    fn test_node_metadata_keeps_mistyped_known_keys() {
+        let meta = NodeMetadata::from_json(r#"{"confidence": "high", "files": "a.rs"}"#);
+        assert_eq!(meta.confidence, None);
+        assert!(meta.files.is_empty());
+        assert_eq!(meta.extra["confidence"], "high");
+        assert_eq!(meta.extra["files"], "a.rs");
+    }
+
+    #[test]
+    fn test_node_metadata_empty_is_none() {
+        assert!(NodeMetadata::default().to_json().is_none());
+        assert!(NodeMetadata::from_json("not json").is_empty());
+        assert!(NodeMetadata::from_json(r#"{"metadata_version": 1}"#)
+            .to_json()
+            .is_none());
+    }
+
+    #[test]
+    fn test_decision_node_metadata_accessors() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let id = db
+            .create_node_full(
+                "action",
+                "Ship it",
+                None,
+                Some(70),
+                Some("abc1234"),
+                Some("prompt"),
+                Some("a.rs, b.rs"),
+                Some("main"),
+            )
+            .unwrap();
+        let node = db.get_node(id).unwrap().unwrap();
+        assert_eq!(node.confidence(), Some(70));
+        assert_eq!(node.commit().as_deref(), Some("abc1234"));
+        assert_eq!(node.prompt().as_deref(), Some("prompt"));
+        assert_eq!(node.files(), vec!["a.rs", "b.rs"]);
+        assert_eq!(node.branch().as_deref(), Some("main"));
+        assert_eq!(node.metadata().version, NODE_METADATA_VERSION);
+    }
+
+    // === Tag / Attribute Tests ===
+
+    #[test]
+    fn test_label_edit_parsing() {
+        assert_eq!(
+            "+perf".parse::<LabelEdit>().unwrap(),
+            LabelEdit::AddTag("perf".to_string())
+        );
+        assert_eq!(
+            "perf".parse::<LabelEdit>().unwrap(),
+            LabelEdit::AddTag("perf".to_string())
+        );
+        assert_eq!(
+            "-perf".parse::<LabelEdit>().unwrap(),
+            LabelEdit::RemoveTag("perf".to_string())
+        );
+        assert_eq!(
+            "owner=sam lee".parse::<LabelEdit>().unwrap(),
+            LabelEdit::SetAttribute("owner".to_string(), "sam lee".to_string())
+        );
+        assert_eq!(
+            "owner=".parse::<LabelEdit>().unwrap(),
+            LabelEdit::RemoveAttribute("owner".to_string())
+        );
+        assert!("".parse::<LabelEdit>().is_err());
+        assert!("two words".parse::<LabelEdit>().is_err());
+        assert!("a,b".parse::<LabelEdit>().is_err());
+        assert!("=value".parse::<LabelEdit>().is_err());
+        assert!(LabelEdit::set_attribute("no-equals").is_err());
+    }
+
+    #[test]
+    fn test_edit_labels_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let id = db
+            .create_node("action", "Cache", None, Some(80), None)
+            .unwrap();
+        let edits: Vec<LabelEdit> = ["+perf", "debt", "perf", "owner=sam", "ticket=ABC-1"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        db.edit_labels(id, &edits).unwrap();
+
+        let node = db.get_node(id).unwrap().unwrap();
+        assert_eq!(node.tags(), vec!["perf", "debt"]);
+        assert!(node.has_all_tags(&["debt".to_string(), "perf".to_string()]));
+        assert!(!node.has_all_tags(&["perf".to_string(), "security".to_string()]));
+        assert_eq!(node.metadata().attributes["owner"], "sam");
+        assert_eq!(node.confidence(), Some(80));
+
+        let edits: Vec<LabelEdit> = ["-perf", "ticket="]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let meta = db.edit_labels(id, &edits).unwrap();
+        assert_eq!(meta.tags, vec!["debt"]);
+        assert!(!meta.attributes.contains_key("ticket"));
+
+        assert!(matches!(
+            db.edit_labels(99, &edits),
+            Err(DbError::Validation(_))
+        ));
+    }
+
+    // === Search Tests ===
+
+    #[test]
+    fn test_fts5_query_quotes_words() {
+        assert_eq!(fts5_query("  "), None);
+        assert_eq!(
+            fts5_query("src/db.rs cach").as_deref(),
+            Some(r#""src/db.rs" "cach"*"#)
+        );
+        assert_eq!(
+            fts5_query(r#"say "hi""#).as_deref(),
+            Some(r#""say" """hi"""*"#)
+        );
+    }
+
+    #[test]
+    fn test_search_ranks_and_tracks_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let title_hit = db
+            .create_node("decision", "Use Redis for caching", None, None, None)
+            .unwrap();
+        let prompt_hit = db
+            .create_node_full(
+                "action",
+                "Add layer",
+                Some("Sits in front of the DB"),
+                None,
+                None,
+                Some("what about caching?"),
+                Some("src/cache.rs"),
+                None,
+            )
+            .unwrap();
+        db.create_node("goal", "Unrelated", None, None, None)
+            .unwrap();
+
+        let hits = db.search("caching", &SearchFilters::default()).unwrap();
+        let ids: Vec<i32> = hits.iter().map(|h| h.node.id).collect();
+        assert_eq!(ids, vec![title_hit, prompt_hit]);
+        assert!(hits[0].snippet.contains("[caching]"));
+
+        // Files, prefix matching and filters
+        let hits = db.search("cache.rs", &SearchFilters::default()).unwrap();
+        assert_eq!(hits[0].node.id, prompt_hit);
+        let filters = SearchFilters {
+            node_type: Some("action".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(db.search("cach", &filters).unwrap().len(), 1);
+
+        // Index follows updates and deletes
+        db.update_node(
+            prompt_hit,
+            Some("Warm the layer"),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            db.search("warm", &SearchFilters::default()).unwrap().len(),
+            1
+        );
+        db.delete_node(prompt_hit, false).unwrap();
+        assert!(db
+            .search("warm", &SearchFilters::default())
+            .unwrap()
+            .is_empty());
+        assert!(db
+            .search("   ", &SearchFilters::default())
+            .unwrap()
+            .is_empty());
+    }
+
     // === DecisionSchema Tests ===
 
     #[test]
@@ -2254,6 +4307,282 @@ mod tests {
         assert!(CURRENT_SCHEMA.features.contains(&"decision_edges"));
     }
 
+    // === Graph Vocabulary Tests ===
+
+    #[test]
+    fn test_vocabulary_round_trip() {
+        for t in NodeType::ALL {
+            assert_eq!(t.to_string().parse::<NodeType>().unwrap(), t);
+        }
+        for t in EdgeType::ALL {
+            assert_eq!(t.to_string().parse::<EdgeType>().unwrap(), t);
+        }
+        for s in NodeStatus::ALL {
+            assert_eq!(s.to_string().parse::<NodeStatus>().unwrap(), s);
+        }
+        assert_eq!(EdgeType::LeadsTo.to_string(), "leads_to");
+        assert_eq!(
+            serde_json::to_string(&EdgeType::LeadsTo).unwrap(),
+            "\"leads_to\""
+        );
+    }
+
+    #[test]
+    fn test_unknown_type_suggests_closest() {
+        let err = "gaol".parse::<NodeType>().unwrap_err().to_string();
+        assert!(err.contains("Unknown node type 'gaol'"));
+        assert!(err.contains("Did you mean 'goal'?"));
+
+        let err = "choosen".parse::<EdgeType>().unwrap_err().to_string();
+        assert!(err.contains("Did you mean 'chosen'?"));
+
+        let err = "zzzzzzzz".parse::<NodeStatus>().unwrap_err().to_string();
+        assert!(!err.contains("Did you mean"));
+        assert!(err.contains("pending, active, completed, rejected"));
+    }
+
+    #[test]
+    fn test_database_rejects_unknown_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        assert!(matches!(
+            db.create_node("gaol", "Typo", None, None, None),
+            Err(DbError::Validation(_))
+        ));
+        let a = db.create_node("goal", "A", None, None, None).unwrap();
+        let b = db.create_node("action", "B", None, None, None).unwrap();
+        assert!(matches!(
+            db.create_edge(a, b, "choosen", None),
+            Err(DbError::Validation(_))
+        ));
+        assert!(matches!(
+            db.update_node_status(a, "done"),
+            Err(DbError::Validation(_))
+        ));
+        assert!(matches!(
+            db.update_node(a, None, None, Some("gaol"), None, None, None),
+            Err(DbError::Validation(_))
+        ));
+        assert!(db.get_all_nodes().unwrap().len() == 2);
+        assert!(db.get_all_edges().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_nodes_and_edges_record_author() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("test.db").to_str().unwrap())
+            .unwrap()
+            .with_author(Some("Alice <alice@example.com>".to_string()));
+
+        let a = db.create_node("goal", "G", None, None, None).unwrap();
+        let b = db.create_node("action", "A", None, None, None).unwrap();
+        db.create_edge(a, b, "leads_to", None).unwrap();
+        db.create_edge_by(b, a, "requires", None, None).unwrap();
+
+        let node = db.get_node(a).unwrap().unwrap();
+        assert_eq!(node.author.as_deref(), Some("Alice <alice@example.com>"));
+        assert_eq!(node.author_name(), Some("Alice"));
+        assert!(node.is_authored_by("ALICE"));
+        assert!(node.is_authored_by("alice@example"));
+        assert!(!node.is_authored_by("bob"));
+
+        let edges = db.get_all_edges().unwrap();
+        assert_eq!(
+            edges[0].author.as_deref(),
+            Some("Alice <alice@example.com>")
+        );
+        assert_eq!(edges[1].author, None);
+
+        // Imported nodes keep their own author
+        let mut imported = node.clone();
+        imported.change_id = "imported".to_string();
+        imported.author = Some("Bob".to_string());
+        let id = db.import_node(&imported).unwrap();
+        assert_eq!(db.get_node(id).unwrap().unwrap().author_name(), Some("Bob"));
+    }
+
+    #[test]
+    fn test_sessions_collect_new_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("test.db").to_str().unwrap()).unwrap();
+
+        let before = db.create_node("goal", "Before", None, None, None).unwrap();
+        assert!(db.end_session(None).is_err());
+
+        let session = db.start_session(Some("refactor")).unwrap();
+        assert!(session.is_open());
+        assert!(db.start_session(None).is_err());
+
+        let a = db.create_node("goal", "A", None, None, None).unwrap();
+        let b = db.create_node("action", "B", None, None, None).unwrap();
+        let ended = db.end_session(Some("did the refactor")).unwrap();
+        assert!(!ended.is_open());
+        assert_eq!(ended.summary.as_deref(), Some("did the refactor"));
+        assert_eq!(ended.root_node_id, Some(a));
+        db.create_node("goal", "After", None, None, None).unwrap();
+
+        let ids: Vec<i32> = db
+            .get_session_nodes(session.id)
+            .unwrap()
+            .iter()
+            .map(|n| n.id)
+            .collect();
+        assert_eq!(ids, vec![a, b]);
+        assert!(!ids.contains(&before));
+        assert_eq!(db.get_session_node_counts().unwrap()[&session.id], 2);
+        assert!(db.get_open_session().unwrap().is_none());
+
+        // Deleting the root detaches it from the session
+        db.delete_node(a, false).unwrap();
+        let session = db.get_session(session.id).unwrap().unwrap();
+        assert_eq!(session.root_node_id, None);
+        assert_eq!(db.get_session_nodes(session.id).unwrap().len(), 1);
+        assert_eq!(db.get_sessions().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_context_attaches_to_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("test.db").to_str().unwrap()).unwrap();
+        let node = db
+            .create_node("observation", "Tests fail", None, None, None)
+            .unwrap();
+
+        db.add_context(node, ContextType::Error, "panicked at 'boom'", None)
+            .unwrap();
+        db.add_context(
+            node,
+            ContextType::FileSnapshot,
+            "fn main() {}",
+            Some("src/main.rs"),
+        )
+        .unwrap();
+        assert!(db.add_context(99, ContextType::Url, "x", None).is_err());
+        assert!(db.import_context(node, "screenshot", "{}", "now").is_err());
+
+        let context = db.get_node_context(node).unwrap();
+        assert_eq!(context.len(), 2);
+        assert_eq!(context[0].context_type, "error");
+        assert_eq!(context[0].content(), "panicked at 'boom'");
+        assert_eq!(context[0].source(), None);
+        assert_eq!(context[1].context_type, "file-snapshot");
+        assert_eq!(context[1].source().as_deref(), Some("src/main.rs"));
+        assert_eq!(db.get_all_context().unwrap().len(), 2);
+
+        assert_eq!(
+            "benchmark".parse::<ContextType>().unwrap(),
+            ContextType::Benchmark
+        );
+        let err = "eror".parse::<ContextType>().unwrap_err().to_string();
+        assert!(err.contains("Did you mean 'error'?"));
+
+        // Deleting the node drops its context
+        db.delete_node(node, false).unwrap();
+        assert!(db.get_all_context().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_command_log_links_to_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("test.db").to_str().unwrap()).unwrap();
+        let node = db
+            .create_node("action", "Fix the parser", None, None, None)
+            .unwrap();
+
+        let first = db
+            .log_command("cargo test", None, Some("/repo"), Some(node))
+            .unwrap();
+        let long = "x".repeat(COMMAND_OUTPUT_LIMIT) + "tail";
+        db.complete_command(first, 101, Some(&long), Some("boom"), 1500)
+            .unwrap();
+        let second = db.log_command("cargo fmt", None, None, None).unwrap();
+        assert!(db.log_command("ls", None, None, Some(99)).is_err());
+        assert!(db.link_command(second, 99).is_err());
+
+        let commands = db.get_node_commands(node).unwrap();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].exit_code, Some(101));
+        assert!(!commands[0].succeeded());
+        let stdout = commands[0].stdout.as_deref().unwrap();
+        assert!(stdout.starts_with("[… 4 bytes truncated]"));
+        assert!(stdout.ends_with("tail"));
+        assert_eq!(commands[0].stderr.as_deref(), Some("boom"));
+
+        db.link_command(second, node).unwrap();
+        assert_eq!(db.get_linked_commands().unwrap().len(), 2);
+        assert_eq!(db.get_recent_commands(10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_truncate_command_output_keeps_char_boundaries() {
+        assert_eq!(truncate_command_output("short", 10), "short");
+        assert_eq!(
+            truncate_command_output("abcdef", 3),
+            "[… 3 bytes truncated]\ndef"
+        );
+        // "é" is two bytes; the cut moves forward rather than split it
+        assert_eq!(
+            truncate_command_output("aéb", 2),
+            "[… 3 bytes truncated]\nb"
+        );
+    }
+
+    #[test]
+    fn test_output_tail_stays_within_limit() {
+        let mut short = OutputTail::new(64);
+        short.push(b"short");
+        assert_eq!(short.into_string(), "short");
+
+        let mut tail = OutputTail::new(64);
+        for _ in 0..10 {
+            tail.push(b"0123456789");
+        }
+        tail.push(b"tail end");
+        let text = tail.into_string();
+        assert!(text.len() <= 64);
+        assert!(text.starts_with("[… 70 bytes truncated]\n"));
+        assert!(text.ends_with("0123456789tail end"));
+        // Storing it keeps it as-is
+        assert_eq!(truncate_command_output(&text, 64), text);
+    }
+
+    #[test]
+    fn test_resolve_author_prefers_config() {
+        let config = AuthorConfig {
+            name: Some("Alice".to_string()),
+            email: Some("alice@example.com".to_string()),
+        };
+        assert_eq!(
+            resolve_author(&config).as_deref(),
+            Some("Alice <alice@example.com>")
+        );
+        assert_eq!(author_name("Alice <alice@example.com>"), "Alice");
+        assert_eq!(author_name("alice@example.com"), "alice@example.com");
+    }
+
+    #[test]
+    fn test_database_accepts_custom_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap())
+            .unwrap()
+            .with_custom_types(TypesConfig {
+                node: vec!["hypothesis".to_string()],
+                edge: vec!["supersedes".to_string()],
+                status: vec!["blocked".to_string()],
+            });
+
+        let a = db.create_node("hypothesis", "H", None, None, None).unwrap();
+        let b = db.create_node("goal", "G", None, None, None).unwrap();
+        db.create_edge(a, b, "supersedes", None).unwrap();
+        db.update_node_status(a, "blocked").unwrap();
+
+        let err = db.validate_node_type("hypothesys").unwrap_err().to_string();
+        assert!(err.contains("Did you mean 'hypothesis'?"));
+    }
+
     // === update_node_commit Tests ===
 
     #[test]
@@ -2343,4 +4672,292 @@ mod tests {
 
         assert_eq!(meta.get("commit").unwrap(), "new_commit_hash");
     }
+
+    // === update_node Tests ===
+
+    #[test]
+    fn test_update_node_changes_fields_and_preserves_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let node_id = db
+            .create_node_full(
+                "option",
+                "Use Redis",
+                Some("In-memory cache"),
+                Some(60),
+                Some("abc1234"),
+                Some("User prompt"),
+                Some("a.rs"),
+                Some("main"),
+            )
+            .unwrap();
+
+        db.update_node(
+            node_id,
+            Some("Use Redis for sessions"),
+            None,
+            Some("decision"),
+            Some(Some(80)),
+            Some("a.rs, b.rs"),
+            None,
+        )
+        .unwrap();
+
+        let node = db.get_node(node_id).unwrap().unwrap();
+        assert_eq!(node.title, "Use Redis for sessions");
+        assert_eq!(node.node_type, "decision");
+        assert_eq!(node.description.as_deref(), Some("In-memory cache"));
+
+        let meta: serde_json::Value =
+            serde_json::from_str(node.metadata_json.as_ref().unwrap()).unwrap();
+        assert_eq!(meta.get("confidence").unwrap(), 80);
+        assert_eq!(meta.get("files").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(meta.get("commit").unwrap(), "abc1234");
+        assert_eq!(meta.get("prompt").unwrap(), "User prompt");
+        assert_eq!(meta.get("branch").unwrap(), "main");
+    }
+
+    #[test]
+    fn test_update_node_empty_string_clears() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let node_id = db
+            .create_node_full(
+                "goal",
+                "Goal",
+                Some("desc"),
+                Some(70),
+                None,
+                None,
+                None,
+                Some("feature-x"),
+            )
+            .unwrap();
+
+        db.update_node(node_id, None, Some(""), None, Some(None), None, Some(""))
+            .unwrap();
+
+        let node = db.get_node(node_id).unwrap().unwrap();
+        assert!(node.description.is_none());
+        assert!(node.metadata_json.is_none());
+    }
+
+    #[test]
+    fn test_update_node_missing_node() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let result = db.update_node(42, Some("x"), None, None, None, None, None);
+        assert!(matches!(result, Err(DbError::Validation(_))));
+    }
+
+    #[test]
+    fn test_delete_node_removes_edges_and_records_tombstones() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let goal = db.create_node("goal", "Goal", None, None, None).unwrap();
+        let action = db
+            .create_node("action", "Action", None, None, None)
+            .unwrap();
+        db.create_edge(goal, action, "leads_to", None).unwrap();
+        let action_cid = db.get_node(action).unwrap().unwrap().change_id;
+
+        let result = db.delete_node(action, false).unwrap();
+        assert_eq!(result.nodes_deleted, vec![action]);
+        assert_eq!(result.edges_deleted, 1);
+
+        assert!(db.get_node(action).unwrap().is_none());
+        assert!(db.get_node(goal).unwrap().is_some());
+        assert!(db.get_all_edges().unwrap().is_empty());
+
+        let tombstones = db.get_tombstones().unwrap();
+        assert_eq!(tombstones.len(), 2);
+        assert!(tombstones
+            .iter()
+            .any(|t| t.kind == TOMBSTONE_NODE && t.change_id == action_cid));
+        assert!(tombstones.iter().any(|t| t.kind == TOMBSTONE_EDGE));
+    }
+
+    #[test]
+    fn test_delete_node_cascade_keeps_shared_children() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let root = db.create_node("goal", "Root", None, None, None).unwrap();
+        let other = db.create_node("goal", "Other", None, None, None).unwrap();
+        let only_child = db.create_node("action", "Only", None, None, None).unwrap();
+        let grandchild = db
+            .create_node("outcome", "Grand", None, None, None)
+            .unwrap();
+        let shared = db
+            .create_node("action", "Shared", None, None, None)
+            .unwrap();
+        db.create_edge(root, only_child, "leads_to", None).unwrap();
+        db.create_edge(only_child, grandchild, "leads_to", None)
+            .unwrap();
+        db.create_edge(root, shared, "leads_to", None).unwrap();
+        db.create_edge(other, shared, "leads_to", None).unwrap();
+
+        let mut deleted = db.delete_node(root, true).unwrap().nodes_deleted;
+        deleted.sort();
+        assert_eq!(deleted, vec![root, only_child, grandchild]);
+
+        assert!(db.get_node(shared).unwrap().is_some());
+        assert!(db.get_node(other).unwrap().is_some());
+        let edges = db.get_all_edges().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(
+            (edges[0].from_node_id, edges[0].to_node_id),
+            (other, shared)
+        );
+    }
+
+    #[test]
+    fn test_delete_missing_node() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let result = db.delete_node(42, false);
+        assert!(matches!(result, Err(DbError::Validation(_))));
+    }
+
+    #[test]
+    fn test_delete_edges_filters_by_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let a = db.create_node("decision", "A", None, None, None).unwrap();
+        let b = db.create_node("option", "B", None, None, None).unwrap();
+        db.create_edge(a, b, "leads_to", None).unwrap();
+        db.create_edge(a, b, "chosen", None).unwrap();
+
+        assert_eq!(db.delete_edges(a, b, Some("chosen")).unwrap(), 1);
+        assert_eq!(db.delete_edges(a, b, Some("chosen")).unwrap(), 0);
+        assert_eq!(db.get_all_edges().unwrap().len(), 1);
+        assert_eq!(db.delete_edges(a, b, None).unwrap(), 1);
+        assert_eq!(db.get_tombstones().unwrap().len(), 2);
+    }
+
+    // === merge_nodes Tests ===
+
+    #[test]
+    fn test_merge_nodes_rewires_edges() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let keep = db
+            .create_node("goal", "Add auth", None, None, None)
+            .unwrap();
+        let dup = db
+            .create_node("goal", "Add auth", None, None, None)
+            .unwrap();
+        let a = db.create_node("action", "A", None, None, None).unwrap();
+        let b = db.create_node("action", "B", None, None, None).unwrap();
+        db.create_edge(keep, a, "leads_to", None).unwrap();
+        db.create_edge(dup, a, "leads_to", None).unwrap(); // duplicate after merge
+        db.create_edge(dup, b, "leads_to", None).unwrap(); // moved
+        db.create_edge(keep, dup, "leads_to", None).unwrap(); // self-loop after merge
+        let dup_cid = db.get_node(dup).unwrap().unwrap().change_id;
+        let keep_cid = db.get_node(keep).unwrap().unwrap().change_id;
+
+        let result = db.merge_nodes(keep, &[dup]).unwrap();
+        assert_eq!(result.edges_moved, 1);
+        assert_eq!(result.edges_dropped, 2);
+        assert_eq!(result.aliases, vec![dup_cid.clone()]);
+
+        assert!(db.get_node(dup).unwrap().is_none());
+        let mut targets: Vec<(i32, i32)> = db
+            .get_all_edges()
+            .unwrap()
+            .iter()
+            .map(|e| (e.from_node_id, e.to_node_id))
+            .collect();
+        targets.sort();
+        assert_eq!(targets, vec![(keep, a), (keep, b)]);
+        assert!(db
+            .get_all_edges()
+            .unwrap()
+            .iter()
+            .all(|e| e.from_change_id.as_deref() == Some(keep_cid.as_str())));
+
+        let aliases = db.get_change_id_aliases().unwrap();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[0].alias, dup_cid);
+        assert_eq!(aliases[0].change_id, keep_cid);
+    }
+
+    #[test]
+    fn test_merge_nodes_merges_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let older = db
+            .create_node_full(
+                "goal",
+                "Goal",
+                None,
+                None,
+                Some("aaa1111"),
+                Some("first prompt"),
+                Some("a.rs,b.rs"),
+                None,
+            )
+            .unwrap();
+        let keep = db
+            .create_node_full(
+                "goal",
+                "Goal",
+                None,
+                Some(90),
+                Some("bbb2222"),
+                None,
+                Some("b.rs,c.rs"),
+                Some("main"),
+            )
+            .unwrap();
+
+        db.merge_nodes(keep, &[older]).unwrap();
+
+        let node = db.get_node(keep).unwrap().unwrap();
+        let meta: serde_json::Value =
+            serde_json::from_str(node.metadata_json.as_ref().unwrap()).unwrap();
+        assert_eq!(meta["commit"], "aaa1111");
+        assert_eq!(meta["prompt"], "first prompt");
+        assert_eq!(meta["files"], json!(["b.rs", "c.rs", "a.rs"]));
+        assert_eq!(meta["confidence"], 90);
+        assert_eq!(meta["branch"], "main");
+    }
+
+    #[test]
+    fn test_merge_nodes_rejects_invalid_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let keep = db.create_node("goal", "Goal", None, None, None).unwrap();
+        assert!(matches!(
+            db.merge_nodes(keep, &[keep]),
+            Err(DbError::Validation(_))
+        ));
+        assert!(matches!(
+            db.merge_nodes(keep, &[99]),
+            Err(DbError::Validation(_))
+        ));
+        assert!(matches!(
+            db.merge_nodes(keep, &[]),
+            Err(DbError::Validation(_))
+        ));
+    }
 }
@@ -3,13 +3,92 @@
 //! Implements jj-inspired change_id based syncing between local databases
 //! and version-controlled patch files.
 
-use crate::db::{Database, DecisionEdge, DecisionNode};
+use crate::db::{
+    edge_tombstone_key, ContextType, Database, DbError, DecisionContext, DecisionEdge,
+    DecisionNode, NewAppliedPatch, NewAppliedPatchItem, NodeConflict, NodeSyncBase, Tombstone,
+    TOMBSTONE_EDGE, TOMBSTONE_NODE,
+};
+use crate::roadmap::compute_hash;
+use crate::signing::{KeyStore, PatchIntegrity, PatchSignature};
+use ed25519_dalek::SigningKey;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 
+/// Patch format version written by this build
+///
+/// 1.1 added `updated_at`, `base_hash`, `stub`, `author` (on nodes and edges),
+/// `tombstones`, `digest` and `signature`, all optional. 1.2 added `context`, and
+/// 1.3 added `ancestor_hashes` on nodes.
+pub const PATCH_VERSION: &str = "1.3";
+
+/// Upgrade steps between patch format versions, applied in order on load
+type PatchUpgrade = fn(&mut Map<String, Value>);
+const PATCH_UPGRADES: &[(&str, &str, PatchUpgrade)] = &[
+    ("1.0", "1.1", upgrade_1_0),
+    ("1.1", "1.2", upgrade_1_1),
+    ("1.2", "1.3", upgrade_1_2),
+];
+
+/// 1.0 -> 1.1: every new field is optional, so the content carries over as-is
+fn upgrade_1_0(_patch: &mut Map<String, Value>) {}
+
+/// 1.1 -> 1.2: `context` is optional too
+fn upgrade_1_1(_patch: &mut Map<String, Value>) {}
+
+/// 1.2 -> 1.3: nodes without `ancestor_hashes` only know their `base_hash`
+fn upgrade_1_2(_patch: &mut Map<String, Value>) {}
+
+/// Parse "major.minor" for ordering versions
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Bring raw patch JSON up to [`PATCH_VERSION`].
+///
+/// Returns the version the patch was written in if it had to be upgraded. Patches
+/// from a newer deciduous are rejected rather than half-understood.
+pub fn upgrade_patch_json(value: &mut Value) -> Result<Option<String>, String> {
+    let Value::Object(map) = value else {
+        return Err("patch must be a JSON object".to_string());
+    };
+    let original = match map.get("version") {
+        Some(Value::String(v)) => v.clone(),
+        Some(_) => return Err("patch version must be a string".to_string()),
+        None => return Err("patch has no version".to_string()),
+    };
+    let parsed = parse_version(&original)
+        .ok_or_else(|| format!("unrecognized patch format version '{}'", original))?;
+    let current = parse_version(PATCH_VERSION).expect("PATCH_VERSION is valid");
+    if parsed > current {
+        return Err(format!(
+            "patch format {} is newer than this deciduous supports ({}); upgrade deciduous",
+            original, PATCH_VERSION
+        ));
+    }
+    if original == PATCH_VERSION {
+        return Ok(None);
+    }
+
+    let mut version = original.clone();
+    while version != PATCH_VERSION {
+        let (_, to, upgrade) = PATCH_UPGRADES
+            .iter()
+            .find(|(from, _, _)| *from == version)
+            .ok_or_else(|| format!("no upgrade path from patch format {}", version))?;
+        upgrade(map);
+        version = to.to_string();
+    }
+    map.insert("version".to_string(), Value::String(version));
+    Ok(Some(original))
+}
+
 /// A patch file containing nodes and edges to sync
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GraphPatch {
     /// Patch format version
     pub version: String,
@@ -25,10 +104,25 @@ pub struct GraphPatch {
     pub nodes: Vec<PatchNode>,
     /// Edges included in this patch
     pub edges: Vec<PatchEdge>,
+    /// Deleted nodes and edges, so applying the patch won't resurrect them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tombstones: Vec<PatchTombstone>,
+    /// Context (error logs, benchmark output, ...) attached to nodes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<PatchContext>,
+    /// SHA-256 of everything else in the patch, set on export
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    /// Optional signature over `digest`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<PatchSignature>,
+    /// Version the patch was written in, if it was upgraded on load
+    #[serde(skip)]
+    upgraded_from: Option<String>,
 }
 
 /// A node in a patch file (uses change_id, not integer id)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PatchNode {
     /// Globally unique change ID
     pub change_id: String,
@@ -44,10 +138,372 @@ pub struct PatchNode {
     pub metadata_json: Option<String>,
     /// Created timestamp
     pub created_at: String,
+    /// Last-modified timestamp; a newer copy replaces the local node on apply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Content hash of the version this copy was edited from, as last synced by the
+    /// exporter. Lets apply tell a fast-forward from a concurrent edit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_hash: Option<String>,
+    /// Versions shared before `base_hash`, newest first. A receiver that missed
+    /// some exports can still fast-forward from any of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ancestor_hashes: Vec<String>,
+    /// Unchanged node included only because an exported edge points at it. Apply
+    /// creates it if missing but never updates an existing node from it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stub: bool,
+    /// Who created the node; nodes without one are credited to the patch author
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+impl PatchNode {
+    /// Whether this copy has the same content as a local node, ignoring timestamps
+    pub fn same_content(&self, node: &DecisionNode) -> bool {
+        NodeContent::of_patch_node(self) == NodeContent::of_node(node)
+    }
+
+    /// The node this entry describes, under a local ID. Status, timestamps and the
+    /// raw metadata JSON (including keys this version doesn't know) are kept as-is;
+    /// entries without `updated_at` fall back to `created_at`.
+    pub fn to_node(&self, id: i32) -> DecisionNode {
+        DecisionNode {
+            id,
+            change_id: self.change_id.clone(),
+            node_type: self.node_type.clone(),
+            title: self.title.clone(),
+            description: self.description.clone(),
+            status: self.status.clone(),
+            created_at: self.created_at.clone(),
+            updated_at: self
+                .updated_at
+                .clone()
+                .unwrap_or_else(|| self.created_at.clone()),
+            metadata_json: self.metadata_json.clone(),
+            author: self.author.clone(),
+        }
+    }
+
+    /// Content hashes this copy descends from, newest first
+    pub fn lineage(&self) -> Vec<String> {
+        self.base_hash
+            .iter()
+            .chain(&self.ancestor_hashes)
+            .cloned()
+            .collect()
+    }
+
+    /// When this copy was last modified (`created_at` for old patches)
+    pub fn modified_at(&self) -> &str {
+        self.updated_at.as_deref().unwrap_or(&self.created_at)
+    }
+
+    /// Whether this copy was modified after the local node. Patches written before
+    /// nodes carried `updated_at` are never considered newer.
+    pub fn is_newer_than(&self, node: &DecisionNode) -> bool {
+        self.updated_at
+            .as_deref()
+            .is_some_and(|t| compare_timestamps(t, &node.updated_at) == Ordering::Greater)
+    }
+}
+
+/// Most earlier versions remembered per node; past this, edits fall back to a merge
+const MAX_LINEAGE: usize = 64;
+
+/// Content hashes in order with repeats removed, capped at [`MAX_LINEAGE`]
+fn bounded_lineage(hashes: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    hashes
+        .into_iter()
+        .filter(|h| seen.insert(h.clone()))
+        .take(MAX_LINEAGE)
+        .collect()
+}
+
+/// Compare RFC 3339 timestamps by instant (they may carry different UTC offsets),
+/// falling back to string order if either fails to parse
+fn compare_timestamps(a: &str, b: &str) -> Ordering {
+    match (
+        chrono::DateTime::parse_from_rfc3339(a),
+        chrono::DateTime::parse_from_rfc3339(b),
+    ) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+/// The part of a node that sync compares and merges: everything but IDs and timestamps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeContent {
+    pub node_type: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub status: String,
+    /// Metadata object; its keys merge independently
+    #[serde(default)]
+    pub metadata: Map<String, Value>,
+}
+
+impl NodeContent {
+    pub fn of_node(node: &DecisionNode) -> Self {
+        Self::from_parts(
+            &node.node_type,
+            &node.title,
+            &node.description,
+            &node.status,
+            node.metadata_json.as_deref(),
+        )
+    }
+
+    pub fn of_patch_node(node: &PatchNode) -> Self {
+        Self::from_parts(
+            &node.node_type,
+            &node.title,
+            &node.description,
+            &node.status,
+            node.metadata_json.as_deref(),
+        )
+    }
+
+    fn from_parts(
+        node_type: &str,
+        title: &str,
+        description: &Option<String>,
+        status: &str,
+        metadata_json: Option<&str>,
+    ) -> Self {
+        let metadata = match metadata_json.and_then(|j| serde_json::from_str(j).ok()) {
+            Some(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        Self {
+            node_type: node_type.to_string(),
+            title: title.to_string(),
+            description: description.clone(),
+            status: status.to_string(),
+            metadata,
+        }
+    }
+
+    /// Parse content stored by `to_json`
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+
+    /// Canonical JSON (metadata keys are sorted)
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// SHA-256 of the canonical JSON
+    pub fn hash(&self) -> String {
+        compute_hash(&self.to_json())
+    }
+
+    /// Metadata as stored in `metadata_json` (None when empty)
+    pub fn metadata_json(&self) -> Option<String> {
+        if self.metadata.is_empty() {
+            None
+        } else {
+            Some(Value::Object(self.metadata.clone()).to_string())
+        }
+    }
+
+    /// `node` with this content, keeping its IDs and created_at
+    pub fn apply_to(&self, node: &DecisionNode, updated_at: &str) -> DecisionNode {
+        DecisionNode {
+            node_type: self.node_type.clone(),
+            title: self.title.clone(),
+            description: self.description.clone(),
+            status: self.status.clone(),
+            metadata_json: self.metadata_json(),
+            updated_at: updated_at.to_string(),
+            ..node.clone()
+        }
+    }
+
+    /// Display value of a field named as in `ContentMerge::conflicts`
+    pub fn field(&self, name: &str) -> String {
+        match name {
+            "type" => self.node_type.clone(),
+            "title" => self.title.clone(),
+            "description" => self.description.clone().unwrap_or_default(),
+            "status" => self.status.clone(),
+            _ => name
+                .strip_prefix("metadata.")
+                .and_then(|key| self.metadata.get(key))
+                .map(Value::to_string)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Names of the fields that differ from `other`
+    pub fn differing_fields(&self, other: &NodeContent) -> Vec<String> {
+        merge_content(self, self, other).changed
+    }
+}
+
+/// Result of a field-by-field three-way merge
+#[derive(Debug, Clone)]
+pub struct ContentMerge {
+    /// Merged content; fields changed differently on both sides keep our value
+    pub merged: NodeContent,
+    /// Fields changed differently on both sides ("metadata.<key>" for metadata keys)
+    pub conflicts: Vec<String>,
+    /// Fields taken from their side
+    pub changed: Vec<String>,
+}
+
+/// Merge two edited versions of a node against their common ancestor.
+///
+/// A field changed on one side only takes that side's value. Metadata keys are
+/// merged one by one, so different keys edited on each side both survive.
+pub fn merge_content(base: &NodeContent, ours: &NodeContent, theirs: &NodeContent) -> ContentMerge {
+    let mut conflicts = Vec::new();
+    let mut changed = Vec::new();
+    let mut pick = |field: &str, base: Value, ours: Value, theirs: Value| -> Value {
+        if ours == theirs || theirs == base {
+            ours
+        } else if ours == base {
+            changed.push(field.to_string());
+            theirs
+        } else {
+            conflicts.push(field.to_string());
+            ours
+        }
+    };
+
+    let text = |s: &str| Value::String(s.to_string());
+    let node_type = pick(
+        "type",
+        text(&base.node_type),
+        text(&ours.node_type),
+        text(&theirs.node_type),
+    );
+    let title = pick(
+        "title",
+        text(&base.title),
+        text(&ours.title),
+        text(&theirs.title),
+    );
+    let description = pick(
+        "description",
+        base.description.clone().into(),
+        ours.description.clone().into(),
+        theirs.description.clone().into(),
+    );
+    let status = pick(
+        "status",
+        text(&base.status),
+        text(&ours.status),
+        text(&theirs.status),
+    );
+
+    let keys: BTreeSet<&String> = base
+        .metadata
+        .keys()
+        .chain(ours.metadata.keys())
+        .chain(theirs.metadata.keys())
+        .collect();
+    let mut metadata = Map::new();
+    for key in keys {
+        // Value::Null stands in for an absent key
+        let get = |c: &NodeContent| c.metadata.get(key).cloned().unwrap_or(Value::Null);
+        let value = pick(
+            &format!("metadata.{}", key),
+            get(base),
+            get(ours),
+            get(theirs),
+        );
+        if !value.is_null() {
+            metadata.insert(key.clone(), value);
+        }
+    }
+
+    let as_text = |v: Value| v.as_str().unwrap_or_default().to_string();
+    ContentMerge {
+        merged: NodeContent {
+            node_type: as_text(node_type),
+            title: as_text(title),
+            description: description.as_str().map(String::from),
+            status: as_text(status),
+            metadata,
+        },
+        conflicts,
+        changed,
+    }
+}
+
+/// Turn a `--since` value into an RFC 3339 timestamp. Accepts an RFC 3339 timestamp,
+/// a date (YYYY-MM-DD, local midnight) or a git ref (its commit time).
+pub fn resolve_since(spec: &str) -> Result<String, String> {
+    if chrono::DateTime::parse_from_rfc3339(spec).is_ok() {
+        return Ok(spec.to_string());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(spec, "%Y-%m-%d") {
+        if let Some(midnight) = date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|dt| dt.and_local_timezone(chrono::Local).earliest())
+        {
+            return Ok(midnight.to_rfc3339());
+        }
+    }
+
+    let output = std::process::Command::new("git")
+        .args(["log", "-1", "--format=%cI", spec, "--"])
+        .output()
+        .map_err(|e| format!("Running git: {}", e))?;
+    let time = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !time.is_empty() {
+        Ok(time)
+    } else {
+        Err(format!(
+            "'{}' is not a timestamp (RFC 3339 or YYYY-MM-DD) or a git ref",
+            spec
+        ))
+    }
+}
+
+/// Canonical form of an export's node filters, recorded so `--since-last` only
+/// continues from an export that covered the same nodes. None means unfiltered.
+pub fn export_filter(
+    node_ids: Option<&[i32]>,
+    branch: Option<&str>,
+    tags: &[String],
+) -> Option<String> {
+    if node_ids.is_none() && branch.is_none() && tags.is_empty() {
+        return None;
+    }
+    let node_ids = node_ids.map(|ids| ids.iter().copied().collect::<BTreeSet<i32>>());
+    let tags: BTreeSet<&str> = tags.iter().map(String::as_str).collect();
+    Some(serde_json::json!({ "nodes": node_ids, "branch": branch, "tags": tags }).to_string())
+}
+
+/// How to settle a node conflict
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictResolution {
+    /// Keep the local version
+    Ours,
+    /// Take the version from the patch
+    Theirs,
+    /// Replace the node with hand-merged content
+    Edit(NodeContent),
+}
+
+impl ConflictResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictResolution::Ours => "ours",
+            ConflictResolution::Theirs => "theirs",
+            ConflictResolution::Edit(_) => "edit",
+        }
+    }
 }
 
 /// An edge in a patch file (uses change_ids for references)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PatchEdge {
     /// Source node change_id
     pub from_change_id: String,
@@ -57,6 +513,42 @@ pub struct PatchEdge {
     pub edge_type: String,
     /// Optional rationale for the edge
     pub rationale: Option<String>,
+    /// Who created the edge; edges without one are credited to the patch author
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+/// A context item in a patch file, attached to a node by change_id
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct PatchContext {
+    /// change_id of the node the context belongs to
+    pub node_change_id: String,
+    /// file-snapshot, error, benchmark, url or snippet
+    pub context_type: String,
+    /// Captured content as JSON (`{"content": ..., "source": ...}`)
+    pub content_json: String,
+    /// When the context was captured
+    pub captured_at: String,
+}
+
+/// A deletion record in a patch file
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PatchTombstone {
+    /// What was deleted: "node" or "edge"
+    pub kind: String,
+    /// Node change_id, or edge key for edges
+    pub change_id: String,
+    /// Source node change_id (edges only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_change_id: Option<String>,
+    /// Target node change_id (edges only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_change_id: Option<String>,
+    /// Edge type (edges only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge_type: Option<String>,
+    /// When the deletion happened
+    pub deleted_at: String,
 }
 
 impl GraphPatch {
@@ -67,13 +559,18 @@ impl GraphPatch {
         base_commit: Option<String>,
     ) -> Self {
         Self {
-            version: "1.0".to_string(),
+            version: PATCH_VERSION.to_string(),
             author,
             branch,
             created_at: chrono::Local::now().to_rfc3339(),
             base_commit,
             nodes: Vec::new(),
             edges: Vec::new(),
+            tombstones: Vec::new(),
+            context: Vec::new(),
+            digest: None,
+            signature: None,
+            upgraded_from: None,
         }
     }
 
@@ -81,7 +578,23 @@ impl GraphPatch {
     pub fn load(path: &Path) -> Result<Self, String> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| format!("Failed to read patch file: {}", e))?;
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse patch JSON: {}", e))
+        Self::from_json(&content)
+    }
+
+    /// Parse patch JSON, upgrading older format versions
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut value: Value =
+            serde_json::from_str(json).map_err(|e| format!("Failed to parse patch JSON: {}", e))?;
+        let upgraded_from = upgrade_patch_json(&mut value)?;
+        let mut patch: Self = serde_json::from_value(value)
+            .map_err(|e| format!("Failed to parse patch JSON: {}", e))?;
+        patch.upgraded_from = upgraded_from;
+        Ok(patch)
+    }
+
+    /// Format version the patch was written in before being upgraded on load
+    pub fn upgraded_from(&self) -> Option<&str> {
+        self.upgraded_from.as_deref()
     }
 
     /// Save the patch to a JSON file
@@ -98,8 +611,132 @@ impl GraphPatch {
         std::fs::write(path, content).map_err(|e| format!("Failed to write patch file: {}", e))
     }
 
+    /// Hash of the patch content, leaving out the digest and signature themselves.
+    /// An upgraded patch is hashed under the version it was written in.
+    pub fn compute_digest(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut value {
+            map.remove("digest");
+            map.remove("signature");
+            if let Some(original) = &self.upgraded_from {
+                map.insert("version".to_string(), Value::String(original.clone()));
+            }
+        }
+        // Object keys serialize sorted, so this is stable across field order
+        compute_hash(&value.to_string())
+    }
+
+    /// Digest identifying the patch: the recorded one, or computed for legacy patches
+    pub fn effective_digest(&self) -> String {
+        self.digest.clone().unwrap_or_else(|| self.compute_digest())
+    }
+
+    /// Record the content digest, dropping any signature over an older one
+    pub fn seal(&mut self) {
+        self.upgraded_from = None;
+        self.signature = None;
+        self.digest = Some(self.compute_digest());
+    }
+
+    /// Seal the patch and sign its digest
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.upgraded_from = None;
+        let digest = self.compute_digest();
+        self.signature = Some(PatchSignature::create(key, &digest));
+        self.digest = Some(digest);
+    }
+
+    /// Check that the patch is unchanged since export and, if signed, that the
+    /// signature holds.
+    ///
+    /// With `require_signed`, the patch must also be signed by a key in `keys`.
+    /// Only 1.0 patches, written before digests existed, may lack one, and they pass
+    /// only without `require_signed`.
+    pub fn verify(&self, keys: &KeyStore, require_signed: bool) -> Result<PatchIntegrity, String> {
+        let integrity = match (&self.digest, &self.signature) {
+            (None, None) if self.upgraded_from() == Some("1.0") => PatchIntegrity::Unsealed,
+            (None, None) => {
+                return Err(format!(
+                    "patch has no digest, but format {} patches are sealed on export \
+                     (it was modified or stripped)",
+                    self.upgraded_from().unwrap_or(&self.version)
+                ))
+            }
+            (None, Some(_)) => return Err("patch is signed but has no digest".to_string()),
+            (Some(digest), signature) => {
+                if *digest != self.compute_digest() {
+                    return Err("digest mismatch: patch was modified after export".to_string());
+                }
+                match signature {
+                    None => PatchIntegrity::Sealed,
+                    Some(signature) => {
+                        signature.verify(digest)?;
+                        PatchIntegrity::Signed {
+                            key_id: signature.key_id().to_string(),
+                            trusted: keys.is_trusted(&signature.public_key),
+                        }
+                    }
+                }
+            }
+        };
+
+        if require_signed {
+            match &integrity {
+                PatchIntegrity::Signed { trusted: true, .. } => {}
+                PatchIntegrity::Signed { key_id, .. } => {
+                    return Err(format!(
+                        "signed by untrusted key {} (add its .pub file to {})",
+                        key_id,
+                        keys.dir().display()
+                    ))
+                }
+                _ => return Err("patch is not signed and signing is required".to_string()),
+            }
+        }
+        Ok(integrity)
+    }
+
+    /// Sort nodes, edges and tombstones into a stable order so equal graphs
+    /// serialize identically: nodes by creation time, the rest by their keys
+    pub fn canonicalize(&mut self) {
+        self.nodes.sort_by(|a, b| {
+            compare_timestamps(&a.created_at, &b.created_at)
+                .then_with(|| a.change_id.cmp(&b.change_id))
+        });
+        self.edges.sort_by(|a, b| {
+            (&a.from_change_id, &a.to_change_id, &a.edge_type).cmp(&(
+                &b.from_change_id,
+                &b.to_change_id,
+                &b.edge_type,
+            ))
+        });
+        self.tombstones
+            .sort_by(|a, b| (&a.kind, &a.change_id).cmp(&(&b.kind, &b.change_id)));
+        self.context.sort_by(|a, b| {
+            (
+                &a.node_change_id,
+                &a.captured_at,
+                &a.context_type,
+                &a.content_json,
+            )
+                .cmp(&(
+                    &b.node_change_id,
+                    &b.captured_at,
+                    &b.context_type,
+                    &b.content_json,
+                ))
+        });
+    }
+
     /// Add a node to the patch
     pub fn add_node(&mut self, node: &DecisionNode) {
+        self.add_node_with_base(node, Vec::new());
+    }
+
+    /// Add a node to the patch, recording the content hashes it descends from
+    /// (newest first; the first is the version it was edited from)
+    pub fn add_node_with_base(&mut self, node: &DecisionNode, lineage: Vec<String>) {
+        let mut lineage = lineage.into_iter();
         self.nodes.push(PatchNode {
             change_id: node.change_id.clone(),
             node_type: node.node_type.clone(),
@@ -108,9 +745,27 @@ impl GraphPatch {
             status: node.status.clone(),
             metadata_json: node.metadata_json.clone(),
             created_at: node.created_at.clone(),
+            updated_at: Some(node.updated_at.clone()),
+            base_hash: lineage.next(),
+            ancestor_hashes: lineage.collect(),
+            stub: false,
+            author: node.author.clone(),
         });
     }
 
+    /// Add an unchanged node that exported edges reference
+    pub fn add_stub(&mut self, node: &DecisionNode) {
+        self.add_node(node);
+        if let Some(stub) = self.nodes.last_mut() {
+            stub.stub = true;
+        }
+    }
+
+    /// Number of stub nodes in the patch
+    pub fn stub_count(&self) -> usize {
+        self.nodes.iter().filter(|n| n.stub).count()
+    }
+
     /// Add an edge to the patch
     pub fn add_edge(&mut self, edge: &DecisionEdge) {
         if let (Some(from_cid), Some(to_cid)) = (&edge.from_change_id, &edge.to_change_id) {
@@ -119,9 +774,32 @@ impl GraphPatch {
                 to_change_id: to_cid.clone(),
                 edge_type: edge.edge_type.clone(),
                 rationale: edge.rationale.clone(),
+                author: edge.author.clone(),
             });
         }
     }
+
+    /// Add a context item belonging to the node with `node_change_id`
+    pub fn add_context(&mut self, node_change_id: &str, context: &DecisionContext) {
+        self.context.push(PatchContext {
+            node_change_id: node_change_id.to_string(),
+            context_type: context.context_type.clone(),
+            content_json: context.content_json.clone(),
+            captured_at: context.captured_at.clone(),
+        });
+    }
+
+    /// Add a tombstone to the patch
+    pub fn add_tombstone(&mut self, tombstone: &Tombstone) {
+        self.tombstones.push(PatchTombstone {
+            kind: tombstone.kind.clone(),
+            change_id: tombstone.change_id.clone(),
+            from_change_id: tombstone.from_change_id.clone(),
+            to_change_id: tombstone.to_change_id.clone(),
+            edge_type: tombstone.edge_type.clone(),
+            deleted_at: tombstone.deleted_at.clone(),
+        });
+    }
 }
 
 /// Result of applying a patch
@@ -129,34 +807,273 @@ impl GraphPatch {
 pub struct ApplyResult {
     /// Number of nodes added
     pub nodes_added: usize,
-    /// Number of nodes skipped (already existed)
+    /// Number of nodes skipped (already existed with the same content)
     pub nodes_skipped: usize,
+    /// Number of existing nodes replaced by a newer copy from the patch
+    pub nodes_updated: usize,
+    /// Existing nodes that differ from the patch but weren't replaced because the
+    /// patch copy isn't newer
+    pub nodes_diverged: Vec<String>,
+    /// Number of existing nodes edited on both sides whose changes merged cleanly
+    pub nodes_merged: usize,
+    /// Nodes edited on both sides in ways that couldn't be merged (recorded for
+    /// `deciduous diff conflicts`)
+    pub conflicts: Vec<String>,
     /// Number of edges added
     pub edges_added: usize,
     /// Number of edges skipped (already existed)
     pub edges_skipped: usize,
-    /// Edges that couldn't be created (missing nodes)
+    /// Nodes that couldn't be created (unknown node type)
+    pub nodes_failed: Vec<String>,
+    /// Edges that couldn't be created (missing nodes or unknown edge type)
     pub edges_failed: Vec<String>,
+    /// Number of local nodes deleted by tombstones in the patch
+    pub nodes_deleted: usize,
+    /// Number of local edges deleted by tombstones in the patch
+    pub edges_deleted: usize,
+    /// change_ids of the nodes this patch created
+    pub nodes_introduced: Vec<String>,
+    /// (from_change_id, to_change_id, edge_type) of the edges this patch created
+    pub edges_introduced: Vec<(String, String, String)>,
+    /// Number of context items added
+    pub context_added: usize,
+    /// Number of context items skipped (already present, or their node was deleted)
+    pub context_skipped: usize,
+    /// Context items that couldn't be attached (missing node or unknown type)
+    pub context_failed: Vec<String>,
+}
+
+/// Order patches so each comes after the patches providing the nodes its edges
+/// and stubs point at; otherwise oldest first. Returns indices into `patches`.
+///
+/// Patches caught in a dependency cycle keep their creation order.
+pub fn order_patches(patches: &[GraphPatch]) -> Vec<usize> {
+    let by_age = |a: &usize, b: &usize| {
+        compare_timestamps(&patches[*a].created_at, &patches[*b].created_at).then(a.cmp(b))
+    };
+    let mut oldest_first: Vec<usize> = (0..patches.len()).collect();
+    oldest_first.sort_by(by_age);
+
+    // The oldest patch carrying a full copy of a node provides it
+    let mut provider: HashMap<&str, usize> = HashMap::new();
+    for &i in &oldest_first {
+        for node in patches[i].nodes.iter().filter(|n| !n.stub) {
+            provider.entry(node.change_id.as_str()).or_insert(i);
+        }
+    }
+
+    let deps: Vec<BTreeSet<usize>> = patches
+        .iter()
+        .enumerate()
+        .map(|(i, patch)| {
+            let own: HashSet<&str> = patch
+                .nodes
+                .iter()
+                .filter(|n| !n.stub)
+                .map(|n| n.change_id.as_str())
+                .collect();
+            let referenced = patch
+                .edges
+                .iter()
+                .flat_map(|e| [e.from_change_id.as_str(), e.to_change_id.as_str()])
+                .chain(
+                    patch
+                        .nodes
+                        .iter()
+                        .filter(|n| n.stub)
+                        .map(|n| n.change_id.as_str()),
+                )
+                .chain(patch.context.iter().map(|c| c.node_change_id.as_str()));
+            referenced
+                .filter(|cid| !own.contains(cid))
+                .filter_map(|cid| provider.get(cid).copied())
+                .filter(|&j| j != i)
+                .collect()
+        })
+        .collect();
+
+    let mut order = Vec::with_capacity(patches.len());
+    let mut placed = vec![false; patches.len()];
+    while order.len() < patches.len() {
+        let ready = oldest_first
+            .iter()
+            .copied()
+            .find(|&i| !placed[i] && deps[i].iter().all(|&j| placed[j]))
+            // Cycle: fall back to the oldest remaining patch
+            .or_else(|| oldest_first.iter().copied().find(|&i| !placed[i]));
+        let Some(next) = ready else { break };
+        placed[next] = true;
+        order.push(next);
+    }
+    order
+}
+
+/// Fold patches into one deduplicated patch, as if their changes had been made in a
+/// single export.
+///
+/// Patches are folded in dependency order. For each node the newest copy wins, but
+/// it carries the lineage of every copy, so someone who applied none or only some
+/// of the inputs still fast-forwards. Deleted nodes and edges are dropped and only their
+/// tombstones kept; duplicate edges and context, self-loops and stubs nothing
+/// refers to are removed.
+pub fn squash_patches(patches: &[GraphPatch]) -> GraphPatch {
+    let order = order_patches(patches);
+
+    let mut tombstones: HashMap<(String, String), PatchTombstone> = HashMap::new();
+    for tombstone in order.iter().flat_map(|&i| &patches[i].tombstones) {
+        let key = (tombstone.kind.clone(), tombstone.change_id.clone());
+        match tombstones.get(&key) {
+            Some(kept) if compare_timestamps(&kept.deleted_at, &tombstone.deleted_at).is_le() => {}
+            _ => {
+                tombstones.insert(key, tombstone.clone());
+            }
+        }
+    }
+    let is_deleted =
+        |kind: &str, key: &str| tombstones.contains_key(&(kind.to_string(), key.to_string()));
+
+    let mut nodes: HashMap<String, PatchNode> = HashMap::new();
+    for node in order.iter().flat_map(|&i| &patches[i].nodes) {
+        if is_deleted(TOMBSTONE_NODE, &node.change_id) {
+            continue;
+        }
+        match nodes.get_mut(&node.change_id) {
+            None => {
+                nodes.insert(node.change_id.clone(), node.clone());
+            }
+            // Stubs never replace anything; full copies always replace stubs
+            Some(_) if node.stub => {}
+            Some(kept) if kept.stub => *kept = node.clone(),
+            Some(kept) => {
+                if compare_timestamps(node.modified_at(), kept.modified_at()).is_ge() {
+                    let mut lineage =
+                        bounded_lineage(node.lineage().into_iter().chain(kept.lineage()))
+                            .into_iter();
+                    *kept = node.clone();
+                    kept.base_hash = lineage.next();
+                    kept.ancestor_hashes = lineage.collect();
+                }
+            }
+        }
+    }
+
+    let mut edges: HashMap<(String, String, String), PatchEdge> = HashMap::new();
+    for edge in order.iter().flat_map(|&i| &patches[i].edges) {
+        let key = edge_tombstone_key(&edge.from_change_id, &edge.to_change_id, &edge.edge_type);
+        if edge.from_change_id == edge.to_change_id
+            || is_deleted(TOMBSTONE_EDGE, &key)
+            || is_deleted(TOMBSTONE_NODE, &edge.from_change_id)
+            || is_deleted(TOMBSTONE_NODE, &edge.to_change_id)
+        {
+            continue;
+        }
+        let key = (
+            edge.from_change_id.clone(),
+            edge.to_change_id.clone(),
+            edge.edge_type.clone(),
+        );
+        match edges.get_mut(&key) {
+            // A later copy only fills in a missing rationale or replaces it
+            Some(kept) => {
+                if edge.rationale.is_some() {
+                    kept.rationale = edge.rationale.clone();
+                }
+            }
+            None => {
+                edges.insert(key, edge.clone());
+            }
+        }
+    }
+
+    let mut context: Vec<PatchContext> = Vec::new();
+    let mut seen_context: HashSet<&PatchContext> = HashSet::new();
+    for item in order.iter().flat_map(|&i| &patches[i].context) {
+        if !is_deleted(TOMBSTONE_NODE, &item.node_change_id) && seen_context.insert(item) {
+            context.push(item.clone());
+        }
+    }
+
+    let endpoints: HashSet<&str> = edges
+        .values()
+        .flat_map(|e| [e.from_change_id.as_str(), e.to_change_id.as_str()])
+        .chain(context.iter().map(|c| c.node_change_id.as_str()))
+        .collect();
+    nodes.retain(|cid, node| !node.stub || endpoints.contains(cid.as_str()));
+
+    // Header fields survive only where every input agrees
+    let common = |field: fn(&GraphPatch) -> &Option<String>| {
+        let mut values = patches.iter().map(field);
+        let first = values.next().cloned().flatten();
+        values.all(|v| *v == first).then_some(first).flatten()
+    };
+    let newest = order
+        .iter()
+        .copied()
+        .max_by(|&a, &b| compare_timestamps(&patches[a].created_at, &patches[b].created_at));
+    let mut squashed = GraphPatch::new(
+        common(|p| &p.author),
+        common(|p| &p.branch),
+        newest.and_then(|i| patches[i].base_commit.clone()),
+    );
+    if let Some(i) = newest {
+        squashed.created_at = patches[i].created_at.clone();
+    }
+    squashed.nodes = nodes.into_values().collect();
+    squashed.edges = edges.into_values().collect();
+    squashed.tombstones = tombstones.into_values().collect();
+    squashed.context = context;
+    squashed.canonicalize();
+    squashed.seal();
+    squashed
 }
 
 impl Database {
-    /// Export nodes and edges as a patch
+    /// The whole graph as a canonical patch for committing: stable ordering, no
+    /// branch, and a `created_at` taken from the newest change rather than the
+    /// clock, so an unchanged graph produces a byte-identical file.
+    pub fn snapshot_patch(&self, author: Option<String>) -> Result<GraphPatch, DbError> {
+        let mut patch = self.export_patch(None, None, &[], author, None, None)?;
+        patch.branch = None;
+        let newest = patch
+            .nodes
+            .iter()
+            .map(|n| n.modified_at())
+            .chain(patch.tombstones.iter().map(|t| t.deleted_at.as_str()))
+            .max_by(|a, b| compare_timestamps(a, b))
+            .map(str::to_string);
+        if let Some(newest) = newest {
+            patch.created_at = newest;
+        }
+        patch.canonicalize();
+        patch.seal();
+        Ok(patch)
+    }
+
+    /// Export nodes and edges as a patch.
+    ///
+    /// With `since` (an RFC 3339 timestamp), only nodes updated and edges and
+    /// deletions made after it are exported. Unchanged nodes those edges point at
+    /// are added as stubs so the patch stays self-contained.
     pub fn export_patch(
         &self,
         node_ids: Option<Vec<i32>>,
         branch_filter: Option<&str>,
+        tags: &[String],
         author: Option<String>,
         base_commit: Option<String>,
+        since: Option<&str>,
     ) -> Result<GraphPatch, crate::db::DbError> {
         let all_nodes = self.get_all_nodes()?;
         let all_edges = self.get_all_edges()?;
+        let is_recent =
+            |ts: &str| since.map_or(true, |s| compare_timestamps(ts, s) == Ordering::Greater);
 
         // Get current branch for patch metadata
         let current_branch = crate::db::get_current_git_branch();
         let mut patch = GraphPatch::new(author, current_branch, base_commit);
 
         // Filter nodes
-        let nodes: Vec<&DecisionNode> = all_nodes
+        let selected: HashMap<&str, &DecisionNode> = all_nodes
             .iter()
             .filter(|n| {
                 // Filter by node IDs if specified
@@ -166,42 +1083,113 @@ impl Database {
                     }
                 }
 
+                // Filter by tags if specified (node must carry all of them)
+                if !n.has_all_tags(tags) {
+                    return false;
+                }
+
                 // Filter by branch if specified
                 if let Some(branch) = branch_filter {
-                    if let Some(ref meta) = n.metadata_json {
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(meta) {
-                            if let Some(node_branch) = json.get("branch").and_then(|b| b.as_str()) {
-                                return node_branch == branch;
-                            }
-                        }
-                    }
-                    return false; // No branch metadata and branch filter specified
+                    // No branch metadata never matches a branch filter
+                    return n.branch().as_deref() == Some(branch);
                 }
 
                 true
             })
+            .map(|n| (n.change_id.as_str(), n))
+            .collect();
+        let nodes: Vec<&DecisionNode> = all_nodes
+            .iter()
+            .filter(|n| selected.contains_key(n.change_id.as_str()) && is_recent(&n.updated_at))
             .collect();
 
         // Collect change_ids of nodes being exported
         let change_ids: HashSet<&str> = nodes.iter().map(|n| n.change_id.as_str()).collect();
 
-        // Add nodes to patch
+        // Each node carries the versions it descends from. What we export becomes
+        // the version we last shared, and earlier ones stay in its lineage, so a
+        // teammate who never received an export (a scratch file, a snapshot) can
+        // still fast-forward past it.
+        let bases: HashMap<String, NodeSyncBase> = self
+            .get_sync_bases()?
+            .into_iter()
+            .map(|b| (b.change_id.clone(), b))
+            .collect();
         for node in &nodes {
-            patch.add_node(node);
+            let content = NodeContent::of_node(node);
+            let hash = content.hash();
+            let lineage = match bases.get(&node.change_id) {
+                Some(base) if base.content_hash == hash => base.lineage(),
+                base => {
+                    let lineage = base.map_or_else(Vec::new, |b| {
+                        bounded_lineage(std::iter::once(b.content_hash.clone()).chain(b.lineage()))
+                    });
+                    self.set_sync_base(&node.change_id, &hash, &lineage, &content.to_json())?;
+                    lineage
+                }
+            };
+            patch.add_node_with_base(node, lineage);
         }
 
-        // Add edges where BOTH endpoints are in the patch
+        // Add edges where BOTH endpoints pass the filters
         // Note: We use AND, not OR, because applying a patch requires both nodes to exist
+        let mut stubs: HashSet<&str> = HashSet::new();
         for edge in &all_edges {
             if let (Some(ref from_cid), Some(ref to_cid)) =
                 (&edge.from_change_id, &edge.to_change_id)
             {
-                if change_ids.contains(from_cid.as_str()) && change_ids.contains(to_cid.as_str()) {
-                    patch.add_edge(edge);
+                let (Some(from), Some(to)) = (
+                    selected.get(from_cid.as_str()),
+                    selected.get(to_cid.as_str()),
+                ) else {
+                    continue;
+                };
+                // Edges are never edited, so creation time decides
+                if !is_recent(&edge.created_at) {
+                    continue;
+                }
+                patch.add_edge(edge);
+                for node in [from, to] {
+                    if !change_ids.contains(node.change_id.as_str())
+                        && stubs.insert(node.change_id.as_str())
+                    {
+                        patch.add_stub(node);
+                    }
                 }
             }
         }
 
+        // Context captured since `since` travels with its node, stubbed if unchanged
+        let change_id_of: HashMap<i32, &str> = all_nodes
+            .iter()
+            .map(|n| (n.id, n.change_id.as_str()))
+            .collect();
+        for context in self.get_all_context()? {
+            let Some(node) = change_id_of
+                .get(&context.node_id)
+                .and_then(|cid| selected.get(cid))
+            else {
+                continue;
+            };
+            if !is_recent(&context.captured_at) {
+                continue;
+            }
+            patch.add_context(&node.change_id, &context);
+            if !change_ids.contains(node.change_id.as_str())
+                && stubs.insert(node.change_id.as_str())
+            {
+                patch.add_stub(node);
+            }
+        }
+
+        // Deletions always travel with the patch so teammates drop them too
+        for tombstone in self.get_tombstones()? {
+            if is_recent(&tombstone.deleted_at) {
+                patch.add_tombstone(&tombstone);
+            }
+        }
+
+        patch.seal();
         Ok(patch)
     }
 
@@ -213,89 +1201,91 @@ impl Database {
     ) -> Result<ApplyResult, crate::db::DbError> {
         let mut result = ApplyResult::default();
 
-        // Get existing change_ids
+        // Apply deletions first, then refuse anything that has been tombstoned
+        self.apply_tombstones(patch, dry_run, &mut result)?;
+        let mut deleted_keys: HashSet<(String, String)> = self
+            .get_tombstones()?
+            .into_iter()
+            .map(|t| (t.kind, t.change_id))
+            .collect();
+        deleted_keys.extend(
+            patch
+                .tombstones
+                .iter()
+                .map(|t| (t.kind.clone(), t.change_id.clone())),
+        );
+        let is_node_deleted =
+            |cid: &str| deleted_keys.contains(&(TOMBSTONE_NODE.to_string(), cid.to_string()));
+
+        // change_ids of merged-away nodes resolve to the node they were merged into
+        let aliases: HashMap<String, String> = self
+            .get_change_id_aliases()?
+            .into_iter()
+            .map(|a| (a.alias, a.change_id))
+            .collect();
+        let resolve = |cid: &str| aliases.get(cid).cloned().unwrap_or_else(|| cid.to_string());
+
+        // Existing nodes by change_id
         let existing_nodes = self.get_all_nodes()?;
-        let existing_change_ids: HashSet<String> =
-            existing_nodes.iter().map(|n| n.change_id.clone()).collect();
+        let existing_by_change_id: HashMap<&str, &DecisionNode> = existing_nodes
+            .iter()
+            .map(|n| (n.change_id.as_str(), n))
+            .collect();
 
         // Track newly added change_ids -> local ids
-        let mut change_id_to_local_id: std::collections::HashMap<String, i32> = existing_nodes
+        let mut change_id_to_local_id: HashMap<String, i32> = existing_nodes
             .iter()
             .map(|n| (n.change_id.clone(), n.id))
             .collect();
 
+        let bases: HashMap<String, NodeSyncBase> = self
+            .get_sync_bases()?
+            .into_iter()
+            .map(|b| (b.change_id.clone(), b))
+            .collect();
+
         // Apply nodes
         for patch_node in &patch.nodes {
-            if existing_change_ids.contains(&patch_node.change_id) {
+            if aliases.contains_key(&patch_node.change_id) || is_node_deleted(&patch_node.change_id)
+            {
                 result.nodes_skipped += 1;
                 continue;
             }
 
-            if !dry_run {
-                // Get branch from metadata
-                let branch = patch_node
-                    .metadata_json
-                    .as_ref()
-                    .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
-                    .and_then(|j| {
-                        j.get("branch")
-                            .and_then(|b| b.as_str())
-                            .map(|s| s.to_string())
-                    });
-
-                let confidence = patch_node
-                    .metadata_json
-                    .as_ref()
-                    .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
-                    .and_then(|j| {
-                        j.get("confidence")
-                            .and_then(|c| c.as_u64())
-                            .map(|c| c as u8)
-                    });
-
-                let prompt = patch_node
-                    .metadata_json
-                    .as_ref()
-                    .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
-                    .and_then(|j| {
-                        j.get("prompt")
-                            .and_then(|p| p.as_str())
-                            .map(|s| s.to_string())
-                    });
+            if let Some(local) = existing_by_change_id.get(patch_node.change_id.as_str()) {
+                // Stubs only make sure edge endpoints exist
+                if patch_node.stub {
+                    result.nodes_skipped += 1;
+                    continue;
+                }
+                let base = bases.get(&patch_node.change_id);
+                self.apply_to_existing_node(patch_node, local, base, dry_run, &mut result)?;
+                continue;
+            }
 
-                let files = patch_node
-                    .metadata_json
-                    .as_ref()
-                    .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
-                    .and_then(|j| {
-                        j.get("files").and_then(|f| {
-                            if let Some(arr) = f.as_array() {
-                                Some(
-                                    arr.iter()
-                                        .filter_map(|v| v.as_str())
-                                        .collect::<Vec<_>>()
-                                        .join(","),
-                                )
-                            } else {
-                                None
-                            }
-                        })
-                    });
+            if let Err(e) = self
+                .validate_node_type(&patch_node.node_type)
+                .and_then(|()| self.validate_status(&patch_node.status))
+            {
+                result
+                    .nodes_failed
+                    .push(format!("Node {}: {}", patch_node.change_id, e));
+                continue;
+            }
 
-                // Create node with explicit change_id
-                let local_id = self.create_node_with_change_id(
+            if !dry_run {
+                let mut node = patch_node.to_node(0);
+                node.author = node.author.or_else(|| patch.author.clone());
+                let local_id = self.import_node(&node)?;
+                result.nodes_introduced.push(patch_node.change_id.clone());
+                change_id_to_local_id.insert(patch_node.change_id.clone(), local_id);
+                let content = NodeContent::of_patch_node(patch_node);
+                self.set_sync_base(
                     &patch_node.change_id,
-                    &patch_node.node_type,
-                    &patch_node.title,
-                    patch_node.description.as_deref(),
-                    confidence,
-                    None, // commit
-                    prompt.as_deref(),
-                    files.as_deref(),
-                    branch.as_deref(),
+                    &content.hash(),
+                    &bounded_lineage(patch_node.lineage()),
+                    &content.to_json(),
                 )?;
-
-                change_id_to_local_id.insert(patch_node.change_id.clone(), local_id);
             }
 
             result.nodes_added += 1;
@@ -303,7 +1293,7 @@ impl Database {
 
         // Get existing edges (by change_id pairs)
         let existing_edges = self.get_all_edges()?;
-        let existing_edge_keys: HashSet<(String, String, String)> = existing_edges
+        let mut existing_edge_keys: HashSet<(String, String, String)> = existing_edges
             .iter()
             .filter_map(|e| match (&e.from_change_id, &e.to_change_id) {
                 (Some(from), Some(to)) => Some((from.clone(), to.clone(), e.edge_type.clone())),
@@ -313,31 +1303,56 @@ impl Database {
 
         // Apply edges
         for patch_edge in &patch.edges {
+            let from_cid = resolve(&patch_edge.from_change_id);
+            let to_cid = resolve(&patch_edge.to_change_id);
             let edge_key = (
-                patch_edge.from_change_id.clone(),
-                patch_edge.to_change_id.clone(),
+                from_cid.clone(),
+                to_cid.clone(),
                 patch_edge.edge_type.clone(),
             );
 
-            if existing_edge_keys.contains(&edge_key) {
+            let tombstone_key = edge_tombstone_key(
+                &patch_edge.from_change_id,
+                &patch_edge.to_change_id,
+                &patch_edge.edge_type,
+            );
+            if existing_edge_keys.contains(&edge_key)
+                || from_cid == to_cid
+                || deleted_keys.contains(&(TOMBSTONE_EDGE.to_string(), tombstone_key))
+                || is_node_deleted(&patch_edge.from_change_id)
+                || is_node_deleted(&patch_edge.to_change_id)
+            {
                 result.edges_skipped += 1;
                 continue;
             }
 
+            if let Err(e) = self.validate_edge_type(&patch_edge.edge_type) {
+                result.edges_failed.push(format!(
+                    "Edge {} -> {}: {}",
+                    patch_edge.from_change_id, patch_edge.to_change_id, e
+                ));
+                continue;
+            }
+
             // Look up local IDs
-            let from_id = change_id_to_local_id.get(&patch_edge.from_change_id);
-            let to_id = change_id_to_local_id.get(&patch_edge.to_change_id);
+            let from_id = change_id_to_local_id.get(&from_cid);
+            let to_id = change_id_to_local_id.get(&to_cid);
 
             match (from_id, to_id) {
                 (Some(&from), Some(&to)) => {
                     if !dry_run {
-                        self.create_edge(
+                        self.create_edge_by(
                             from,
                             to,
                             &patch_edge.edge_type,
                             patch_edge.rationale.as_deref(),
+                            patch_edge.author.as_deref().or(patch.author.as_deref()),
                         )?;
                     }
+                    if !dry_run {
+                        result.edges_introduced.push(edge_key.clone());
+                    }
+                    existing_edge_keys.insert(edge_key);
                     result.edges_added += 1;
                 }
                 _ => {
@@ -350,25 +1365,381 @@ impl Database {
             }
         }
 
+        self.apply_context(
+            patch,
+            &change_id_to_local_id,
+            &resolve,
+            &is_node_deleted,
+            dry_run,
+            &mut result,
+        )?;
+
         Ok(result)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Attach the patch's context items to their nodes, skipping ones already here
+    fn apply_context(
+        &self,
+        patch: &GraphPatch,
+        local_ids: &HashMap<String, i32>,
+        resolve: &dyn Fn(&str) -> String,
+        is_node_deleted: &dyn Fn(&str) -> bool,
+        dry_run: bool,
+        result: &mut ApplyResult,
+    ) -> Result<(), DbError> {
+        if patch.context.is_empty() {
+            return Ok(());
+        }
+        let mut existing: HashSet<(i32, String, String, String)> = self
+            .get_all_context()?
+            .into_iter()
+            .map(|c| (c.node_id, c.context_type, c.content_json, c.captured_at))
+            .collect();
 
-    fn sample_node(id: i32, change_id: &str, node_type: &str, title: &str) -> DecisionNode {
-        DecisionNode {
-            id,
-            change_id: change_id.to_string(),
-            node_type: node_type.to_string(),
-            title: title.to_string(),
+        for item in &patch.context {
+            if is_node_deleted(&item.node_change_id) {
+                result.context_skipped += 1;
+                continue;
+            }
+            let Some(&node_id) = local_ids.get(&resolve(&item.node_change_id)) else {
+                result
+                    .context_failed
+                    .push(format!("Context for {}: missing node", item.node_change_id));
+                continue;
+            };
+            let key = (
+                node_id,
+                item.context_type.clone(),
+                item.content_json.clone(),
+                item.captured_at.clone(),
+            );
+            if existing.contains(&key) {
+                result.context_skipped += 1;
+                continue;
+            }
+            if let Err(e) = item.context_type.parse::<ContextType>() {
+                result
+                    .context_failed
+                    .push(format!("Context for {}: {}", item.node_change_id, e));
+                continue;
+            }
+            if !dry_run {
+                self.import_context(
+                    node_id,
+                    &item.context_type,
+                    &item.content_json,
+                    &item.captured_at,
+                )?;
+            }
+            existing.insert(key);
+            result.context_added += 1;
+        }
+        Ok(())
+    }
+
+    /// Remember that a patch file was applied and which nodes and edges it created,
+    /// so `diff status` and `diff revert` can find them later
+    pub fn record_applied_patch_result(
+        &self,
+        patch: &GraphPatch,
+        path: &str,
+        result: &ApplyResult,
+    ) -> Result<(), DbError> {
+        let digest = patch.effective_digest();
+        let now = chrono::Local::now().to_rfc3339();
+        let mut items: Vec<NewAppliedPatchItem> = result
+            .nodes_introduced
+            .iter()
+            .map(|cid| NewAppliedPatchItem {
+                patch_digest: &digest,
+                kind: TOMBSTONE_NODE,
+                change_id: cid.clone(),
+                from_change_id: None,
+                to_change_id: None,
+                edge_type: None,
+            })
+            .collect();
+        items.extend(result.edges_introduced.iter().map(|(from, to, edge_type)| {
+            NewAppliedPatchItem {
+                patch_digest: &digest,
+                kind: TOMBSTONE_EDGE,
+                change_id: edge_tombstone_key(from, to, edge_type),
+                from_change_id: Some(from),
+                to_change_id: Some(to),
+                edge_type: Some(edge_type),
+            }
+        }));
+        self.record_applied_patch(
+            &NewAppliedPatch {
+                digest: &digest,
+                path,
+                author: patch.author.as_deref(),
+                created_at: &patch.created_at,
+                applied_at: &now,
+                nodes_added: result.nodes_added as i32,
+                nodes_updated: (result.nodes_updated + result.nodes_merged) as i32,
+                edges_added: result.edges_added as i32,
+                nodes_failed: result.nodes_failed.len() as i32,
+                edges_failed: result.edges_failed.len() as i32,
+            },
+            &items,
+        )
+    }
+
+    /// Reconcile a patch node with the local node that has the same change_id.
+    ///
+    /// Identical content is skipped. If the patch copy descends from our current
+    /// version it fast-forwards; if it is a version we already synced, ours is ahead.
+    /// Otherwise both sides changed it: the edits are merged field by field against
+    /// the common ancestor, and anything that can't be merged is recorded as a
+    /// conflict. Patches without base hashes fall back to the newer `updated_at`.
+    fn apply_to_existing_node(
+        &self,
+        patch_node: &PatchNode,
+        local: &DecisionNode,
+        base: Option<&NodeSyncBase>,
+        dry_run: bool,
+        result: &mut ApplyResult,
+    ) -> Result<(), DbError> {
+        let theirs = NodeContent::of_patch_node(patch_node);
+        let ours = NodeContent::of_node(local);
+        let their_hash = theirs.hash();
+        let lineage = bounded_lineage(patch_node.lineage());
+        let label = format!("Node {} ({})", patch_node.change_id, local.title);
+
+        let record_base = || {
+            if dry_run {
+                return Ok(());
+            }
+            self.set_sync_base(
+                &patch_node.change_id,
+                &their_hash,
+                &lineage,
+                &theirs.to_json(),
+            )
+        };
+
+        if theirs == ours {
+            if base.map_or(true, |b| b.content_hash != their_hash) {
+                record_base()?;
+            }
+            result.nodes_skipped += 1;
+            return Ok(());
+        }
+
+        let take_theirs = |result: &mut ApplyResult| -> Result<(), DbError> {
+            if let Err(e) = self.validate_content(&theirs) {
+                result
+                    .nodes_failed
+                    .push(format!("Node {}: {}", patch_node.change_id, e));
+                return Ok(());
+            }
+            if !dry_run {
+                let mut node = patch_node.to_node(local.id);
+                node.created_at = local.created_at.clone();
+                self.overwrite_node(&node)?;
+            }
+            record_base()?;
+            result.nodes_updated += 1;
+            Ok(())
+        };
+
+        // Older patches carry no base hash, so fall back to the newer timestamp
+        let Some(base_hash) = patch_node.base_hash.as_deref() else {
+            if patch_node.is_newer_than(local) {
+                return take_theirs(result);
+            }
+            let reason = match &patch_node.updated_at {
+                None => "patch copy has no timestamp",
+                Some(t) if compare_timestamps(t, &local.updated_at) == Ordering::Equal => {
+                    "same timestamp, different content"
+                }
+                Some(_) => "local copy is newer",
+            };
+            result.nodes_diverged.push(format!("{}: {}", label, reason));
+            return Ok(());
+        };
+
+        if lineage.contains(&ours.hash()) {
+            return take_theirs(result);
+        }
+        if base.is_some_and(|b| b.content_hash == their_hash || b.lineage().contains(&their_hash)) {
+            result
+                .nodes_diverged
+                .push(format!("{}: local copy is newer", label));
+            return Ok(());
+        }
+
+        // Both sides edited the node: merge against the common ancestor if we have it
+        let ancestor = base
+            .filter(|b| lineage.contains(&b.content_hash))
+            .and_then(|b| NodeContent::from_json(&b.content));
+        let merge = ancestor.as_ref().map(|a| merge_content(a, &ours, &theirs));
+
+        match merge {
+            Some(merge) if merge.conflicts.is_empty() => {
+                if let Err(e) = self.validate_content(&merge.merged) {
+                    result
+                        .nodes_failed
+                        .push(format!("Node {}: {}", patch_node.change_id, e));
+                    return Ok(());
+                }
+                if !dry_run {
+                    let now = chrono::Local::now().to_rfc3339();
+                    self.overwrite_node(&merge.merged.apply_to(local, &now))?;
+                }
+                record_base()?;
+                result.nodes_merged += 1;
+            }
+            merge => {
+                let detail = match merge {
+                    Some(m) => format!("both sides changed {}", m.conflicts.join(", ")),
+                    None => "both sides changed it and the common version is unknown".to_string(),
+                };
+                if !dry_run {
+                    self.create_node_conflict(
+                        &patch_node.change_id,
+                        Some(base_hash),
+                        ancestor.map(|a| a.to_json()).as_deref(),
+                        &ours.to_json(),
+                        &theirs.to_json(),
+                    )?;
+                }
+                result.conflicts.push(format!("{}: {}", label, detail));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that content uses a known node type and status
+    fn validate_content(&self, content: &NodeContent) -> Result<(), DbError> {
+        self.validate_node_type(&content.node_type)?;
+        self.validate_status(&content.status)
+    }
+
+    /// Settle a recorded node conflict. The patch's version becomes the sync base, so
+    /// the outcome exports as a fast-forward for whoever sent it.
+    pub fn resolve_node_conflict(
+        &self,
+        conflict: &NodeConflict,
+        resolution: &ConflictResolution,
+    ) -> Result<(), DbError> {
+        let theirs = NodeContent::from_json(&conflict.remote_value).ok_or_else(|| {
+            DbError::Validation(format!(
+                "Conflict {} has unreadable patch content",
+                conflict.id
+            ))
+        })?;
+
+        let replacement = match resolution {
+            ConflictResolution::Ours => None,
+            ConflictResolution::Theirs => Some(&theirs),
+            ConflictResolution::Edit(content) => Some(content),
+        };
+        if let Some(content) = replacement {
+            self.validate_content(content)?;
+            let local = self
+                .get_all_nodes()?
+                .into_iter()
+                .find(|n| n.change_id == conflict.node_change_id)
+                .ok_or_else(|| {
+                    DbError::Validation(format!(
+                        "Node {} no longer exists; resolve with 'ours' to dismiss the conflict",
+                        conflict.node_change_id
+                    ))
+                })?;
+            let now = chrono::Local::now().to_rfc3339();
+            self.overwrite_node(&content.apply_to(&local, &now))?;
+        }
+
+        self.set_sync_base(
+            &conflict.node_change_id,
+            &theirs.hash(),
+            &conflict.base_hash.iter().cloned().collect::<Vec<_>>(),
+            &theirs.to_json(),
+        )?;
+        self.mark_node_conflict_resolved(conflict.id, resolution.as_str())
+    }
+
+    /// Record a patch's tombstones locally and delete the matching nodes and edges
+    fn apply_tombstones(
+        &self,
+        patch: &GraphPatch,
+        dry_run: bool,
+        result: &mut ApplyResult,
+    ) -> Result<(), crate::db::DbError> {
+        if patch.tombstones.is_empty() {
+            return Ok(());
+        }
+
+        let nodes = self.get_all_nodes()?;
+        let edges = self.get_all_edges()?;
+
+        for tombstone in &patch.tombstones {
+            match tombstone.kind.as_str() {
+                TOMBSTONE_NODE => {
+                    if let Some(node) = nodes.iter().find(|n| n.change_id == tombstone.change_id) {
+                        if !dry_run {
+                            let deleted = self.delete_node(node.id, false)?;
+                            result.edges_deleted += deleted.edges_deleted;
+                        }
+                        result.nodes_deleted += 1;
+                    }
+                }
+                TOMBSTONE_EDGE => {
+                    let matching = edges.iter().filter(|e| {
+                        e.from_change_id.as_deref() == tombstone.from_change_id.as_deref()
+                            && e.to_change_id.as_deref() == tombstone.to_change_id.as_deref()
+                            && Some(e.edge_type.as_str()) == tombstone.edge_type.as_deref()
+                    });
+                    for edge in matching {
+                        if dry_run {
+                            result.edges_deleted += 1;
+                        } else {
+                            // May already be gone if a node tombstone removed it
+                            result.edges_deleted += self.delete_edges(
+                                edge.from_node_id,
+                                edge.to_node_id,
+                                Some(&edge.edge_type),
+                            )?;
+                        }
+                    }
+                }
+                _ => continue,
+            }
+
+            if !dry_run {
+                self.record_tombstone(
+                    &tombstone.kind,
+                    &tombstone.change_id,
+                    tombstone.from_change_id.as_deref(),
+                    tombstone.to_change_id.as_deref(),
+                    tombstone.edge_type.as_deref(),
+                    &tombstone.deleted_at,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_node(id: i32, change_id: &str, node_type: &str, title: &str) -> DecisionNode {
+        DecisionNode {
+            id,
+            change_id: change_id.to_string(),
+            node_type: node_type.to_string(),
+            title: title.to_string(),
             description: None,
             status: "pending".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
             metadata_json: Some(r#"{"branch": "main", "confidence": 90}"#.to_string()),
+            author: None,
         }
     }
 
@@ -383,6 +1754,7 @@ mod tests {
             weight: Some(1.0),
             rationale: Some("test rationale".to_string()),
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            author: None,
         }
     }
 
@@ -396,7 +1768,7 @@ mod tests {
             Some("abc123".to_string()),
         );
 
-        assert_eq!(patch.version, "1.0");
+        assert_eq!(patch.version, PATCH_VERSION);
         assert_eq!(patch.author, Some("alice".to_string()));
         assert_eq!(patch.branch, Some("feature-x".to_string()));
         assert_eq!(patch.base_commit, Some("abc123".to_string()));
@@ -474,12 +1846,47 @@ mod tests {
         let json = serde_json::to_string_pretty(&patch).expect("serialize");
 
         // Verify it contains expected fields
-        assert!(json.contains("\"version\": \"1.0\""));
+        assert!(json.contains("\"version\": \"1.3\""));
         assert!(json.contains("\"author\": \"alice\""));
         assert!(json.contains("\"nodes\": []"));
         assert!(json.contains("\"edges\": []"));
     }
 
+    #[test]
+    fn test_patch_version_upgrade() {
+        let mut old = GraphPatch::new(Some("alice".to_string()), None, None);
+        old.add_node(&sample_node(1, "cid-1", "goal", "Goal 1"));
+        old.version = "1.0".to_string();
+        old.seal();
+        let json = serde_json::to_string(&old).unwrap();
+
+        // Upgraded in memory, but the digest still covers the patch as written
+        let loaded = GraphPatch::from_json(&json).unwrap();
+        assert_eq!(loaded.version, PATCH_VERSION);
+        assert_eq!(loaded.upgraded_from(), Some("1.0"));
+        assert_eq!(
+            loaded.verify(&KeyStore::new("/nonexistent"), false),
+            Ok(PatchIntegrity::Sealed)
+        );
+
+        // Resealing makes it a current-version patch
+        let mut resealed = loaded.clone();
+        resealed.seal();
+        let reloaded = GraphPatch::from_json(&serde_json::to_string(&resealed).unwrap()).unwrap();
+        assert_eq!(reloaded.upgraded_from(), None);
+        assert_eq!(reloaded.digest, resealed.digest);
+        assert_ne!(reloaded.digest, old.digest);
+
+        let newer = json.replace("\"1.0\"", "\"9.0\"");
+        assert!(GraphPatch::from_json(&newer)
+            .unwrap_err()
+            .contains("patch format 9.0 is newer"));
+        let garbled = json.replace("\"1.0\"", "\"one\"");
+        assert!(GraphPatch::from_json(&garbled)
+            .unwrap_err()
+            .contains("unrecognized patch format version"));
+    }
+
     // === PatchNode Tests ===
 
     #[test]
@@ -494,6 +1901,7 @@ mod tests {
             created_at: "2024-06-01T12:00:00Z".to_string(),
             updated_at: "2024-06-01T12:00:00Z".to_string(),
             metadata_json: Some(r#"{"confidence": 85}"#.to_string()),
+            author: None,
         };
 
         let mut patch = GraphPatch::new(None, None, None);
@@ -518,6 +1926,7 @@ mod tests {
         assert_eq!(result.nodes_skipped, 0);
         assert_eq!(result.edges_added, 0);
         assert_eq!(result.edges_skipped, 0);
+        assert!(result.nodes_failed.is_empty());
         assert!(result.edges_failed.is_empty());
     }
 
@@ -548,6 +1957,7 @@ mod tests {
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
             metadata_json: None,
+            author: None,
         };
 
         patch.add_node(&node);
@@ -565,4 +1975,1068 @@ mod tests {
             Some("Line1\nLine2\tTabbed".to_string())
         );
     }
+
+    // === Tombstone Tests ===
+
+    #[test]
+    fn test_patch_without_tombstones_omits_field() {
+        let patch = GraphPatch::new(None, None, None);
+        let json = serde_json::to_string(&patch).unwrap();
+        assert!(!json.contains("tombstones"));
+
+        // Older patches without the field still parse
+        let restored: GraphPatch = serde_json::from_str(&json).unwrap();
+        assert!(restored.tombstones.is_empty());
+    }
+
+    #[test]
+    fn test_apply_tombstone_deletes_and_blocks_resurrection() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+        let target = Database::new(dir.path().join("b.db").to_str().unwrap()).unwrap();
+
+        let goal = source
+            .create_node("goal", "Goal", None, None, None)
+            .unwrap();
+        let action = source
+            .create_node("action", "Action", None, None, None)
+            .unwrap();
+        source.create_edge(goal, action, "leads_to", None).unwrap();
+
+        // Teammate receives the full graph first
+        let full = source
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        target.apply_patch(&full, false).unwrap();
+        assert_eq!(target.get_all_nodes().unwrap().len(), 2);
+
+        // Then the node is deleted upstream
+        source.delete_node(action, false).unwrap();
+        let after_delete = source
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        assert_eq!(after_delete.tombstones.len(), 2);
+
+        let result = target.apply_patch(&after_delete, false).unwrap();
+        assert_eq!(result.nodes_deleted, 1);
+        assert_eq!(target.get_all_nodes().unwrap().len(), 1);
+        assert!(target.get_all_edges().unwrap().is_empty());
+
+        // Re-applying the stale patch must not bring the node back
+        let result = target.apply_patch(&full, false).unwrap();
+        assert_eq!(result.nodes_added, 0);
+        assert_eq!(result.edges_added, 0);
+        assert_eq!(target.get_all_nodes().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_apply_reports_unknown_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+
+        let mut patch = GraphPatch::new(None, None, None);
+        patch.add_node(&sample_node(1, "cid-good", "goal", "Good"));
+        patch.add_node(&sample_node(2, "cid-bad", "gaol", "Bad"));
+        patch.add_node(&sample_node(3, "cid-other", "action", "Other"));
+        let mut edge = sample_edge(1, 1, 3, "cid-good", "cid-other");
+        edge.edge_type = "choosen".to_string();
+        patch.add_edge(&edge);
+
+        let result = db.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_added, 2);
+        assert_eq!(result.nodes_failed.len(), 1);
+        assert!(result.nodes_failed[0].contains("cid-bad"));
+        assert_eq!(result.edges_added, 0);
+        assert_eq!(result.edges_failed.len(), 1);
+    }
+
+    #[test]
+    fn test_apply_preserves_full_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+
+        let mut patch = GraphPatch::new(None, None, None);
+        let mut node = sample_node(1, "cid-meta", "action", "Action");
+        node.metadata_json = Some(
+            r#"{"commit": "abc1234", "prompt": "p", "tags": ["perf"], "attributes": {"owner": "sam"}, "custom": {"k": 1}}"#
+                .to_string(),
+        );
+        patch.add_node(&node);
+
+        db.apply_patch(&patch, false).unwrap();
+
+        let applied = db.get_all_nodes().unwrap().remove(0);
+        let meta = applied.metadata();
+        assert_eq!(meta.commit.as_deref(), Some("abc1234"));
+        assert_eq!(meta.prompt.as_deref(), Some("p"));
+        assert_eq!(meta.tags, vec!["perf"]);
+        assert_eq!(meta.attributes["owner"], "sam");
+        assert_eq!(meta.extra["custom"]["k"], 1);
+    }
+
+    #[test]
+    fn test_apply_keeps_authors_and_credits_patch_author() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = Database::new(dir.path().join("a.db").to_str().unwrap())
+            .unwrap()
+            .with_author(Some("Alice".to_string()));
+        let target = Database::new(dir.path().join("b.db").to_str().unwrap())
+            .unwrap()
+            .with_author(Some("Bob".to_string()));
+
+        let goal = source
+            .create_node("goal", "Goal", None, None, None)
+            .unwrap();
+        let action = source
+            .create_node("action", "Act", None, None, None)
+            .unwrap();
+        source.create_edge(goal, action, "leads_to", None).unwrap();
+        let mut patch = source
+            .export_patch(None, None, &[], Some("Carol".to_string()), None, None)
+            .unwrap();
+        assert_eq!(patch.nodes[0].author.as_deref(), Some("Alice"));
+        assert_eq!(patch.edges[0].author.as_deref(), Some("Alice"));
+
+        // Entries from older patches have no author of their own
+        patch.nodes[1].author = None;
+        target.apply_patch(&patch, false).unwrap();
+
+        let nodes = target.get_all_nodes().unwrap();
+        assert_eq!(nodes[0].author.as_deref(), Some("Alice"));
+        assert_eq!(nodes[1].author.as_deref(), Some("Carol"));
+        assert_eq!(
+            target.get_all_edges().unwrap()[0].author.as_deref(),
+            Some("Alice")
+        );
+    }
+
+    #[test]
+    fn test_context_travels_with_patches() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+        let target = Database::new(dir.path().join("b.db").to_str().unwrap()).unwrap();
+
+        let obs = source
+            .create_node("observation", "Slow", None, None, None)
+            .unwrap();
+        source
+            .add_context(obs, ContextType::Benchmark, "p99 = 120ms", None)
+            .unwrap();
+        let full = source
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        assert_eq!(full.context.len(), 1);
+        assert_eq!(full.context[0].context_type, "benchmark");
+
+        let result = target.apply_patch(&full, false).unwrap();
+        assert_eq!(result.context_added, 1);
+        let target_obs = target.get_all_nodes().unwrap()[0].id;
+        assert_eq!(
+            target.get_node_context(target_obs).unwrap()[0].content(),
+            "p99 = 120ms"
+        );
+
+        // Applying again doesn't duplicate it
+        let result = target.apply_patch(&full, false).unwrap();
+        assert_eq!((result.context_added, result.context_skipped), (0, 1));
+
+        // Context added to an unchanged node exports with a stub of the node
+        let since = chrono::Local::now().to_rfc3339();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        source
+            .add_context(obs, ContextType::Url, "https://example.com/run/2", None)
+            .unwrap();
+        let patch = source
+            .export_patch(None, None, &[], None, None, Some(&since))
+            .unwrap();
+        assert_eq!(patch.context.len(), 1);
+        assert_eq!(patch.stub_count(), 1);
+        let result = target.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.context_added, 1);
+        assert_eq!(target.get_node_context(target_obs).unwrap().len(), 2);
+
+        // Context for a node the database never saw fails instead of vanishing
+        let mut orphan = GraphPatch::new(None, None, None);
+        orphan.context = patch.context.clone();
+        orphan.context[0].node_change_id = "unknown".to_string();
+        orphan.context[0].context_type = "url".to_string();
+        let result = target.apply_patch(&orphan, false).unwrap();
+        assert_eq!(result.context_failed.len(), 1);
+
+        // Squashing keeps one copy of each item
+        let squashed = squash_patches(&[full.clone(), full, patch]);
+        assert_eq!(squashed.context.len(), 2);
+        assert_eq!(squashed.nodes.len(), 1);
+        assert!(!squashed.nodes[0].stub);
+    }
+
+    #[test]
+    fn test_export_patch_filters_by_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+
+        let tagged = db.create_node("goal", "Tagged", None, None, None).unwrap();
+        db.create_node("goal", "Untagged", None, None, None)
+            .unwrap();
+        db.edit_labels(tagged, &["+perf".parse().unwrap()]).unwrap();
+
+        let patch = db
+            .export_patch(None, None, &["perf".to_string()], None, None, None)
+            .unwrap();
+        assert_eq!(patch.nodes.len(), 1);
+        assert_eq!(patch.nodes[0].title, "Tagged");
+    }
+
+    // === Integrity Tests ===
+
+    #[test]
+    fn test_patch_digest_detects_tampering() {
+        let mut patch = GraphPatch::new(None, None, None);
+        patch.add_node(&sample_node(1, "a", "goal", "Original"));
+        let keys = KeyStore::new(tempfile::tempdir().unwrap().path().join("keys"));
+
+        // 1.0 patches predate digests, so they are accepted unless signing is required
+        let mut legacy = patch.clone();
+        legacy.version = "1.0".to_string();
+        let legacy = GraphPatch::from_json(&serde_json::to_string(&legacy).unwrap()).unwrap();
+        assert_eq!(legacy.verify(&keys, false), Ok(PatchIntegrity::Unsealed));
+        assert!(legacy.verify(&keys, true).is_err());
+        assert!(patch
+            .verify(&keys, false)
+            .unwrap_err()
+            .contains("no digest"));
+
+        patch.seal();
+        let json = serde_json::to_string(&patch).unwrap();
+        let loaded: GraphPatch = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.verify(&keys, false), Ok(PatchIntegrity::Sealed));
+
+        let mut tampered = loaded.clone();
+        tampered.nodes[0].title = "Changed".to_string();
+        assert!(tampered
+            .verify(&keys, false)
+            .unwrap_err()
+            .contains("digest mismatch"));
+    }
+
+    #[test]
+    fn test_patch_signature_and_trust() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = KeyStore::new(dir.path().join("keys"));
+        let mut patch = GraphPatch::new(None, None, None);
+        patch.add_node(&sample_node(1, "a", "goal", "Signed goal"));
+
+        // Signed by a key we don't hold a .pub for
+        let stranger = SigningKey::from_bytes(&[3u8; 32]);
+        patch.sign(&stranger);
+        assert!(matches!(
+            patch.verify(&keys, false),
+            Ok(PatchIntegrity::Signed { trusted: false, .. })
+        ));
+        assert!(patch
+            .verify(&keys, true)
+            .unwrap_err()
+            .contains("untrusted key"));
+
+        keys.generate(false).unwrap();
+        patch.sign(&keys.signing_key().unwrap());
+        assert!(matches!(
+            patch.verify(&keys, true),
+            Ok(PatchIntegrity::Signed { trusted: true, .. })
+        ));
+
+        // Re-sealing after an edit (without the key) drops the signature
+        let mut resealed = patch.clone();
+        resealed.nodes[0].title = "Edited".to_string();
+        resealed.seal();
+        assert_eq!(resealed.verify(&keys, false), Ok(PatchIntegrity::Sealed));
+        assert!(resealed.verify(&keys, true).is_err());
+
+        // Keeping the old signature on a new digest fails outright
+        resealed.signature = patch.signature.clone();
+        assert!(resealed
+            .verify(&keys, false)
+            .unwrap_err()
+            .contains("does not match"));
+    }
+
+    // === Update Tests ===
+
+    #[test]
+    fn test_compare_timestamps_across_offsets() {
+        assert_eq!(
+            compare_timestamps("2024-01-01T10:00:00+02:00", "2024-01-01T09:00:00Z"),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_timestamps("2024-01-01T10:00:00+02:00", "2024-01-01T08:00:00Z"),
+            Ordering::Equal
+        );
+        assert_eq!(compare_timestamps("b", "a"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_apply_updates_newer_nodes_and_reports_divergence() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+        let target = Database::new(dir.path().join("b.db").to_str().unwrap()).unwrap();
+
+        let goal = source
+            .create_node("goal", "Goal", None, None, None)
+            .unwrap();
+        let full = source
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        target.apply_patch(&full, false).unwrap();
+
+        // Re-applying an unchanged patch is a no-op
+        let result = target.apply_patch(&full, false).unwrap();
+        assert_eq!(result.nodes_skipped, 1);
+        assert_eq!(result.nodes_updated, 0);
+
+        // A teammate completes the goal and attaches a commit
+        source.update_node_status(goal, "completed").unwrap();
+        source.update_node_commit(goal, "abc1234").unwrap();
+        let newer = source
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+
+        let dry = target.apply_patch(&newer, true).unwrap();
+        assert_eq!(dry.nodes_updated, 1);
+        assert_eq!(target.get_all_nodes().unwrap()[0].status, "pending");
+
+        let result = target.apply_patch(&newer, false).unwrap();
+        assert_eq!(result.nodes_updated, 1);
+        assert!(result.nodes_diverged.is_empty());
+        let local = target.get_all_nodes().unwrap().remove(0);
+        assert_eq!(local.status, "completed");
+        assert_eq!(local.metadata().commit.as_deref(), Some("abc1234"));
+        assert_eq!(local.updated_at, newer.nodes[0].updated_at.clone().unwrap());
+
+        // Local edits made after that win over the now-stale patch
+        target
+            .update_node(
+                local.id,
+                Some("Goal (renamed)"),
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        let result = target.apply_patch(&newer, false).unwrap();
+        assert_eq!(result.nodes_updated, 0);
+        assert_eq!(result.nodes_diverged.len(), 1);
+        assert!(result.nodes_diverged[0].contains("local copy is newer"));
+        assert_eq!(target.get_all_nodes().unwrap()[0].title, "Goal (renamed)");
+
+        // Patches from before nodes carried updated_at never overwrite
+        let mut legacy = newer.clone();
+        legacy.nodes[0].updated_at = None;
+        legacy.nodes[0].base_hash = None;
+        let result = target.apply_patch(&legacy, false).unwrap();
+        assert!(result.nodes_diverged[0].contains("no timestamp"));
+    }
+
+    // === Three-Way Merge Tests ===
+
+    fn content(title: &str, status: &str, metadata: serde_json::Value) -> NodeContent {
+        NodeContent {
+            node_type: "goal".to_string(),
+            title: title.to_string(),
+            description: None,
+            status: status.to_string(),
+            metadata: metadata.as_object().cloned().unwrap_or_default(),
+        }
+    }
+
+    #[test]
+    fn test_merge_content() {
+        let base = content("Goal", "pending", serde_json::json!({"confidence": 50}));
+        let ours = content(
+            "Goal (renamed)",
+            "pending",
+            serde_json::json!({"confidence": 50, "commit": "abc"}),
+        );
+        let theirs = content("Goal", "completed", serde_json::json!({"confidence": 90}));
+
+        let merge = merge_content(&base, &ours, &theirs);
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(merge.changed, vec!["status", "metadata.confidence"]);
+        assert_eq!(merge.merged.title, "Goal (renamed)");
+        assert_eq!(merge.merged.status, "completed");
+        assert_eq!(merge.merged.metadata["confidence"], 90);
+        assert_eq!(merge.merged.metadata["commit"], "abc");
+
+        let theirs = content(
+            "Goal (retitled)",
+            "pending",
+            serde_json::json!({"confidence": 10, "commit": "def"}),
+        );
+        let merge = merge_content(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, vec!["title", "metadata.commit"]);
+        assert_eq!(merge.merged.metadata["confidence"], 10);
+        assert_eq!(merge.merged.title, "Goal (renamed)");
+    }
+
+    #[test]
+    fn test_content_hash_ignores_metadata_key_order() {
+        let mut a = sample_node(1, "cid", "goal", "Goal");
+        let mut b = a.clone();
+        a.metadata_json = Some(r#"{"branch": "main", "confidence": 90}"#.to_string());
+        b.metadata_json = Some(r#"{"confidence":90,"branch":"main"}"#.to_string());
+        b.updated_at = "2030-01-01T00:00:00Z".to_string();
+        assert_eq!(
+            NodeContent::of_node(&a).hash(),
+            NodeContent::of_node(&b).hash()
+        );
+    }
+
+    /// Two databases that both hold one shared goal, as after a first sync
+    fn synced_pair(dir: &tempfile::TempDir) -> (Database, Database, i32, i32) {
+        let alice = Database::new(dir.path().join("alice.db").to_str().unwrap()).unwrap();
+        let bob = Database::new(dir.path().join("bob.db").to_str().unwrap()).unwrap();
+        let goal = alice
+            .create_node("goal", "Goal", None, Some(50), None)
+            .unwrap();
+        let patch = alice
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        bob.apply_patch(&patch, false).unwrap();
+        let bob_goal = bob.get_all_nodes().unwrap()[0].id;
+        (alice, bob, goal, bob_goal)
+    }
+
+    #[test]
+    fn test_apply_merges_concurrent_edits_to_different_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let (alice, bob, goal, bob_goal) = synced_pair(&dir);
+
+        alice
+            .update_node(goal, Some("Goal (renamed)"), None, None, None, None, None)
+            .unwrap();
+        bob.update_node_status(bob_goal, "completed").unwrap();
+
+        let patch = alice
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        let result = bob.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_merged, 1);
+        assert!(result.conflicts.is_empty());
+        let merged = bob.get_node(bob_goal).unwrap().unwrap();
+        assert_eq!(merged.title, "Goal (renamed)");
+        assert_eq!(merged.status, "completed");
+
+        // Bob's merge goes back to Alice as a fast-forward
+        let patch = bob.export_patch(None, None, &[], None, None, None).unwrap();
+        let result = alice.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_updated, 1);
+        assert_eq!(alice.get_node(goal).unwrap().unwrap().status, "completed");
+    }
+
+    #[test]
+    fn test_apply_records_conflicts_and_resolution_fast_forwards() {
+        let dir = tempfile::tempdir().unwrap();
+        let (alice, bob, goal, bob_goal) = synced_pair(&dir);
+
+        alice
+            .update_node(goal, Some("Alice's title"), None, None, None, None, None)
+            .unwrap();
+        bob.update_node(bob_goal, Some("Bob's title"), None, None, None, None, None)
+            .unwrap();
+
+        let patch = alice
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        let dry = bob.apply_patch(&patch, true).unwrap();
+        assert_eq!(dry.conflicts.len(), 1);
+        assert!(bob.get_unresolved_node_conflicts().unwrap().is_empty());
+
+        let result = bob.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        assert!(result.conflicts[0].contains("both sides changed title"));
+        assert_eq!(
+            bob.get_node(bob_goal).unwrap().unwrap().title,
+            "Bob's title"
+        );
+
+        // Re-applying doesn't record the same conflict twice
+        bob.apply_patch(&patch, false).unwrap();
+        let conflicts = bob.get_unresolved_node_conflicts().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].base_value.is_some());
+
+        bob.resolve_node_conflict(&conflicts[0], &ConflictResolution::Ours)
+            .unwrap();
+        assert!(bob.get_unresolved_node_conflicts().unwrap().is_empty());
+
+        // Alice receives Bob's title without a conflict of her own
+        let patch = bob.export_patch(None, None, &[], None, None, None).unwrap();
+        let result = alice.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_updated, 1);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(alice.get_node(goal).unwrap().unwrap().title, "Bob's title");
+    }
+
+    #[test]
+    fn test_apply_fast_forwards_past_unshared_exports() {
+        let dir = tempfile::tempdir().unwrap();
+        let (alice, bob, goal, bob_goal) = synced_pair(&dir);
+        let rename = |title: &str| {
+            alice
+                .update_node(goal, Some(title), None, None, None, None, None)
+                .unwrap();
+        };
+
+        // Exports Bob never sees don't make him lose track of the common version
+        rename("Goal v2");
+        alice
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        rename("Goal v3");
+        alice.snapshot_patch(None).unwrap();
+        rename("Goal v4");
+        let patch = alice
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        let result = bob.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_updated, 1);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(bob.get_node(bob_goal).unwrap().unwrap().title, "Goal v4");
+
+        // Concurrent edits still merge against the version Bob last received
+        bob.update_node_status(bob_goal, "completed").unwrap();
+        rename("Goal v5");
+        alice
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        rename("Goal v6");
+        let patch = alice
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        let result = bob.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_merged, 1);
+        assert!(result.conflicts.is_empty());
+        let merged = bob.get_node(bob_goal).unwrap().unwrap();
+        assert_eq!(merged.title, "Goal v6");
+        assert_eq!(merged.status, "completed");
+    }
+
+    #[test]
+    fn test_resolve_conflict_theirs_and_edit() {
+        let dir = tempfile::tempdir().unwrap();
+        let (alice, bob, goal, bob_goal) = synced_pair(&dir);
+
+        alice
+            .update_node(goal, Some("Alice's title"), None, None, None, None, None)
+            .unwrap();
+        bob.update_node(bob_goal, Some("Bob's title"), None, None, None, None, None)
+            .unwrap();
+        let patch = alice
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        bob.apply_patch(&patch, false).unwrap();
+
+        let conflict = bob.get_unresolved_node_conflicts().unwrap().remove(0);
+        bob.resolve_node_conflict(&conflict, &ConflictResolution::Theirs)
+            .unwrap();
+        let node = bob.get_node(bob_goal).unwrap().unwrap();
+        assert_eq!(node.title, "Alice's title");
+        assert_eq!(node.metadata().confidence, Some(50));
+
+        let mut edited = NodeContent::of_node(&node);
+        edited.title = "Agreed title".to_string();
+        edited.status = "bogus".to_string();
+        let err = bob
+            .resolve_node_conflict(&conflict, &ConflictResolution::Edit(edited.clone()))
+            .unwrap_err();
+        assert!(err.to_string().contains("bogus"));
+
+        edited.status = "active".to_string();
+        bob.resolve_node_conflict(&conflict, &ConflictResolution::Edit(edited))
+            .unwrap();
+        let node = bob.get_node(bob_goal).unwrap().unwrap();
+        assert_eq!(
+            (node.title.as_str(), node.status.as_str()),
+            ("Agreed title", "active")
+        );
+    }
+
+    // === Incremental Export Tests ===
+
+    #[test]
+    fn test_resolve_since() {
+        assert_eq!(
+            resolve_since("2024-05-01T10:00:00+02:00").unwrap(),
+            "2024-05-01T10:00:00+02:00"
+        );
+        let date = resolve_since("2024-05-01").unwrap();
+        assert!(date.starts_with("2024-05-01T00:00:00"));
+        assert!(resolve_since("definitely-not-a-ref-or-date").is_err());
+    }
+
+    #[test]
+    fn test_export_since_adds_stubs_for_boundary_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+        let target = Database::new(dir.path().join("b.db").to_str().unwrap()).unwrap();
+
+        let goal = source
+            .create_node("goal", "Goal", None, None, None)
+            .unwrap();
+        let decision = source
+            .create_node("decision", "Decision", None, None, None)
+            .unwrap();
+        source
+            .create_edge(goal, decision, "leads_to", None)
+            .unwrap();
+        let full = source
+            .export_patch(None, None, &[], None, None, None)
+            .unwrap();
+        target.apply_patch(&full, false).unwrap();
+
+        let since = chrono::Local::now().to_rfc3339();
+        let action = source
+            .create_node("action", "Action", None, None, None)
+            .unwrap();
+        source
+            .create_edge(decision, action, "leads_to", None)
+            .unwrap();
+        source.update_node_status(goal, "active").unwrap();
+
+        let patch = source
+            .export_patch(None, None, &[], None, None, Some(&since))
+            .unwrap();
+        let titles: Vec<(&str, bool)> = patch
+            .nodes
+            .iter()
+            .map(|n| (n.title.as_str(), n.stub))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![("Goal", false), ("Action", false), ("Decision", true)]
+        );
+        assert_eq!(patch.edges.len(), 1);
+        assert_eq!(patch.stub_count(), 1);
+
+        // The stub never overwrites the teammate's own copy
+        let target_decision = target
+            .get_all_nodes()
+            .unwrap()
+            .into_iter()
+            .find(|n| n.title == "Decision")
+            .unwrap();
+        target
+            .update_node(
+                target_decision.id,
+                Some("Decision (theirs)"),
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        let result = target.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_added, 1);
+        assert_eq!(result.nodes_updated, 1);
+        assert_eq!(result.edges_added, 1);
+        assert!(result.edges_failed.is_empty());
+        assert_eq!(
+            target.get_node(target_decision.id).unwrap().unwrap().title,
+            "Decision (theirs)"
+        );
+
+        // A database that never saw the boundary node gets it from the stub
+        let fresh = Database::new(dir.path().join("c.db").to_str().unwrap()).unwrap();
+        let result = fresh.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_added, 3);
+        assert!(result.edges_failed.is_empty());
+    }
+
+    // === Application History Tests ===
+
+    #[test]
+    fn test_order_patches_puts_providers_first() {
+        let goal = sample_node(1, "goal", "goal", "Goal");
+        let action = sample_node(2, "action", "action", "Action");
+
+        // The older patch builds on a node only the newer one carries in full
+        let mut dependent = GraphPatch::new(None, None, None);
+        dependent.created_at = "2024-01-01T00:00:00Z".to_string();
+        dependent.add_stub(&goal);
+        dependent.add_node(&action);
+        dependent.add_edge(&sample_edge(1, 1, 2, "goal", "action"));
+        let mut provider = GraphPatch::new(None, None, None);
+        provider.created_at = "2024-02-01T00:00:00Z".to_string();
+        provider.add_node(&goal);
+        let mut unrelated = GraphPatch::new(None, None, None);
+        unrelated.created_at = "2023-12-01T00:00:00Z".to_string();
+
+        assert_eq!(
+            order_patches(&[dependent, provider, unrelated]),
+            vec![2, 1, 0]
+        );
+
+        // Patches needing each other fall back to creation order
+        let mut older = GraphPatch::new(None, None, None);
+        older.created_at = "2024-01-01T00:00:00Z".to_string();
+        older.add_node(&goal);
+        older.add_stub(&action);
+        let mut newer = GraphPatch::new(None, None, None);
+        newer.created_at = "2024-02-01T00:00:00Z".to_string();
+        newer.add_node(&action);
+        newer.add_stub(&goal);
+        assert_eq!(order_patches(&[newer, older]), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_record_and_revert_applied_patch() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+        let target = Database::new(dir.path().join("b.db").to_str().unwrap()).unwrap();
+
+        let shared = source
+            .create_node("goal", "Shared", None, None, None)
+            .unwrap();
+        target
+            .apply_patch(
+                &source
+                    .export_patch(None, None, &[], None, None, None)
+                    .unwrap(),
+                false,
+            )
+            .unwrap();
+
+        let since = chrono::Local::now().to_rfc3339();
+        let action = source
+            .create_node("action", "From patch", None, None, None)
+            .unwrap();
+        source
+            .create_edge(shared, action, "leads_to", None)
+            .unwrap();
+        let patch = source
+            .export_patch(None, None, &[], None, None, Some(&since))
+            .unwrap();
+
+        let result = target.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_introduced.len(), 1);
+        assert_eq!(result.edges_introduced.len(), 1);
+        target
+            .record_applied_patch_result(&patch, "p.json", &result)
+            .unwrap();
+        let applied = target.get_applied_patches().unwrap();
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].digest, patch.effective_digest());
+        assert_eq!((applied[0].nodes_added, applied[0].edges_added), (1, 1));
+        assert!(!applied[0].is_partial());
+
+        // A local edge onto the patch's node goes with it
+        let local = target
+            .create_node("outcome", "Local", None, None, None)
+            .unwrap();
+        let from_patch = target
+            .get_all_nodes()
+            .unwrap()
+            .into_iter()
+            .find(|n| n.title == "From patch")
+            .unwrap();
+        target
+            .create_edge(from_patch.id, local, "leads_to", None)
+            .unwrap();
+
+        let reverted = target.revert_patch(&applied[0].digest).unwrap();
+        assert_eq!(reverted.nodes_removed, 1);
+        assert_eq!(reverted.edges_removed, 1);
+        assert_eq!(reverted.dependent_edges_removed, 1);
+        let titles: Vec<String> = target
+            .get_all_nodes()
+            .unwrap()
+            .into_iter()
+            .map(|n| n.title)
+            .collect();
+        assert_eq!(titles, vec!["Shared", "Local"]);
+        assert!(target.get_applied_patches().unwrap().is_empty());
+        assert!(target.get_tombstones().unwrap().is_empty());
+
+        // No tombstones, so the patch applies again
+        let result = target.apply_patch(&patch, false).unwrap();
+        assert_eq!((result.nodes_added, result.edges_added), (1, 1));
+    }
+
+    // === Squash and Snapshot Tests ===
+
+    #[test]
+    fn test_squash_patches() {
+        let mut goal = sample_node(1, "goal", "goal", "Goal v1");
+        let decision = sample_node(2, "decision", "decision", "Decision");
+        let action = sample_node(3, "action", "action", "Action");
+
+        let mut first = GraphPatch::new(Some("alice".to_string()), None, None);
+        first.created_at = "2024-01-01T00:00:00Z".to_string();
+        first.add_node_with_base(&goal, vec!["base-v0".to_string()]);
+        first.add_node(&decision);
+        let mut edge = sample_edge(1, 1, 2, "goal", "decision");
+        edge.rationale = None;
+        first.add_edge(&edge);
+
+        goal.title = "Goal v2".to_string();
+        goal.updated_at = "2024-02-01T00:00:00Z".to_string();
+        let mut second = GraphPatch::new(Some("alice".to_string()), None, None);
+        second.created_at = "2024-02-01T00:00:00Z".to_string();
+        second.add_node_with_base(&goal, vec!["base-v1".to_string()]);
+        second.add_stub(&decision);
+        second.add_node(&action);
+        second.add_edge(&sample_edge(1, 1, 2, "goal", "decision"));
+        second.add_edge(&sample_edge(2, 2, 3, "decision", "action"));
+
+        let mut third = GraphPatch::new(Some("bob".to_string()), None, None);
+        third.created_at = "2024-03-01T00:00:00Z".to_string();
+        third.add_tombstone(&Tombstone {
+            id: 1,
+            kind: TOMBSTONE_NODE.to_string(),
+            change_id: "action".to_string(),
+            from_change_id: None,
+            to_change_id: None,
+            edge_type: None,
+            deleted_at: "2024-03-01T00:00:00Z".to_string(),
+        });
+
+        let squashed = squash_patches(&[third, second, first]);
+        let nodes: Vec<(&str, bool, Vec<String>)> = squashed
+            .nodes
+            .iter()
+            .map(|n| (n.title.as_str(), n.stub, n.lineage()))
+            .collect();
+        assert_eq!(
+            nodes,
+            // Same creation time, so ordered by change_id
+            vec![
+                ("Decision", false, vec![]),
+                (
+                    "Goal v2",
+                    false,
+                    vec!["base-v1".to_string(), "base-v0".to_string()]
+                )
+            ]
+        );
+        assert_eq!(squashed.edges.len(), 1);
+        assert_eq!(
+            squashed.edges[0].rationale.as_deref(),
+            Some("test rationale")
+        );
+        assert_eq!(squashed.tombstones.len(), 1);
+        assert_eq!(squashed.author, None);
+        assert_eq!(squashed.created_at, "2024-03-01T00:00:00Z");
+        let keys = KeyStore::new(tempfile::tempdir().unwrap().path().join("keys"));
+        assert_eq!(squashed.verify(&keys, false), Ok(PatchIntegrity::Sealed));
+    }
+
+    #[test]
+    fn test_snapshot_is_stable() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+        let goal = db.create_node("goal", "Goal", None, None, None).unwrap();
+        let action = db
+            .create_node("action", "Action", None, None, None)
+            .unwrap();
+        db.create_edge(goal, action, "leads_to", None).unwrap();
+
+        let first = serde_json::to_string_pretty(&db.snapshot_patch(None).unwrap()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = serde_json::to_string_pretty(&db.snapshot_patch(None).unwrap()).unwrap();
+        assert_eq!(first, second);
+
+        let snapshot = db.snapshot_patch(None).unwrap();
+        assert_eq!(snapshot.branch, None);
+        assert_eq!(snapshot.nodes[0].title, "Goal");
+        assert_eq!(snapshot.created_at, snapshot.nodes[1].modified_at());
+    }
+
+    // === Alias Tests ===
+
+    #[test]
+    fn test_apply_resolves_merged_change_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+
+        let keep = db.create_node("goal", "Goal", None, None, None).unwrap();
+        let dup = db
+            .create_node("goal", "Goal again", None, None, None)
+            .unwrap();
+        let dup_cid = db.get_node(dup).unwrap().unwrap().change_id;
+        db.merge_nodes(keep, &[dup]).unwrap();
+
+        // A teammate's patch still references the duplicate
+        let mut patch = GraphPatch::new(None, None, None);
+        patch.nodes.push(PatchNode {
+            change_id: dup_cid.clone(),
+            node_type: "goal".to_string(),
+            title: "Goal again".to_string(),
+            description: None,
+            status: "pending".to_string(),
+            metadata_json: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: None,
+            base_hash: None,
+            ancestor_hashes: Vec::new(),
+            stub: false,
+            author: None,
+        });
+        patch.nodes.push(PatchNode {
+            change_id: "new-action".to_string(),
+            node_type: "action".to_string(),
+            title: "Action".to_string(),
+            description: None,
+            status: "pending".to_string(),
+            metadata_json: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: None,
+            base_hash: None,
+            ancestor_hashes: Vec::new(),
+            stub: false,
+            author: None,
+        });
+        patch.edges.push(PatchEdge {
+            from_change_id: dup_cid,
+            to_change_id: "new-action".to_string(),
+            edge_type: "leads_to".to_string(),
+            rationale: None,
+            author: None,
+        });
+
+        let result = db.apply_patch(&patch, false).unwrap();
+        assert_eq!(result.nodes_added, 1);
+        assert_eq!(result.nodes_skipped, 1);
+        assert_eq!(result.edges_added, 1);
+        assert!(result.edges_failed.is_empty());
+
+        let edges = db.get_all_edges().unwrap();
+        assert_eq!(edges[0].from_node_id, keep);
+    }
+}
+
+// =============================================================================
+// Property-Based Tests (Proptest)
+// =============================================================================
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use crate::db::{NodeStatus, NodeType};
+    use proptest::prelude::*;
+
+    // Metadata mixing known keys with ones this version doesn't understand
+    fn metadata_json() -> impl Strategy<Value = Option<String>> {
+        let metadata = (
+            proptest::option::of(0..=100u8),
+            proptest::option::of("[0-9a-f]{7,40}"),
+            proptest::option::of("\\PC{0,40}"),
+            proptest::collection::vec("[a-z]{1,8}", 0..3),
+            proptest::option::of(any::<i64>()),
+        )
+            .prop_map(|(confidence, commit, prompt, tags, custom)| {
+                let mut map = serde_json::Map::new();
+                if let Some(c) = confidence {
+                    map.insert("confidence".into(), c.into());
+                }
+                if let Some(c) = commit {
+                    map.insert("commit".into(), c.into());
+                }
+                if let Some(p) = prompt {
+                    map.insert("prompt".into(), p.into());
+                }
+                if !tags.is_empty() {
+                    map.insert("tags".into(), tags.into());
+                }
+                if let Some(v) = custom {
+                    map.insert("x_custom".into(), serde_json::json!({ "value": v }));
+                }
+                serde_json::Value::Object(map).to_string()
+            });
+        proptest::option::of(metadata)
+    }
+
+    fn timestamp() -> impl Strategy<Value = String> {
+        (0i64..4_000_000_000, -12i32..=14).prop_map(|(secs, offset_hours)| {
+            let offset = chrono::FixedOffset::east_opt(offset_hours * 3600).unwrap();
+            chrono::DateTime::from_timestamp(secs, 0)
+                .unwrap()
+                .with_timezone(&offset)
+                .to_rfc3339()
+        })
+    }
+
+    fn decision_node() -> impl Strategy<Value = DecisionNode> {
+        (
+            prop::sample::select(NodeType::ALL.map(|t| t.as_str()).to_vec()),
+            "\\PC{1,60}",
+            proptest::option::of("\\PC{0,80}"),
+            prop::sample::select(NodeStatus::ALL.map(|s| s.as_str()).to_vec()),
+            timestamp(),
+            timestamp(),
+            metadata_json(),
+            proptest::option::of("\\PC{1,30}"),
+        )
+            .prop_map(
+                |(
+                    node_type,
+                    title,
+                    description,
+                    status,
+                    created_at,
+                    updated_at,
+                    metadata_json,
+                    author,
+                )| {
+                    DecisionNode {
+                        id: 0,
+                        change_id: uuid::Uuid::new_v4().to_string(),
+                        node_type: node_type.to_string(),
+                        title,
+                        description,
+                        status: status.to_string(),
+                        created_at,
+                        updated_at,
+                        metadata_json,
+                        author,
+                    }
+                },
+            )
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn prop_export_apply_round_trips_nodes(nodes in proptest::collection::vec(decision_node(), 1..6)) {
+            let dir = tempfile::tempdir().unwrap();
+            let source = Database::new(dir.path().join("a.db").to_str().unwrap()).unwrap();
+            let target = Database::new(dir.path().join("b.db").to_str().unwrap()).unwrap();
+            for node in &nodes {
+                source.import_node(node).unwrap();
+            }
+
+            let patch = source.export_patch(None, None, &[], None, None, None).unwrap();
+            let result = target.apply_patch(&patch, false).unwrap();
+            prop_assert_eq!(result.nodes_added, nodes.len());
+
+            let mut expected = source.get_all_nodes().unwrap();
+            let mut applied = target.get_all_nodes().unwrap();
+            expected.sort_by(|a, b| a.change_id.cmp(&b.change_id));
+            applied.sort_by(|a, b| a.change_id.cmp(&b.change_id));
+            for (a, b) in expected.iter().zip(&applied) {
+                prop_assert_eq!(&a.change_id, &b.change_id);
+                prop_assert_eq!(&a.node_type, &b.node_type);
+                prop_assert_eq!(&a.title, &b.title);
+                prop_assert_eq!(&a.description, &b.description);
+                prop_assert_eq!(&a.status, &b.status);
+                prop_assert_eq!(&a.created_at, &b.created_at);
+                prop_assert_eq!(&a.updated_at, &b.updated_at);
+                prop_assert_eq!(&a.metadata_json, &b.metadata_json);
+                prop_assert_eq!(&a.author, &b.author);
+            }
+        }
+    }
 }
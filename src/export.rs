@@ -2,7 +2,9 @@
 //!
 //! Provides DOT graph export and PR writeup generation.
 
-use crate::db::{DecisionEdge, DecisionGraph, DecisionNode};
+use crate::db::{
+    DecisionContext, DecisionEdge, DecisionGraph, DecisionNode, EdgeType, NodeMetadata, NodeType,
+};
 use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 
@@ -24,6 +26,19 @@ macro_rules! wln {
     };
 }
 
+/// Git commit info for the timeline view (docs/git-history.json entries)
+///
+/// Matches `GitCommit` in web/src/types/graph.ts.
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct GitCommit {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub date: String,
+    pub message: String,
+    pub files_changed: Option<u32>,
+}
+
 /// Configuration for DOT export
 #[derive(Debug, Clone)]
 pub struct DotConfig {
@@ -53,48 +68,48 @@ impl Default for DotConfig {
 
 /// Get the shape for a node type
 fn node_shape(node_type: &str) -> &'static str {
-    match node_type {
-        "goal" => "house",
-        "decision" => "diamond",
-        "option" => "parallelogram",
-        "action" => "box",
-        "outcome" => "ellipse",
-        "observation" => "note",
-        _ => "box",
+    match node_type.parse::<NodeType>() {
+        Ok(NodeType::Goal) => "house",
+        Ok(NodeType::Decision) => "diamond",
+        Ok(NodeType::Option) => "parallelogram",
+        Ok(NodeType::Action) => "box",
+        Ok(NodeType::Outcome) => "ellipse",
+        Ok(NodeType::Observation) => "note",
+        Err(_) => "box",
     }
 }
 
 /// Get the fill color for a node type
 fn node_color(node_type: &str) -> &'static str {
-    match node_type {
-        "goal" => "#FFE4B5",        // Moccasin (warm yellow)
-        "decision" => "#E6E6FA",    // Lavender
-        "option" => "#E0FFFF",      // Light cyan
-        "action" => "#90EE90",      // Light green
-        "outcome" => "#87CEEB",     // Sky blue
-        "observation" => "#DDA0DD", // Plum
-        _ => "#F5F5F5",             // White smoke
+    match node_type.parse::<NodeType>() {
+        Ok(NodeType::Goal) => "#FFE4B5",        // Moccasin (warm yellow)
+        Ok(NodeType::Decision) => "#E6E6FA",    // Lavender
+        Ok(NodeType::Option) => "#E0FFFF",      // Light cyan
+        Ok(NodeType::Action) => "#90EE90",      // Light green
+        Ok(NodeType::Outcome) => "#87CEEB",     // Sky blue
+        Ok(NodeType::Observation) => "#DDA0DD", // Plum
+        Err(_) => "#F5F5F5",                    // White smoke
     }
 }
 
 /// Get the edge style based on edge type
 fn edge_style(edge_type: &str) -> &'static str {
-    match edge_type {
-        "chosen" => "bold",
-        "rejected" => "dashed",
-        "blocks" => "dotted",
+    match edge_type.parse::<EdgeType>() {
+        Ok(EdgeType::Chosen) => "bold",
+        Ok(EdgeType::Rejected) => "dashed",
+        Ok(EdgeType::Blocks) => "dotted",
         _ => "solid",
     }
 }
 
 /// Get the edge color based on edge type
 fn edge_color(edge_type: &str) -> &'static str {
-    match edge_type {
-        "chosen" => "#228B22",   // Forest green
-        "rejected" => "#DC143C", // Crimson
-        "blocks" => "#FF4500",   // Orange red
-        "enables" => "#4169E1",  // Royal blue
-        _ => "#333333",          // Dark gray
+    match edge_type.parse::<EdgeType>() {
+        Ok(EdgeType::Chosen) => "#228B22",   // Forest green
+        Ok(EdgeType::Rejected) => "#DC143C", // Crimson
+        Ok(EdgeType::Blocks) => "#FF4500",   // Orange red
+        Ok(EdgeType::Enables) => "#4169E1",  // Royal blue
+        _ => "#333333",                      // Dark gray
     }
 }
 
@@ -118,24 +133,12 @@ fn truncate(s: &str, max_len: usize) -> String {
 
 /// Extract confidence from metadata_json
 fn extract_confidence(metadata: &Option<String>) -> Option<u8> {
-    metadata.as_ref().and_then(|m| {
-        serde_json::from_str::<serde_json::Value>(m)
-            .ok()
-            .and_then(|v| v.get("confidence").and_then(|c| c.as_u64()))
-            .map(|c| c as u8)
-    })
+    NodeMetadata::from_option(metadata.as_deref()).confidence
 }
 
 /// Extract commit hash from metadata_json
 fn extract_commit(metadata: &Option<String>) -> Option<String> {
-    metadata.as_ref().and_then(|m| {
-        serde_json::from_str::<serde_json::Value>(m)
-            .ok()
-            .and_then(|v| {
-                v.get("commit")
-                    .and_then(|c| c.as_str().map(|s| s.to_string()))
-            })
-    })
+    NodeMetadata::from_option(metadata.as_deref()).commit
 }
 
 /// Convert a decision graph to DOT format
@@ -250,6 +253,7 @@ pub fn filter_graph_from_roots(graph: &DecisionGraph, root_ids: &[i32]) -> Decis
         .collect();
 
     DecisionGraph {
+        context: context_of(graph, &nodes),
         nodes,
         edges,
         config: graph.config.clone(),
@@ -275,12 +279,35 @@ pub fn filter_graph_by_ids(graph: &DecisionGraph, node_ids: &[i32]) -> DecisionG
         .collect();
 
     DecisionGraph {
+        context: context_of(graph, &nodes),
         nodes,
         edges,
         config: graph.config.clone(),
     }
 }
 
+/// Context items belonging to `nodes`
+fn context_of(graph: &DecisionGraph, nodes: &[DecisionNode]) -> Vec<DecisionContext> {
+    let ids: HashSet<i32> = nodes.iter().map(|n| n.id).collect();
+    graph
+        .context
+        .iter()
+        .filter(|c| ids.contains(&c.node_id))
+        .cloned()
+        .collect()
+}
+
+/// Filter a graph to nodes carrying every one of `tags` (no traversal)
+pub fn filter_graph_by_tags(graph: &DecisionGraph, tags: &[String]) -> DecisionGraph {
+    let node_ids: Vec<i32> = graph
+        .nodes
+        .iter()
+        .filter(|n| n.has_all_tags(tags))
+        .map(|n| n.id)
+        .collect();
+    filter_graph_by_ids(graph, &node_ids)
+}
+
 /// Parse a node range specification (e.g., "1-11" or "1,2,5-10,15")
 pub fn parse_node_range(spec: &str) -> Vec<i32> {
     let mut ids = Vec::new();
@@ -307,6 +334,31 @@ pub fn parse_node_range(spec: &str) -> Vec<i32> {
     ids
 }
 
+/// Format node IDs as a compact range spec (e.g. "1-3,7"), the inverse of `parse_node_range`
+pub fn format_node_range(ids: &[i32]) -> String {
+    let mut sorted = ids.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut parts: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < sorted.len() {
+        let start = sorted[i];
+        let mut end = start;
+        while i + 1 < sorted.len() && sorted[i + 1] == end + 1 {
+            i += 1;
+            end = sorted[i];
+        }
+        parts.push(if start == end {
+            start.to_string()
+        } else {
+            format!("{}-{}", start, end)
+        });
+        i += 1;
+    }
+    parts.join(",")
+}
+
 /// Configuration for PR writeup generation
 #[derive(Debug, Clone)]
 pub struct WriteupConfig {
@@ -343,7 +395,7 @@ pub fn generate_pr_writeup(graph: &DecisionGraph, config: &WriteupConfig) -> Str
     let goals: Vec<&DecisionNode> = filtered
         .nodes
         .iter()
-        .filter(|n| n.node_type == "goal")
+        .filter(|n| n.node_type == NodeType::Goal.as_str())
         .collect();
 
     if !goals.is_empty() {
@@ -565,6 +617,7 @@ mod tests {
                     created_at: "2025-01-01T00:00:00Z".to_string(),
                     updated_at: "2025-01-01T00:00:00Z".to_string(),
                     metadata_json: Some(r#"{"confidence":90}"#.to_string()),
+                    author: None,
                 },
                 DecisionNode {
                     id: 2,
@@ -576,6 +629,7 @@ mod tests {
                     created_at: "2025-01-01T00:00:00Z".to_string(),
                     updated_at: "2025-01-01T00:00:00Z".to_string(),
                     metadata_json: None,
+                    author: None,
                 },
                 DecisionNode {
                     id: 3,
@@ -587,6 +641,7 @@ mod tests {
                     created_at: "2025-01-01T00:00:00Z".to_string(),
                     updated_at: "2025-01-01T00:00:00Z".to_string(),
                     metadata_json: Some(r#"{"commit":"abc1234"}"#.to_string()),
+                    author: None,
                 },
             ],
             edges: vec![
@@ -600,6 +655,7 @@ mod tests {
                     weight: Some(1.0),
                     rationale: Some("Goal requires decision".to_string()),
                     created_at: "2025-01-01T00:00:00Z".to_string(),
+                    author: None,
                 },
                 DecisionEdge {
                     id: 2,
@@ -611,9 +667,11 @@ mod tests {
                     weight: Some(1.0),
                     rationale: None,
                     created_at: "2025-01-01T00:00:00Z".to_string(),
+                    author: None,
                 },
             ],
             config: None,
+            context: vec![],
         }
     }
 
@@ -790,6 +848,25 @@ mod tests {
         assert!(filtered.edges.is_empty());
     }
 
+    #[test]
+    fn test_filter_graph_keeps_context_of_kept_nodes() {
+        let mut graph = sample_graph();
+        graph.context = [1, 3]
+            .into_iter()
+            .map(|node_id| DecisionContext {
+                id: node_id,
+                node_id,
+                context_type: "error".to_string(),
+                content_json: crate::db::build_context_json("boom", None),
+                captured_at: "2024-01-01T00:00:00Z".to_string(),
+            })
+            .collect();
+
+        let filtered = filter_graph_by_ids(&graph, &[3]);
+        assert_eq!(filtered.context.len(), 1);
+        assert_eq!(filtered.context[0].node_id, 3);
+    }
+
     #[test]
     fn test_filter_graph_single_node() {
         let graph = sample_graph();
@@ -808,6 +885,32 @@ mod tests {
         assert!(filtered.nodes.is_empty());
     }
 
+    #[test]
+    fn test_filter_graph_by_tags() {
+        let mut graph = sample_graph();
+        graph.nodes[0].metadata_json = Some(r#"{"tags":["perf","debt"]}"#.to_string());
+        graph.nodes[1].metadata_json = Some(r#"{"tags":["perf"]}"#.to_string());
+
+        let perf = filter_graph_by_tags(&graph, &["perf".to_string()]);
+        assert_eq!(perf.nodes.len(), 2);
+        assert_eq!(perf.edges.len(), 1);
+
+        let both = filter_graph_by_tags(&graph, &["perf".to_string(), "debt".to_string()]);
+        assert_eq!(both.nodes.len(), 1);
+        assert_eq!(both.nodes[0].id, 1);
+        assert!(both.edges.is_empty());
+    }
+
+    #[test]
+    fn test_format_node_range_round_trip() {
+        assert_eq!(format_node_range(&[]), "");
+        assert_eq!(format_node_range(&[7, 1, 2, 3, 3, 9, 10]), "1-3,7,9-10");
+        assert_eq!(
+            parse_node_range(&format_node_range(&[5, 1, 2, 8])),
+            vec![1, 2, 5, 8]
+        );
+    }
+
     // === Extract Tests ===
 
     #[test]
@@ -893,6 +996,7 @@ mod tests {
             nodes: vec![],
             edges: vec![],
             config: None,
+            context: vec![],
         };
         let config = DotConfig::default();
         let dot = graph_to_dot(&graph, &config);
@@ -907,6 +1011,7 @@ mod tests {
             nodes: vec![],
             edges: vec![],
             config: None,
+            context: vec![],
         };
         let config = WriteupConfig {
             title: "Empty".to_string(),
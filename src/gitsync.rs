@@ -0,0 +1,272 @@
+//! Git-native graph sync through a dedicated ref
+//!
+//! Instead of loose patch files, the whole graph is stored as `graph.json` (a sealed
+//! [`GraphPatch`]) in a chain of commits on `refs/deciduous/graph`. The commits are
+//! built with git plumbing, so the working tree, index and branches are never
+//! touched. `deciduous push` commits the local graph and pushes the ref;
+//! `deciduous pull` fetches it and applies it through the usual change_id merge.
+
+use crate::db::Database;
+use crate::diff::{ApplyResult, GraphPatch};
+use crate::signing::{KeyStore, PatchIntegrity};
+use ed25519_dalek::SigningKey;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Ref holding the graph commit chain
+pub const GRAPH_REF: &str = "refs/deciduous/graph";
+
+/// File in each graph commit's tree
+pub const GRAPH_FILE: &str = "graph.json";
+
+/// Local ref where `pull` keeps the last fetched graph of a remote
+pub fn remote_graph_ref(remote: &str) -> String {
+    format!("refs/deciduous/remotes/{}/graph", remote)
+}
+
+/// What `Database::push_graph` did
+#[derive(Debug, Clone)]
+pub struct PushResult {
+    /// Commit the remote ref now points at
+    pub commit: String,
+    /// False when the graph was unchanged since the last graph commit
+    pub committed: bool,
+    pub node_count: usize,
+    pub edge_count: usize,
+}
+
+/// What `Database::pull_graph` did
+#[derive(Debug)]
+pub struct PullResult {
+    /// Fetched commit, or None if the remote has no graph ref yet
+    pub commit: Option<String>,
+    pub integrity: Option<PatchIntegrity>,
+    pub apply: ApplyResult,
+    /// Local graph history had diverged and a merge commit was created
+    pub merged: bool,
+}
+
+impl Database {
+    /// Commit the local graph onto `refs/deciduous/graph` and push it to `remote`.
+    ///
+    /// Fails if the remote has graph commits we haven't pulled yet.
+    pub fn push_graph(
+        &self,
+        remote: &str,
+        author: Option<String>,
+        key: Option<&SigningKey>,
+    ) -> Result<PushResult, String> {
+        let local = resolve_ref(GRAPH_REF)?;
+        let (commit, committed, patch) =
+            self.commit_graph(local.as_deref(), &[], author, key, "Update decision graph")?;
+        run_git(
+            &["push", remote, &format!("{}:{}", GRAPH_REF, GRAPH_REF)],
+            None,
+        )
+        .map_err(|e| {
+            if e.contains("non-fast-forward") || e.contains("rejected") {
+                format!(
+                    "{} has graph changes you don't have; run 'deciduous pull' first",
+                    remote
+                )
+            } else {
+                e
+            }
+        })?;
+        Ok(PushResult {
+            commit,
+            committed,
+            node_count: patch.nodes.len(),
+            edge_count: patch.edges.len(),
+        })
+    }
+
+    /// Fetch `refs/deciduous/graph` from `remote` and apply it to the local database.
+    ///
+    /// The local ref then fast-forwards to the fetched commit, or, if both sides have
+    /// new graph commits, gets a merge commit holding the combined graph.
+    pub fn pull_graph(
+        &self,
+        remote: &str,
+        keys: &KeyStore,
+        require_signed: bool,
+    ) -> Result<PullResult, String> {
+        let mut result = PullResult {
+            commit: None,
+            integrity: None,
+            apply: ApplyResult::default(),
+            merged: false,
+        };
+        let listed = run_git(&["ls-remote", remote, GRAPH_REF], None)?;
+        if listed.trim().is_empty() {
+            return Ok(result);
+        }
+
+        let tracking = remote_graph_ref(remote);
+        run_git(
+            &[
+                "fetch",
+                "--quiet",
+                remote,
+                &format!("+{}:{}", GRAPH_REF, tracking),
+            ],
+            None,
+        )?;
+        let fetched = resolve_ref(&tracking)?
+            .ok_or_else(|| format!("Fetching {} from {} failed", GRAPH_REF, remote))?;
+
+        let patch = read_graph(&fetched)?;
+        result.integrity = Some(
+            patch
+                .verify(keys, require_signed)
+                .map_err(|e| format!("Rejected {} from {}: {}", GRAPH_REF, remote, e))?,
+        );
+        result.apply = self.apply_patch(&patch, false).map_err(|e| e.to_string())?;
+        let source = format!("{}@{}", tracking, &fetched[..7.min(fetched.len())]);
+        self.record_applied_patch_result(&patch, &source, &result.apply)
+            .map_err(|e| e.to_string())?;
+
+        match resolve_ref(GRAPH_REF)? {
+            Some(local) if is_ancestor(&fetched, &local)? => {}
+            Some(local) if !is_ancestor(&local, &fetched)? => {
+                self.commit_graph(
+                    Some(&local),
+                    &[fetched.as_str()],
+                    patch.author.clone(),
+                    None,
+                    &format!("Merge decision graph from {}", remote),
+                )?;
+                result.merged = true;
+            }
+            local => update_ref(GRAPH_REF, &fetched, local.as_deref())?,
+        }
+        result.commit = Some(fetched);
+        Ok(result)
+    }
+
+    /// Export the whole graph and commit it on top of `parent` (plus `extra_parents`).
+    /// No commit is made if the graph is unchanged and there is nothing to merge.
+    fn commit_graph(
+        &self,
+        parent: Option<&str>,
+        extra_parents: &[&str],
+        author: Option<String>,
+        key: Option<&SigningKey>,
+        message: &str,
+    ) -> Result<(String, bool, GraphPatch), String> {
+        let mut patch = self
+            .export_patch(None, None, &[], author, None, None)
+            .map_err(|e| e.to_string())?;
+
+        if let (Some(parent), true) = (parent, extra_parents.is_empty()) {
+            // Only the export time differs when nothing changed
+            if let Ok(previous) = read_graph(parent) {
+                patch.created_at = previous.created_at.clone();
+                let unchanged = patch.compute_digest() == previous.compute_digest();
+                let resigned = key.is_some() && previous.signature.is_none();
+                if unchanged && !resigned {
+                    return Ok((parent.to_string(), false, previous));
+                }
+                patch.created_at = chrono::Local::now().to_rfc3339();
+            }
+        }
+        match key {
+            Some(key) => patch.sign(key),
+            None => patch.seal(),
+        }
+
+        let json = serde_json::to_string_pretty(&patch)
+            .map_err(|e| format!("Failed to serialize graph: {}", e))?;
+        let blob = run_git(&["hash-object", "-w", "--stdin"], Some(&json))?;
+        let tree = run_git(
+            &["mktree"],
+            Some(&format!("100644 blob {}\t{}\n", blob.trim(), GRAPH_FILE)),
+        )?;
+
+        let summary = format!(
+            "{}\n\n{} nodes, {} edges",
+            message,
+            patch.nodes.len(),
+            patch.edges.len()
+        );
+        let mut args = vec!["commit-tree", tree.trim(), "-m", &summary];
+        for p in parent.iter().chain(extra_parents) {
+            args.push("-p");
+            args.push(p);
+        }
+        let commit = run_git(&args, None)?.trim().to_string();
+        update_ref(GRAPH_REF, &commit, parent)?;
+        Ok((commit, true, patch))
+    }
+}
+
+/// Read the graph stored in a graph commit
+pub fn read_graph(rev: &str) -> Result<GraphPatch, String> {
+    let json = run_git(
+        &["cat-file", "blob", &format!("{}:{}", rev, GRAPH_FILE)],
+        None,
+    )?;
+    GraphPatch::from_json(&json).map_err(|e| format!("{}: {}", GRAPH_FILE, e))
+}
+
+/// Commit a ref points at, or None if it doesn't exist
+pub fn resolve_ref(name: &str) -> Result<Option<String>, String> {
+    let output = Command::new("git")
+        .args([
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", name),
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Some(sha).filter(|s| output.status.success() && !s.is_empty()))
+}
+
+/// Point a ref at a commit, failing if it moved from `old` in the meantime
+fn update_ref(name: &str, new: &str, old: Option<&str>) -> Result<(), String> {
+    // An all-zero old value asserts the ref doesn't exist yet
+    let zero = "0".repeat(new.len());
+    run_git(&["update-ref", name, new, old.unwrap_or(&zero)], None).map(|_| ())
+}
+
+/// Whether `ancestor` is reachable from `descendant`
+fn is_ancestor(ancestor: &str, descendant: &str) -> Result<bool, String> {
+    let status = Command::new("git")
+        .args(["merge-base", "--is-ancestor", ancestor, descendant])
+        .status()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    Ok(status.success())
+}
+
+/// Run git, returning stdout or stderr as the error
+fn run_git(args: &[&str], stdin: Option<&str>) -> Result<String, String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())
+            .map_err(|e| format!("Failed to write to git: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
@@ -31,6 +31,17 @@ main_branches = ["main", "master"]
 # Automatically detect and store git branch when creating nodes
 # Set to false to disable branch tracking entirely
 auto_detect = true
+
+[sync]
+# Only apply patches signed by a key in .deciduous/keys/*.pub
+# (sign exports with 'deciduous diff export --sign' after 'deciduous diff keygen')
+require_signed = false
+
+[author]
+# Recorded as the author of new nodes and edges ('deciduous nodes --author' filters on it)
+# Defaults to git config user.name / user.email
+# name = "Your Name"
+# email = "you@example.com"
 "#;
 
 /// GitHub Pages deploy workflow (deploys to gh-pages branch, safe for project repos)
@@ -319,7 +330,7 @@ deciduous diff apply --dry-run .deciduous/patches/bob-refactor.json
 ### Patch Format (JSON)
 ```json
 {
-  "version": "1.0",
+  "version": "1.3",
   "author": "alice",
   "branch": "feature/auth",
   "nodes": [{ "change_id": "uuid...", "title": "...", ... }],
@@ -353,7 +364,11 @@ argument-hint: [focus-area]
 ## Step 1: Query the Graph
 
 ```bash
-# See all decisions (look for recent ones and pending status)
+# Start here: open goals, pending decisions, recent outcomes and chains
+deciduous recover
+deciduous recover --branch   # Only the current branch
+
+# Drill down: all decisions (look for recent ones and pending status)
 deciduous nodes
 
 # Filter by current branch (useful for feature work)
@@ -1064,13 +1079,16 @@ When starting a session or recovering context, query the decision graph:
 
 <session_start>
 ```bash
-# 1. See what decisions exist (look for recent/pending)
+# 1. Summary of open goals, pending decisions and recent chains
+deciduous recover
+
+# 2. All decisions, if you need more detail
 deciduous nodes
 
-# 2. See how they connect
+# 3. See how they connect
 deciduous edges
 
-# 3. Check git state
+# 4. Check git state
 git status
 git log --oneline -10
 ```
@@ -1458,7 +1476,11 @@ description: "Recover context from decision graph - USE THIS ON SESSION START. U
 ## Step 1: Query the Graph
 
 ```bash
-# See all decisions (look for recent ones and pending status)
+# Start here: open goals, pending decisions, recent outcomes and chains
+deciduous recover
+deciduous recover --branch   # Only the current branch
+
+# Drill down: all decisions (look for recent ones and pending status)
 deciduous nodes
 
 # Filter by current branch (useful for feature work)
@@ -1872,7 +1894,11 @@ argument-hint: [FOCUS="<area>"]
 ## Step 1: Query the Graph
 
 ```bash
-# See all decisions (look for recent ones and pending status)
+# Start here: open goals, pending decisions, recent outcomes and chains
+deciduous recover
+deciduous recover --branch   # Only the current branch
+
+# Drill down: all decisions (look for recent ones and pending status)
 deciduous nodes
 
 # Filter by current branch (useful for feature work)
@@ -2177,7 +2203,7 @@ pub fn init_project(editor: Editor) -> Result<(), String> {
     }
 
     // Set the env var so Database::open() uses this path
-    // Database::open() uses CREATE TABLE IF NOT EXISTS - safe for existing DBs
+    // Database::open() only applies pending migrations - safe for existing DBs
     std::env::set_var("DECIDUOUS_DB_PATH", &db_path);
 
     // 3. Create editor-specific configuration
@@ -3004,6 +3030,7 @@ mod tests {
     #[test]
     fn test_opencode_recover_cmd_contains_recovery() {
         assert!(OPENCODE_RECOVER_CMD.contains("Context Recovery"));
+        assert!(OPENCODE_RECOVER_CMD.contains("deciduous recover"));
         assert!(OPENCODE_RECOVER_CMD.contains("deciduous nodes"));
         assert!(OPENCODE_RECOVER_CMD.contains("deciduous edges"));
         assert!(OPENCODE_RECOVER_CMD.contains("$ARGUMENTS"));
@@ -3273,6 +3300,7 @@ This should be preserved."#;
     #[test]
     fn test_codex_recover_prompt_contains_recovery() {
         assert!(CODEX_RECOVER_PROMPT.contains("Context Recovery"));
+        assert!(CODEX_RECOVER_PROMPT.contains("deciduous recover"));
         assert!(CODEX_RECOVER_PROMPT.contains("deciduous nodes"));
         assert!(CODEX_RECOVER_PROMPT.contains("deciduous edges"));
     }
@@ -0,0 +1,401 @@
+//! JSON Schemas for the files deciduous reads and writes
+//!
+//! The schemas are generated from the Rust types, so they can't drift from what
+//! serde accepts. `bin/gen_schemas.rs` writes them to `schemas/` for other tools;
+//! `diff validate` checks patches against them with the small validator below,
+//! which covers the subset of JSON Schema that schemars emits.
+
+use crate::db::DecisionGraph;
+use crate::diff::GraphPatch;
+use crate::export::GitCommit;
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use serde_json::Value;
+use std::fmt;
+
+/// Schema for patch files (`diff export`, `.deciduous/patches/*.json`)
+pub fn patch_schema() -> RootSchema {
+    schema_for!(GraphPatch)
+}
+
+/// Schema for graph-data.json (`deciduous sync`, `/api/graph`)
+pub fn graph_data_schema() -> RootSchema {
+    schema_for!(DecisionGraph)
+}
+
+/// Schema for git-history.json (commits linked from nodes)
+pub fn git_history_schema() -> RootSchema {
+    schema_for!(Vec<GitCommit>)
+}
+
+/// Builds one of the published schemas
+pub type SchemaGenerator = fn() -> RootSchema;
+
+/// File names and generators of the schemas published in `schemas/`
+pub const PUBLISHED: &[(&str, SchemaGenerator)] = &[
+    ("graph-patch.schema.json", patch_schema),
+    ("graph-data.schema.json", graph_data_schema),
+    ("git-history.schema.json", git_history_schema),
+];
+
+/// Pretty-printed schema as written to `schemas/`
+pub fn to_json(schema: &RootSchema) -> String {
+    let mut json = serde_json::to_string_pretty(schema).unwrap_or_default();
+    json.push('\n');
+    json
+}
+
+/// A value that doesn't match its schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value ("" is the document itself)
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "(root)"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{}: {}", pointer, self.message)
+    }
+}
+
+/// Check a JSON document against a schema, returning every violation found
+pub fn validate(schema: &RootSchema, value: &Value) -> Vec<SchemaViolation> {
+    let root = serde_json::to_value(schema).unwrap_or(Value::Null);
+    let mut violations = Vec::new();
+    check(&root, &root, value, "", &mut violations);
+    violations
+}
+
+fn check(
+    root: &Value,
+    schema: &Value,
+    value: &Value,
+    pointer: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => return violation(out, pointer, "value is not allowed here".into()),
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve(root, reference) {
+            Some(target) => check(root, target, value, pointer, out),
+            None => violation(
+                out,
+                pointer,
+                format!("unresolvable schema reference {}", reference),
+            ),
+        }
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            // Nothing below applies to a value of the wrong type
+            return violation(
+                out,
+                pointer,
+                format!(
+                    "expected {}, found {}",
+                    types.join(" or "),
+                    type_name(value)
+                ),
+            );
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            violation(
+                out,
+                pointer,
+                format!("must be one of {}", allowed.join(", ")),
+            );
+        }
+    }
+
+    if let (Some(minimum), Some(n)) = (
+        schema.get("minimum").and_then(Value::as_f64),
+        value.as_f64(),
+    ) {
+        if n < minimum {
+            violation(out, pointer, format!("must be at least {}", minimum));
+        }
+    }
+
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for sub in all {
+            check(root, sub, value, pointer, out);
+        }
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(branches) = schema.get(key).and_then(Value::as_array) {
+            check_alternatives(root, branches, value, pointer, out);
+        }
+    }
+
+    if let Value::Object(object) = value {
+        check_object(root, schema, object, pointer, out);
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            check(root, item_schema, item, &format!("{}/{}", pointer, i), out);
+        }
+    }
+}
+
+fn check_object(
+    root: &Value,
+    schema: &serde_json::Map<String, Value>,
+    object: &serde_json::Map<String, Value>,
+    pointer: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    for name in schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !object.contains_key(name) {
+            violation(
+                out,
+                pointer,
+                format!("missing required property '{}'", name),
+            );
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, field) in object {
+        let field_pointer = format!("{}/{}", pointer, escape_pointer(name));
+        match properties.and_then(|p| p.get(name)) {
+            Some(field_schema) => check(root, field_schema, field, &field_pointer, out),
+            None => {
+                if let Some(extra) = schema.get("additionalProperties") {
+                    if extra == &Value::Bool(false) {
+                        violation(out, &field_pointer, "unexpected property".into());
+                    } else {
+                        check(root, extra, field, &field_pointer, out);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A value matching any branch passes. Otherwise the branch that got furthest
+/// explains the failure: one whose type matches the value, then the one with the
+/// fewest errors.
+fn check_alternatives(
+    root: &Value,
+    branches: &[Value],
+    value: &Value,
+    pointer: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    let mut best: Option<((bool, usize), Vec<SchemaViolation>)> = None;
+    for branch in branches {
+        let mut errors = Vec::new();
+        check(root, branch, value, pointer, &mut errors);
+        if errors.is_empty() {
+            return;
+        }
+        let rank = (!accepts_type(root, branch, value), errors.len());
+        if best
+            .as_ref()
+            .map_or(true, |(best_rank, _)| rank < *best_rank)
+        {
+            best = Some((rank, errors));
+        }
+    }
+    out.extend(best.map(|(_, errors)| errors).unwrap_or_default());
+}
+
+/// Whether a schema's top-level `type` (following `$ref`) allows the value
+fn accepts_type(root: &Value, schema: &Value, value: &Value) -> bool {
+    let Some(schema) = schema.as_object() else {
+        return true;
+    };
+    if let Some(target) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| resolve(root, r))
+    {
+        return accepts_type(root, target, value);
+    }
+    match schema.get("type") {
+        Some(Value::String(t)) => has_type(value, t),
+        Some(Value::Array(ts)) => ts
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|t| has_type(value, t)),
+        _ => true,
+    }
+}
+
+/// Look up a local reference such as `#/definitions/PatchNode`
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Escape a key for use as a JSON pointer segment (RFC 6901)
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn violation(out: &mut Vec<SchemaViolation>, pointer: &str, message: String) {
+    out.push(SchemaViolation {
+        pointer: pointer.to_string(),
+        message,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::Path;
+
+    fn patch_json() -> Value {
+        json!({
+            "version": "1.1",
+            "author": "alice",
+            "branch": null,
+            "created_at": "2025-12-10T12:00:00Z",
+            "base_commit": null,
+            "nodes": [{
+                "change_id": "cid-1",
+                "node_type": "goal",
+                "title": "Goal",
+                "description": null,
+                "status": "pending",
+                "created_at": "2025-12-10T12:00:00Z",
+                "metadata_json": null
+            }],
+            "edges": []
+        })
+    }
+
+    #[test]
+    fn test_valid_patch_has_no_violations() {
+        assert_eq!(validate(&patch_schema(), &patch_json()), vec![]);
+
+        let patch = GraphPatch::new(Some("bob".to_string()), None, None);
+        let value = serde_json::to_value(&patch).unwrap();
+        assert_eq!(validate(&patch_schema(), &value), vec![]);
+    }
+
+    #[test]
+    fn test_violations_point_at_the_bad_value() {
+        let mut value = patch_json();
+        value["nodes"][0]["title"] = json!(42);
+        value["nodes"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("node_type");
+        value["edges"] = json!("none");
+        value["signature"] = json!({"public_key": "abc"});
+
+        let violations = validate(&patch_schema(), &value);
+        let found: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            found,
+            vec![
+                "/edges: expected array, found string",
+                "/nodes/0: missing required property 'node_type'",
+                "/nodes/0/title: expected string, found integer",
+                "/signature: missing required property 'signature'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_root_violation_and_pointer_escaping() {
+        let violations = validate(&patch_schema(), &json!([]));
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            "(root): expected object, found array"
+        );
+        assert_eq!(escape_pointer("a/b~c"), "a~1b~0c");
+    }
+
+    #[test]
+    fn test_git_history_schema() {
+        let commits = json!([
+            {"hash": "abc", "short_hash": "abc", "author": "a", "date": "d", "message": "m", "files_changed": 3},
+            {"hash": "def", "short_hash": "def", "author": "a", "date": "d", "message": "m", "files_changed": -1}
+        ]);
+        let violations = validate(&git_history_schema(), &commits);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].pointer, "/1/files_changed");
+    }
+
+    #[test]
+    fn test_docs_data_matches_schemas() {
+        let docs = Path::new(env!("CARGO_MANIFEST_DIR")).join("docs");
+        for (file, schema) in [
+            ("graph-data.json", graph_data_schema()),
+            ("git-history.json", git_history_schema()),
+        ] {
+            let content = std::fs::read_to_string(docs.join(file)).unwrap();
+            let value: Value = serde_json::from_str(&content).unwrap();
+            assert_eq!(validate(&schema, &value), vec![], "docs/{}", file);
+        }
+    }
+
+    #[test]
+    fn test_published_schemas_are_up_to_date() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+        for (file, generate) in PUBLISHED {
+            let published = std::fs::read_to_string(dir.join(file)).unwrap_or_default();
+            assert!(
+                published == to_json(&generate()),
+                "schemas/{} is stale; run `cargo run --features schema-gen --bin gen_schemas`",
+                file
+            );
+        }
+    }
+}
@@ -43,24 +43,45 @@ pub mod db;
 pub mod diff;
 pub mod export;
 pub mod github;
+pub mod gitsync;
 pub mod init;
+pub mod json_schema;
+pub mod mcp;
+pub mod migrations;
+pub mod query;
+pub mod recover;
 pub mod roadmap;
 pub mod schema;
 pub mod serve;
+pub mod signing;
+pub mod traverse;
 pub mod tui;
 
-pub use config::Config;
+#[cfg(test)]
+mod test_fixtures;
+
+pub use config::{Config, SyncConfig, TypesConfig};
 pub use db::{
-    build_metadata_json, get_current_git_branch, get_current_git_commit, CheckboxState, CommandLog,
+    build_context_json, build_metadata_json, get_current_git_branch, get_current_git_commit,
+    truncate_command_output, AppliedPatch, ChangeIdAlias, CheckboxState, CommandLog, ContextType,
     Database, DbRecord, DbSummary, DecisionContext, DecisionEdge, DecisionGraph, DecisionNode,
-    DecisionSession, GitHubIssueCache, RoadmapConflict, RoadmapItem, RoadmapSyncState,
+    DecisionSession, DeleteResult, EdgeType, GitHubIssueCache, LabelEdit, MergeResult,
+    NodeConflict, NodeMetadata, NodeStatus, NodeType, OutputTail, RevertResult, RoadmapConflict,
+    RoadmapItem, RoadmapSyncState, SearchFilters, SearchHit, Tombstone, COMMAND_OUTPUT_LIMIT,
     CURRENT_SCHEMA,
 };
-pub use diff::{ApplyResult, GraphPatch, PatchEdge, PatchNode};
+pub use diff::{
+    order_patches, squash_patches, upgrade_patch_json, ApplyResult, ConflictResolution, GraphPatch,
+    NodeContent, PatchEdge, PatchNode, PatchTombstone, PATCH_VERSION,
+};
 pub use export::{
-    filter_graph_by_ids, filter_graph_from_roots, generate_pr_writeup, graph_to_dot,
-    parse_node_range, DotConfig, WriteupConfig,
+    filter_graph_by_ids, filter_graph_by_tags, filter_graph_from_roots, format_node_range,
+    generate_pr_writeup, graph_to_dot, parse_node_range, DotConfig, GitCommit, WriteupConfig,
 };
+pub use gitsync::{PullResult, PushResult};
+pub use query::{Query, QueryError};
+pub use signing::{KeyStore, PatchIntegrity, PatchSignature};
+pub use traverse::{build_tree, find_paths, PathStep, TreeNode};
 
 // Re-export TS trait for downstream use
 #[cfg(feature = "ts-rs")]
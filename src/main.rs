@@ -1,4 +1,5 @@
 use chrono::Local;
+use clap::builder::{PossibleValue, PossibleValuesParser};
 use clap::{CommandFactory, Parser, Subcommand};
 use colored::Colorize;
 use deciduous::github::{ensure_roadmap_label, GitHubClient};
@@ -6,8 +7,9 @@ use deciduous::roadmap::{
     generate_issue_body, parse_roadmap, write_roadmap_with_metadata, RoadmapSection,
 };
 use deciduous::{
-    filter_graph_by_ids, generate_pr_writeup, graph_to_dot, parse_node_range, Config, Database,
-    DotConfig, WriteupConfig,
+    filter_graph_by_ids, filter_graph_by_tags, generate_pr_writeup, graph_to_dot, parse_node_range,
+    Config, Database, DotConfig, EdgeType, GitCommit, LabelEdit, NodeStatus, NodeType,
+    WriteupConfig,
 };
 use std::path::PathBuf;
 use std::process::Command as ProcessCommand;
@@ -67,6 +69,7 @@ enum Command {
     /// Add a new node to the decision graph
     Add {
         /// Node type: goal, decision, option, action, outcome, observation
+        #[arg(value_parser = node_type_values())]
         node_type: String,
 
         /// Title of the node
@@ -103,6 +106,14 @@ enum Command {
         /// Skip auto-detection of git branch
         #[arg(long)]
         no_branch: bool,
+
+        /// Tags to attach (repeatable or comma-separated)
+        #[arg(long = "tag", value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Attributes to attach as key=value (repeatable)
+        #[arg(long = "attr")]
+        attrs: Vec<String>,
     },
 
     /// Add an edge between nodes
@@ -118,7 +129,7 @@ enum Command {
         rationale: Option<String>,
 
         /// Edge type: leads_to, requires, chosen, rejected, blocks, enables
-        #[arg(short = 't', long, default_value = "leads_to")]
+        #[arg(short = 't', long, default_value = "leads_to", value_parser = edge_type_values())]
         edge_type: String,
     },
 
@@ -128,6 +139,7 @@ enum Command {
         id: i32,
 
         /// New status: pending, active, completed, rejected
+        #[arg(value_parser = status_values())]
         status: String,
     },
 
@@ -140,6 +152,79 @@ enum Command {
         prompt: Option<String>,
     },
 
+    /// Edit an existing node (opens $EDITOR with the node as TOML if no flags are given)
+    Edit {
+        /// Node ID to edit
+        id: i32,
+
+        /// New title
+        #[arg(long)]
+        title: Option<String>,
+
+        /// New description (empty string clears it)
+        #[arg(short, long)]
+        description: Option<String>,
+
+        /// New node type: goal, decision, option, action, outcome, observation
+        #[arg(short = 't', long = "type", value_parser = node_type_values())]
+        node_type: Option<String>,
+
+        /// New confidence level (0-100)
+        #[arg(short, long)]
+        confidence: Option<u8>,
+
+        /// Files associated with this node (comma-separated, empty string clears)
+        #[arg(short, long)]
+        files: Option<String>,
+
+        /// Git branch (empty string clears)
+        #[arg(short, long)]
+        branch: Option<String>,
+    },
+
+    /// Delete a node and its edges (recorded as tombstones so sync won't resurrect it)
+    Delete {
+        /// Node ID to delete
+        id: i32,
+
+        /// Also delete descendants that have no other parents
+        #[arg(long)]
+        cascade: bool,
+    },
+
+    /// Remove edges between two nodes
+    Unlink {
+        /// Source node ID
+        from: i32,
+
+        /// Target node ID
+        to: i32,
+
+        /// Only remove edges of this type (default: all edges between the nodes)
+        #[arg(short = 't', long, value_parser = edge_type_values())]
+        edge_type: Option<String>,
+    },
+
+    /// Add or remove tags and attributes: +tag, -tag, key=value, key=
+    Tag {
+        /// Node ID
+        id: i32,
+
+        /// Changes to apply (omit to show current tags and attributes)
+        #[arg(allow_hyphen_values = true)]
+        changes: Vec<String>,
+    },
+
+    /// Merge duplicate nodes into one, moving their edges onto the kept node
+    Merge {
+        /// Node ID to keep
+        keep: i32,
+
+        /// Duplicate node IDs to merge into it
+        #[arg(required = true)]
+        duplicates: Vec<i32>,
+    },
+
     /// List all nodes
     Nodes {
         /// Filter by git branch
@@ -147,8 +232,111 @@ enum Command {
         branch: Option<String>,
 
         /// Filter by node type (goal, decision, action, etc.)
-        #[arg(short = 't', long)]
+        #[arg(short = 't', long, value_parser = node_type_values())]
+        node_type: Option<String>,
+
+        /// Only nodes carrying this tag (repeatable; all must match)
+        #[arg(long = "tag", value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Only nodes whose author name or email contains this text
+        #[arg(short, long)]
+        author: Option<String>,
+    },
+
+    /// Full-text search over node titles, descriptions, prompts and files
+    Search {
+        /// Words to search for (all must match; the last may be a prefix)
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+
+        /// Filter by node type
+        #[arg(short = 't', long, value_parser = node_type_values())]
         node_type: Option<String>,
+
+        /// Filter by status
+        #[arg(short, long, value_parser = status_values())]
+        status: Option<String>,
+
+        /// Filter by git branch
+        #[arg(short, long)]
+        branch: Option<String>,
+
+        /// Only nodes carrying this tag (repeatable; all must match)
+        #[arg(long = "tag", value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Maximum number of results
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
+        /// Output results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Query nodes, e.g. `deciduous query type:decision status:pending confidence:<50`
+    ///
+    /// Keys: type, status, branch (globs), tag, attr, title, id, confidence,
+    /// created, updated, has, descendants-of, ancestors-of. Prefix a term with
+    /// '-' to negate it (quote the query or put it after `--`); bare words match
+    /// the title or description.
+    Query {
+        /// Query terms (all must match)
+        terms: Vec<String>,
+
+        /// Output matching nodes as JSON
+        #[arg(long, conflicts_with = "ids")]
+        json: bool,
+
+        /// Output a compact ID list (e.g. 1-4,9) for --nodes options
+        #[arg(long)]
+        ids: bool,
+    },
+
+    /// Print the descendants of a node as an indented tree
+    Tree {
+        /// Node ID to start from
+        id: i32,
+
+        /// Stop after this many levels
+        #[arg(short, long)]
+        depth: Option<usize>,
+
+        /// Output the tree as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Walk up from a node to the root goals it serves
+    Ancestors {
+        /// Node ID to start from
+        id: i32,
+
+        /// Stop after this many levels
+        #[arg(short, long)]
+        depth: Option<usize>,
+
+        /// Output the tree as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Explain how one node leads to another, with edge types and rationales
+    Path {
+        /// Starting node ID (e.g. a goal)
+        from: i32,
+
+        /// Target node ID (e.g. an action)
+        to: i32,
+
+        /// Show every path (up to 100) instead of only the shortest
+        #[arg(long)]
+        all: bool,
+
+        /// Output paths as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// List all edges
@@ -164,6 +352,9 @@ enum Command {
         port: u16,
     },
 
+    /// Serve the graph to AI assistants over stdio (Model Context Protocol)
+    Mcp,
+
     /// Export graph to JSON file
     Sync {
         /// Output path (default: .deciduous/web/graph-data.json)
@@ -178,6 +369,25 @@ enum Command {
         output: Option<PathBuf>,
     },
 
+    /// Print a token-budgeted summary of recent work for recovering context
+    Recover {
+        /// Approximate token budget for the output
+        #[arg(long, default_value_t = deciduous::recover::DEFAULT_BUDGET)]
+        budget: usize,
+
+        /// Only nodes on a branch (the current one when no name is given)
+        #[arg(long, value_name = "NAME")]
+        branch: Option<Option<String>>,
+
+        /// Only nodes updated after a git ref's commit or a time (RFC 3339 or YYYY-MM-DD)
+        #[arg(long, value_name = "REF|TIME")]
+        since: Option<String>,
+
+        /// Output compact JSON instead of markdown
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Show recent command log
     Commands {
         /// Number of commands to show
@@ -185,6 +395,25 @@ enum Command {
         limit: i64,
     },
 
+    /// Run a command, recording its exit code, duration and output in the command log
+    Run {
+        /// Node the command was run for (shown in its detail panel)
+        #[arg(short, long)]
+        node: Option<i32>,
+
+        /// What the command is for
+        #[arg(short, long)]
+        description: Option<String>,
+
+        /// If the command fails, log an outcome node (linked from --node)
+        #[arg(long)]
+        outcome_on_failure: bool,
+
+        /// The command to run, after `--`
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+
     /// Export graph as DOT format
     Dot {
         /// Output file (default: stdout). Use --auto for branch-specific naming.
@@ -214,6 +443,10 @@ enum Command {
         /// Graph direction: TB (top-bottom) or LR (left-right)
         #[arg(long, default_value = "TB")]
         rankdir: String,
+
+        /// Only nodes carrying this tag (repeatable; all must match)
+        #[arg(long = "tag", value_delimiter = ',')]
+        tags: Vec<String>,
     },
 
     /// Generate PR writeup from decision graph
@@ -249,6 +482,10 @@ enum Command {
         /// Skip test plan section
         #[arg(long)]
         no_test_plan: bool,
+
+        /// Only nodes carrying this tag (repeatable; all must match)
+        #[arg(long = "tag", value_delimiter = ',')]
+        tags: Vec<String>,
     },
 
     /// Export or apply graph diff patches for multi-user sync
@@ -257,8 +494,46 @@ enum Command {
         action: DiffAction,
     },
 
-    /// Migrate database to add change_id columns (for multi-user sync)
-    Migrate,
+    /// Commit the graph to refs/deciduous/graph and push it (no patch files)
+    Push {
+        /// Git remote to push to
+        #[arg(default_value = "origin")]
+        remote: String,
+
+        /// Author name recorded in the graph commit (default: your node author)
+        #[arg(short, long)]
+        author: Option<String>,
+
+        /// Sign the graph with .deciduous/keys/signing.key
+        #[arg(long)]
+        sign: bool,
+    },
+
+    /// Fetch refs/deciduous/graph from a remote and merge it into the local graph
+    Pull {
+        /// Git remote to pull from
+        #[arg(default_value = "origin")]
+        remote: String,
+
+        /// Reject a graph not signed by a trusted key (also `[sync] require_signed`)
+        #[arg(long)]
+        require_signed: bool,
+    },
+
+    /// Apply, inspect or roll back database schema migrations
+    Migrate {
+        /// Show applied and pending migrations
+        #[arg(long, conflicts_with_all = ["to", "down"])]
+        status: bool,
+
+        /// Migrate up or down to this migration number
+        #[arg(long, conflicts_with = "down")]
+        to: Option<u32>,
+
+        /// Roll back the most recent migration
+        #[arg(long)]
+        down: bool,
+    },
 
     /// Audit and maintain graph data quality
     Audit {
@@ -292,6 +567,18 @@ enum Command {
         action: RoadmapAction,
     },
 
+    /// Attach captured context (error logs, benchmarks, file snapshots) to nodes
+    Context {
+        #[command(subcommand)]
+        action: ContextAction,
+    },
+
+    /// Group the nodes created during a working session
+    Session {
+        #[command(subcommand)]
+        action: SessionAction,
+    },
+
     /// Generate shell completions
     Completion {
         /// Shell type: bash, zsh, fish, powershell, elvish
@@ -315,13 +602,29 @@ enum DiffAction {
         #[arg(short, long)]
         branch: Option<String>,
 
-        /// Author name to include in patch
+        /// Only nodes carrying this tag (repeatable; all must match)
+        #[arg(long = "tag", value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Author name to include in patch (default: your node author)
         #[arg(short, long)]
         author: Option<String>,
 
         /// Git commit hash at time of export
         #[arg(long)]
         base_commit: Option<String>,
+
+        /// Only what changed since the previous `diff export`
+        #[arg(long, conflicts_with = "since")]
+        since_last: bool,
+
+        /// Only what changed after a git ref's commit or a time (RFC 3339 or YYYY-MM-DD)
+        #[arg(long, value_name = "REF|TIME")]
+        since: Option<String>,
+
+        /// Sign the patch with .deciduous/keys/signing.key
+        #[arg(long)]
+        sign: bool,
     },
 
     /// Apply a patch file to local database
@@ -332,19 +635,89 @@ enum DiffAction {
         /// Show what would be applied without making changes
         #[arg(long)]
         dry_run: bool,
+
+        /// Reject patches not signed by a trusted key (also `[sync] require_signed`)
+        #[arg(long)]
+        require_signed: bool,
+
+        /// Apply every patch in the patches directory not yet applied, in dependency order
+        #[arg(long, conflicts_with = "files")]
+        all: bool,
+
+        /// Directory scanned by --all (default: .deciduous/patches/)
+        #[arg(short, long, requires = "all")]
+        path: Option<PathBuf>,
     },
 
-    /// Show status of unapplied patches
+    /// Show which patches are applied, partially applied or unapplied
     Status {
         /// Directory to scan for patches (default: .deciduous/patches/)
         #[arg(short, long)]
         path: Option<PathBuf>,
     },
 
-    /// Validate a patch file (check for missing node references)
+    /// Validate a patch file (integrity, signature and missing node references)
     Validate {
         /// Patch file(s) to validate
         files: Vec<PathBuf>,
+
+        /// Fail on patches not signed by a trusted key (also `[sync] require_signed`)
+        #[arg(long)]
+        require_signed: bool,
+    },
+
+    /// Fold several patch files into one deduplicated patch
+    Squash {
+        /// Patch files to fold together
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Output file path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Sign the result with .deciduous/keys/signing.key
+        #[arg(long)]
+        sign: bool,
+    },
+
+    /// Write the whole graph as a stable, sorted patch that diffs cleanly in review
+    Snapshot {
+        /// Output file path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Author name to include in the snapshot (default: your node author)
+        #[arg(short, long)]
+        author: Option<String>,
+
+        /// Sign the snapshot with .deciduous/keys/signing.key
+        #[arg(long)]
+        sign: bool,
+    },
+
+    /// Remove the nodes and edges an applied patch added
+    Revert {
+        /// Patch file, or the digest (prefix) of an applied patch
+        patch: String,
+    },
+
+    /// Create a signing key in .deciduous/keys for `diff export --sign`
+    Keygen {
+        /// Replace an existing signing key
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// List nodes edited both here and in an applied patch, or settle them
+    Conflicts {
+        /// Keep our version, take the patch's, or hand-merge in $EDITOR
+        #[arg(long, value_parser = ["ours", "theirs", "edit"])]
+        resolve: Option<String>,
+
+        /// Only this conflict (default: all unresolved)
+        #[arg(long)]
+        id: Option<i32>,
     },
 }
 
@@ -447,6 +820,54 @@ enum RoadmapAction {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum ContextAction {
+    /// Attach context to a node, read from a file or stdin
+    Add {
+        /// Node ID
+        node: i32,
+
+        /// Kind of context
+        #[arg(short = 't', long = "type", value_enum)]
+        context_type: deciduous::ContextType,
+
+        /// Read the content from this file instead of stdin
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+    },
+
+    /// Show the context attached to a node
+    List {
+        /// Node ID
+        node: i32,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SessionAction {
+    /// Start a session; nodes created until it ends are attached to it
+    Start {
+        /// Session name (e.g. the task being worked on)
+        name: Option<String>,
+    },
+
+    /// End the open session
+    End {
+        /// What the session accomplished
+        #[arg(short, long)]
+        summary: Option<String>,
+    },
+
+    /// List sessions, most recent first
+    List,
+
+    /// Show a session and its nodes (default: the open session)
+    Show {
+        /// Session ID
+        id: Option<i32>,
+    },
+}
+
 fn main() {
     let args = Args::parse();
 
@@ -522,6 +943,12 @@ fn main() {
         return;
     }
 
+    // Handle migrate separately - opening normally would auto-migrate to latest
+    if let Command::Migrate { status, to, down } = args.command {
+        run_migrate(status, to, down);
+        return;
+    }
+
     let db = match Database::open() {
         Ok(db) => db,
         Err(e) => {
@@ -544,7 +971,23 @@ fn main() {
             files,
             branch,
             no_branch,
+            tags,
+            attrs,
         } => {
+            // Validate labels before creating anything
+            let label_edits: Result<Vec<LabelEdit>, _> = tags
+                .iter()
+                .map(|t| LabelEdit::add_tag(t))
+                .chain(attrs.iter().map(|a| LabelEdit::set_attribute(a)))
+                .collect();
+            let label_edits = match label_edits {
+                Ok(edits) => edits,
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            };
+
             // Handle prompt from stdin if requested
             let effective_prompt = if prompt_stdin {
                 use std::io::{self, Read};
@@ -601,6 +1044,12 @@ fn main() {
                 effective_branch.as_deref(),
             ) {
                 Ok(id) => {
+                    if !label_edits.is_empty() {
+                        if let Err(e) = db.edit_labels(id, &label_edits) {
+                            eprintln!("{} {}", "Error:".red(), e);
+                            std::process::exit(1);
+                        }
+                    }
                     let conf_str = confidence
                         .map(|c| format!(" [confidence: {}%]", c))
                         .unwrap_or_default();
@@ -620,8 +1069,13 @@ fn main() {
                         .as_ref()
                         .map(|b| format!(" [branch: {}]", b))
                         .unwrap_or_default();
+                    let tags_str = if tags.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" [tags: {}]", tags.join(", "))
+                    };
                     println!(
-                        "{} node {} (type: {}, title: {}){}{}{}{}{}",
+                        "{} node {} (type: {}, title: {}){}{}{}{}{}{}",
                         "Created".green(),
                         id,
                         node_type,
@@ -630,7 +1084,8 @@ fn main() {
                         commit_str,
                         prompt_str,
                         files_str,
-                        branch_str
+                        branch_str,
+                        tags_str
                     );
                 }
                 Err(e) => {
@@ -714,71 +1169,496 @@ fn main() {
             }
         }
 
-        Command::Nodes { branch, node_type } => {
-            match db.get_all_nodes() {
-                Ok(nodes) => {
-                    // Filter nodes by branch and/or type
-                    let filtered: Vec<_> = nodes
-                        .into_iter()
-                        .filter(|n| {
-                            // Filter by branch if specified
-                            let branch_match = match &branch {
-                                Some(b) => n.metadata_json.as_ref().is_some_and(|meta| {
-                                    serde_json::from_str::<serde_json::Value>(meta)
-                                        .ok()
-                                        .and_then(|v| {
-                                            v.get("branch")
-                                                .and_then(|br| br.as_str())
-                                                .map(|s| s.to_string())
-                                        })
-                                        .is_some_and(|node_branch| node_branch == *b)
-                                }),
-                                None => true,
-                            };
-                            // Filter by type if specified
-                            let type_match = match &node_type {
-                                Some(t) => n.node_type == *t,
-                                None => true,
-                            };
-                            branch_match && type_match
-                        })
+        Command::Delete { id, cascade } => match db.delete_node(id, cascade) {
+            Ok(result) => {
+                println!(
+                    "{} {} node(s), {} edge(s)",
+                    "Deleted".green(),
+                    result.nodes_deleted.len(),
+                    result.edges_deleted
+                );
+                if result.nodes_deleted.len() > 1 {
+                    let cascaded: Vec<String> = result
+                        .nodes_deleted
+                        .iter()
+                        .filter(|n| **n != id)
+                        .map(|n| n.to_string())
                         .collect();
-
-                    if filtered.is_empty() {
-                        if branch.is_some() || node_type.is_some() {
-                            println!("No nodes found matching filters.");
-                        } else {
-                            println!(
-                                "No nodes found. Add one with: deciduous add goal \"My goal\""
-                            );
-                        }
-                    } else {
-                        let header = match &branch {
-                            Some(b) => {
-                                format!("Nodes on branch '{}' ({} total):", b, filtered.len())
-                            }
-                            None => format!("{} nodes:", filtered.len()),
-                        };
-                        println!("{}", header.cyan());
-                        println!("{:<5} {:<12} {:<10} TITLE", "ID", "TYPE", "STATUS");
-                        println!("{}", "-".repeat(70));
-                        for n in filtered {
-                            let type_colored = match n.node_type.as_str() {
-                                "goal" => n.node_type.yellow(),
-                                "decision" => n.node_type.cyan(),
-                                "action" => n.node_type.green(),
-                                "outcome" => n.node_type.blue(),
-                                "observation" => n.node_type.magenta(),
-                                _ => n.node_type.white(),
-                            };
-                            println!(
-                                "{:<5} {:<12} {:<10} {}",
-                                n.id, type_colored, n.status, n.title
-                            );
-                        }
-                    }
+                    println!("  Cascaded: {}", cascaded.join(", "));
                 }
-                Err(e) => {
+            }
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red(), e);
+                std::process::exit(1);
+            }
+        },
+
+        Command::Unlink {
+            from,
+            to,
+            edge_type,
+        } => match db.delete_edges(from, to, edge_type.as_deref()) {
+            Ok(0) => {
+                eprintln!(
+                    "{} No edge from {} to {}{}",
+                    "Error:".red(),
+                    from,
+                    to,
+                    edge_type
+                        .map(|t| format!(" of type '{}'", t))
+                        .unwrap_or_default()
+                );
+                std::process::exit(1);
+            }
+            Ok(n) => {
+                println!("{} {} edge(s) {} -> {}", "Removed".green(), n, from, to);
+            }
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red(), e);
+                std::process::exit(1);
+            }
+        },
+
+        Command::Tag { id, changes } => {
+            let edits: Result<Vec<LabelEdit>, _> = changes.iter().map(|c| c.parse()).collect();
+            let result = edits.and_then(|edits| {
+                if edits.is_empty() {
+                    db.get_node(id)?.map(|n| n.metadata()).ok_or_else(|| {
+                        deciduous::db::DbError::Validation(format!(
+                            "Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                            id
+                        ))
+                    })
+                } else {
+                    db.edit_labels(id, &edits)
+                }
+            });
+            match result {
+                Ok(meta) => {
+                    if !changes.is_empty() {
+                        println!("{} node {}", "Updated".green(), id);
+                    }
+                    let tags = if meta.tags.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        meta.tags.join(", ")
+                    };
+                    println!("  Tags: {}", tags);
+                    for (key, value) in &meta.attributes {
+                        println!("  {}={}", key, value);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Command::Merge { keep, duplicates } => match db.merge_nodes(keep, &duplicates) {
+            Ok(result) => {
+                println!(
+                    "{} {} node(s) into {}",
+                    "Merged".green(),
+                    result.aliases.len(),
+                    keep
+                );
+                println!(
+                    "  Edges: {} moved, {} dropped (self-loops/duplicates)",
+                    result.edges_moved, result.edges_dropped
+                );
+            }
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red(), e);
+                std::process::exit(1);
+            }
+        },
+
+        Command::Edit {
+            id,
+            title,
+            description,
+            node_type,
+            confidence,
+            files,
+            branch,
+        } => {
+            let node = match db.get_node(id) {
+                Ok(Some(n)) => n,
+                Ok(None) => {
+                    eprintln!(
+                        "{} Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                        "Error:".red(),
+                        id
+                    );
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            };
+
+            let no_flags = title.is_none()
+                && description.is_none()
+                && node_type.is_none()
+                && confidence.is_none()
+                && files.is_none()
+                && branch.is_none();
+
+            // Without flags, round-trip the node through $EDITOR as TOML
+            let changes = if no_flags {
+                match edit_node_in_editor(&node) {
+                    Ok(Some(changes)) => changes,
+                    Ok(None) => {
+                        println!("{} No changes made to node {}", "Info:".cyan(), id);
+                        return;
+                    }
+                    Err(e) => {
+                        eprintln!("{} {}", "Error:".red(), e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                EditableNode {
+                    node_type: node_type.unwrap_or_default(),
+                    title: title.unwrap_or_default(),
+                    description,
+                    confidence,
+                    files: files.map(|f| f.split(',').map(|s| s.trim().to_string()).collect()),
+                    branch,
+                }
+            };
+
+            let files_str = changes.files.as_ref().map(|f| f.join(","));
+            // The editor returns the whole node, so a missing confidence clears it;
+            // without the editor only a given --confidence changes it
+            let confidence = if no_flags {
+                Some(changes.confidence)
+            } else {
+                changes.confidence.map(Some)
+            };
+            match db.update_node(
+                id,
+                Some(changes.title.as_str()).filter(|t| !t.is_empty()),
+                changes.description.as_deref(),
+                Some(changes.node_type.as_str()).filter(|t| !t.is_empty()),
+                confidence,
+                files_str.as_deref(),
+                changes.branch.as_deref(),
+            ) {
+                Ok(()) => println!("{} node {}", "Updated".green(), id),
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Command::Search {
+            query,
+            node_type,
+            status,
+            branch,
+            tags,
+            limit,
+            json,
+        } => {
+            let query = query.join(" ");
+            let filters = deciduous::SearchFilters {
+                node_type,
+                status,
+                branch,
+                tags,
+                limit: Some(limit),
+            };
+            match db.search(&query, &filters) {
+                Ok(hits) if json => match serde_json::to_string_pretty(&hits) {
+                    Ok(out) => println!("{}", out),
+                    Err(e) => {
+                        eprintln!("{} {}", "Error:".red(), e);
+                        std::process::exit(1);
+                    }
+                },
+                Ok(hits) if hits.is_empty() => {
+                    println!("No nodes match '{}'.", query);
+                }
+                Ok(hits) => {
+                    println!(
+                        "{}",
+                        format!("{} matches for '{}':", hits.len(), query).cyan()
+                    );
+                    for hit in hits {
+                        println!(
+                            "{:<5} {:<12} {:<10} {}",
+                            hit.node.id, hit.node.node_type, hit.node.status, hit.node.title
+                        );
+                        // Title matches are already visible on the first line
+                        if !hit.snippet.is_empty()
+                            && hit.snippet.replace(['[', ']'], "") != hit.node.title
+                        {
+                            println!("      {}", highlight_snippet(&hit.snippet));
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Command::Query { terms, json, ids } => {
+            let query = match deciduous::Query::parse(&terms.join(" ")) {
+                Ok(q) => q,
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            };
+            let graph = match db.get_graph() {
+                Ok(g) => g,
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            };
+            let matches = query.evaluate(&graph);
+
+            if ids {
+                let node_ids: Vec<i32> = matches.iter().map(|n| n.id).collect();
+                println!("{}", deciduous::format_node_range(&node_ids));
+            } else if json {
+                match serde_json::to_string_pretty(&matches) {
+                    Ok(out) => println!("{}", out),
+                    Err(e) => {
+                        eprintln!("{} {}", "Error:".red(), e);
+                        std::process::exit(1);
+                    }
+                }
+            } else if matches.is_empty() {
+                println!("No nodes found matching query.");
+            } else {
+                println!("{}", format!("{} nodes:", matches.len()).cyan());
+                println!("{:<5} {:<12} {:<10} TITLE", "ID", "TYPE", "STATUS");
+                println!("{}", "-".repeat(70));
+                for n in matches {
+                    println!(
+                        "{:<5} {:<12} {:<10} {}",
+                        n.id, n.node_type, n.status, n.title
+                    );
+                }
+            }
+        }
+
+        Command::Tree { id, depth, json } | Command::Ancestors { id, depth, json } => {
+            let direction = if matches!(args.command, Command::Tree { .. }) {
+                deciduous::traverse::Direction::Descendants
+            } else {
+                deciduous::traverse::Direction::Ancestors
+            };
+            let graph = match db.get_graph() {
+                Ok(g) => g,
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            };
+            let Some(tree) = deciduous::build_tree(&graph, id, direction, depth) else {
+                eprintln!(
+                    "{} Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                    "Error:".red(),
+                    id
+                );
+                std::process::exit(1);
+            };
+
+            if json {
+                match serde_json::to_string_pretty(&tree) {
+                    Ok(out) => println!("{}", out),
+                    Err(e) => {
+                        eprintln!("{} {}", "Error:".red(), e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                print!("{}", deciduous::traverse::render_tree(&tree));
+                if direction == deciduous::traverse::Direction::Ancestors {
+                    let roots: Vec<String> = tree
+                        .leaves()
+                        .into_iter()
+                        .filter(|n| n.id != id)
+                        .map(deciduous::traverse::node_label)
+                        .collect();
+                    if roots.is_empty() {
+                        println!("{}", "No ancestors: this node is a root.".dimmed());
+                    } else {
+                        println!();
+                        println!("{}", "Roots:".cyan());
+                        for root in roots {
+                            println!("  {}", root);
+                        }
+                    }
+                }
+            }
+        }
+
+        Command::Path {
+            from,
+            to,
+            all,
+            json,
+        } => {
+            let graph = match db.get_graph() {
+                Ok(g) => g,
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            };
+            for id in [from, to] {
+                if !graph.nodes.iter().any(|n| n.id == id) {
+                    eprintln!(
+                        "{} Node {} does not exist. Run 'deciduous nodes' to see existing nodes.",
+                        "Error:".red(),
+                        id
+                    );
+                    std::process::exit(1);
+                }
+            }
+            if from == to {
+                eprintln!(
+                    "{} A path needs two different nodes (got #{} twice).",
+                    "Error:".red(),
+                    from
+                );
+                std::process::exit(1);
+            }
+
+            let paths = deciduous::find_paths(&graph, from, to, all);
+            if json {
+                match serde_json::to_string_pretty(&paths) {
+                    Ok(out) => println!("{}", out),
+                    Err(e) => {
+                        eprintln!("{} {}", "Error:".red(), e);
+                        std::process::exit(1);
+                    }
+                }
+            } else if paths.is_empty() {
+                let hint = if deciduous::find_paths(&graph, to, from, false).is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " (but #{} leads to #{}; try 'deciduous path {} {}')",
+                        to, from, to, from
+                    )
+                };
+                println!("No path from #{} to #{}{}.", from, to, hint);
+            } else {
+                for (i, path) in paths.iter().enumerate() {
+                    let steps = format!(
+                        "{} step{}",
+                        path.len(),
+                        if path.len() == 1 { "" } else { "s" }
+                    );
+                    if all {
+                        if i > 0 {
+                            println!();
+                        }
+                        println!(
+                            "{}",
+                            format!("Path {} of {} ({}):", i + 1, paths.len(), steps).cyan()
+                        );
+                    } else {
+                        println!("{}", format!("Shortest path ({}):", steps).cyan());
+                    }
+                    print_path(path);
+                }
+            }
+        }
+
+        Command::Nodes {
+            branch,
+            node_type,
+            tags,
+            author,
+        } => {
+            match db.get_all_nodes() {
+                Ok(nodes) => {
+                    // Filter nodes by branch and/or type
+                    let filtered: Vec<_> = nodes
+                        .into_iter()
+                        .filter(|n| {
+                            // Filter by branch if specified
+                            let branch_match = match &branch {
+                                Some(b) => n.branch().as_ref() == Some(b),
+                                None => true,
+                            };
+                            // Filter by type if specified
+                            let type_match = match &node_type {
+                                Some(t) => n.node_type == *t,
+                                None => true,
+                            };
+                            let author_match =
+                                author.as_ref().map_or(true, |a| n.is_authored_by(a));
+                            branch_match && type_match && author_match && n.has_all_tags(&tags)
+                        })
+                        .collect();
+
+                    if filtered.is_empty() {
+                        if branch.is_some()
+                            || node_type.is_some()
+                            || !tags.is_empty()
+                            || author.is_some()
+                        {
+                            println!("No nodes found matching filters.");
+                        } else {
+                            println!(
+                                "No nodes found. Add one with: deciduous add goal \"My goal\""
+                            );
+                        }
+                    } else {
+                        let header = match &branch {
+                            Some(b) => {
+                                format!("Nodes on branch '{}' ({} total):", b, filtered.len())
+                            }
+                            None => format!("{} nodes:", filtered.len()),
+                        };
+                        println!("{}", header.cyan());
+                        println!("{:<5} {:<12} {:<10} TITLE", "ID", "TYPE", "STATUS");
+                        println!("{}", "-".repeat(70));
+                        for n in filtered {
+                            let type_colored = match n.node_type.as_str() {
+                                "goal" => n.node_type.yellow(),
+                                "decision" => n.node_type.cyan(),
+                                "action" => n.node_type.green(),
+                                "outcome" => n.node_type.blue(),
+                                "observation" => n.node_type.magenta(),
+                                _ => n.node_type.white(),
+                            };
+                            let node_tags = n.tags();
+                            let tags_str = if node_tags.is_empty() {
+                                String::new()
+                            } else {
+                                format!(" [{}]", node_tags.join(", "))
+                            };
+                            let author_str = n
+                                .author_name()
+                                .map(|a| format!(" (by {})", a))
+                                .unwrap_or_default();
+                            println!(
+                                "{:<5} {:<12} {:<10} {}{}{}",
+                                n.id,
+                                type_colored,
+                                n.status,
+                                n.title,
+                                tags_str.dimmed(),
+                                author_str.dimmed()
+                            );
+                        }
+                    }
+                }
+                Err(e) => {
                     eprintln!("{} {}", "Error:".red(), e);
                     std::process::exit(1);
                 }
@@ -839,6 +1719,15 @@ fn main() {
             }
         }
 
+        Command::Mcp => {
+            let stdin = std::io::stdin();
+            let server = deciduous::mcp::McpServer::new(db);
+            if let Err(e) = server.serve(stdin.lock(), std::io::stdout()) {
+                eprintln!("{} MCP server: {}", "Error:".red(), e);
+                std::process::exit(1);
+            }
+        }
+
         Command::Sync { output } => {
             // Default to docs/ for GitHub Pages compatibility
             let output_path = output.unwrap_or_else(|| PathBuf::from("docs/graph-data.json"));
@@ -985,19 +1874,82 @@ fn main() {
             }
         }
 
+        Command::Recover {
+            budget,
+            branch,
+            since,
+            json,
+        } => {
+            let branch = match branch {
+                Some(Some(name)) => Some(name),
+                Some(None) => match deciduous::get_current_git_branch() {
+                    Some(name) => Some(name),
+                    None => {
+                        eprintln!(
+                            "{} Not on a git branch; pass --branch <name>",
+                            "Error:".red()
+                        );
+                        std::process::exit(1);
+                    }
+                },
+                None => None,
+            };
+            let since = match since.map(|spec| deciduous::diff::resolve_since(&spec)) {
+                Some(Ok(time)) => Some(time),
+                Some(Err(e)) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+                None => None,
+            };
+            let graph = match db.get_graph() {
+                Ok(g) => g,
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            };
+            let format = if json {
+                deciduous::recover::PackFormat::Json
+            } else {
+                deciduous::recover::PackFormat::Markdown
+            };
+            let options = deciduous::recover::RecoverOptions {
+                budget,
+                branch,
+                since,
+                format,
+            };
+            let pack = deciduous::recover::build_pack(&graph, &options);
+            print!("{}", pack.render(format));
+            if json {
+                println!();
+            }
+        }
+
         Command::Commands { limit } => match db.get_recent_commands(limit) {
             Ok(commands) => {
                 if commands.is_empty() {
                     println!("No commands logged.");
                 } else {
                     for c in commands {
+                        let duration = c
+                            .duration_ms
+                            .map(|ms| format!(", {}", format_duration(ms)))
+                            .unwrap_or_default();
+                        let node = c
+                            .decision_node_id
+                            .map(|id| format!(" -> node {}", id))
+                            .unwrap_or_default();
                         println!(
-                            "[{}] {} (exit: {})",
+                            "[{}] {} (exit: {}{}){}",
                             c.started_at,
                             truncate(&c.command, 60),
                             c.exit_code
                                 .map(|c| c.to_string())
-                                .unwrap_or_else(|| "running".to_string())
+                                .unwrap_or_else(|| "running".to_string()),
+                            duration,
+                            node
                         );
                     }
                 }
@@ -1008,6 +1960,23 @@ fn main() {
             }
         },
 
+        Command::Run {
+            node,
+            description,
+            outcome_on_failure,
+            command,
+        } => run_logged_command(
+            &db,
+            &command,
+            node,
+            description.as_deref(),
+            outcome_on_failure,
+        ),
+
+        Command::Context { action } => run_context(&db, action),
+
+        Command::Session { action } => run_session(&db, action),
+
         Command::Dot {
             output,
             roots,
@@ -1016,6 +1985,7 @@ fn main() {
             auto,
             title,
             rankdir,
+            tags,
         } => {
             match db.get_graph() {
                 Ok(graph) => {
@@ -1033,6 +2003,11 @@ fn main() {
                     } else {
                         graph
                     };
+                    let filtered_graph = if tags.is_empty() {
+                        filtered_graph
+                    } else {
+                        filter_graph_by_tags(&filtered_graph, &tags)
+                    };
 
                     let config = DotConfig {
                         title,
@@ -1147,6 +2122,7 @@ fn main() {
             auto,
             no_dot,
             no_test_plan,
+            tags,
         } => {
             match db.get_graph() {
                 Ok(graph) => {
@@ -1163,6 +2139,11 @@ fn main() {
                     } else {
                         graph
                     };
+                    let filtered_graph = if tags.is_empty() {
+                        filtered_graph
+                    } else {
+                        filter_graph_by_tags(&filtered_graph, &tags)
+                    };
 
                     // Auto-detect GitHub repo from git remote
                     let github_repo = ProcessCommand::new("git")
@@ -1234,126 +2215,410 @@ fn main() {
             }
         }
 
-        Command::Migrate => match db.migrate_add_change_ids() {
-            Ok(true) => {
-                println!(
-                    "{} Database migrated - added change_id columns for multi-user sync",
-                    "Success:".green()
-                );
-            }
-            Ok(false) => {
-                println!(
-                    "{} Database already has change_id columns - no migration needed",
-                    "Info:".cyan()
-                );
-            }
-            Err(e) => {
-                eprintln!("{} Migration failed: {}", "Error:".red(), e);
-                std::process::exit(1);
-            }
-        },
-
+        Command::Migrate { .. } => unreachable!(), // Handled above
+
+        Command::Push {
+            remote,
+            author,
+            sign,
+        } => {
+            let key = if sign {
+                match deciduous::KeyStore::locate().signing_key() {
+                    Ok(key) => Some(key),
+                    Err(e) => {
+                        eprintln!("{} {}", "Error:".red(), e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                None
+            };
+            let author = author.or_else(|| db.author().map(String::from));
+            match db.push_graph(&remote, author, key.as_ref()) {
+                Ok(result) => {
+                    let short = &result.commit[..7.min(result.commit.len())];
+                    if result.committed {
+                        println!(
+                            "{} {} nodes and {} edges to {} ({} {})",
+                            "Pushed:".green(),
+                            result.node_count,
+                            result.edge_count,
+                            remote,
+                            deciduous::gitsync::GRAPH_REF,
+                            short
+                        );
+                    } else {
+                        println!(
+                            "{} Graph unchanged since {}; {} is up to date",
+                            "Info:".cyan(),
+                            short,
+                            remote
+                        );
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Command::Pull {
+            remote,
+            require_signed,
+        } => {
+            let keys = deciduous::KeyStore::locate();
+            let require_signed = require_signed || Config::load().sync.require_signed;
+            match db.pull_graph(&remote, &keys, require_signed) {
+                Ok(result) => {
+                    let Some(commit) = result.commit else {
+                        println!(
+                            "{} {} has no {} yet (create it with 'deciduous push')",
+                            "Info:".cyan(),
+                            remote,
+                            deciduous::gitsync::GRAPH_REF
+                        );
+                        return;
+                    };
+                    let apply = &result.apply;
+                    println!(
+                        "{} {} from {} ({})",
+                        "Pulled:".green(),
+                        &commit[..7.min(commit.len())],
+                        remote,
+                        result
+                            .integrity
+                            .as_ref()
+                            .map(|i| i.describe())
+                            .unwrap_or_default()
+                    );
+                    println!(
+                        "  Nodes: {} added, {} updated, {} merged, {} skipped",
+                        apply.nodes_added,
+                        apply.nodes_updated,
+                        apply.nodes_merged,
+                        apply.nodes_skipped
+                    );
+                    println!(
+                        "  Edges: {} added, {} skipped",
+                        apply.edges_added, apply.edges_skipped
+                    );
+                    if apply.context_added > 0 {
+                        println!("  Context: {} added", apply.context_added);
+                    }
+                    if apply.nodes_deleted > 0 || apply.edges_deleted > 0 {
+                        println!(
+                            "  Deleted: {} nodes, {} edges",
+                            apply.nodes_deleted, apply.edges_deleted
+                        );
+                    }
+                    if !apply.conflicts.is_empty() {
+                        println!(
+                            "  {} {} nodes conflict (see 'deciduous diff conflicts')",
+                            "Warning:".yellow(),
+                            apply.conflicts.len()
+                        );
+                    }
+                    if result.merged {
+                        println!(
+                            "  Local graph history had diverged; created a merge commit (run 'deciduous push' to share it)"
+                        );
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
         Command::Diff { action } => {
             match action {
                 DiffAction::Export {
                     output,
                     nodes,
                     branch,
+                    tags,
                     author,
                     base_commit,
+                    since_last,
+                    since,
+                    sign,
                 } => {
                     // Parse node IDs if provided
                     let node_ids = nodes.as_ref().map(|n| parse_node_range(n));
+                    let filter = deciduous::diff::export_filter(
+                        node_ids.as_deref(),
+                        branch.as_deref(),
+                        &tags,
+                    );
 
-                    match db.export_patch(node_ids, branch.as_deref(), author, base_commit) {
-                        Ok(patch) => match patch.save(&output) {
-                            Ok(()) => {
+                    // Only an export that covered these nodes marks where the last one stopped
+                    let since = if since_last {
+                        match db.last_patch_export(filter.as_deref()) {
+                            Ok(Some(last)) => Some(last.exported_at),
+                            Ok(None) => {
+                                let scope = if filter.is_some() {
+                                    " with these filters"
+                                } else {
+                                    ""
+                                };
                                 println!(
-                                    "{} Exported {} nodes and {} edges to {}",
-                                    "Success:".green(),
-                                    patch.nodes.len(),
-                                    patch.edges.len(),
-                                    output.display()
+                                    "{} No previous export{} recorded; exporting everything",
+                                    "Info:".cyan(),
+                                    scope
                                 );
+                                None
                             }
                             Err(e) => {
                                 eprintln!("{} {}", "Error:".red(), e);
                                 std::process::exit(1);
                             }
-                        },
+                        }
+                    } else {
+                        since.map(|spec| match deciduous::diff::resolve_since(&spec) {
+                            Ok(ts) => ts,
+                            Err(e) => {
+                                eprintln!("{} {}", "Error:".red(), e);
+                                std::process::exit(1);
+                            }
+                        })
+                    };
+
+                    let mut patch = match db.export_patch(
+                        node_ids,
+                        branch.as_deref(),
+                        &tags,
+                        author.or_else(|| db.author().map(String::from)),
+                        base_commit,
+                        since.as_deref(),
+                    ) {
+                        Ok(patch) => patch,
                         Err(e) => {
                             eprintln!("{} {}", "Error:".red(), e);
                             std::process::exit(1);
                         }
+                    };
+                    if sign {
+                        sign_patch_or_exit(&mut patch);
+                    }
+                    if let Err(e) = patch.save(&output) {
+                        eprintln!("{} {}", "Error:".red(), e);
+                        std::process::exit(1);
+                    }
+
+                    let stubs = patch.stub_count();
+                    let node_count = patch.nodes.len() - stubs;
+                    if let Err(e) = db.record_patch_export(
+                        &output.display().to_string(),
+                        &patch.created_at,
+                        since.as_deref(),
+                        node_count,
+                        patch.edges.len(),
+                        filter.as_deref(),
+                    ) {
+                        eprintln!("{} Recording export: {}", "Warning:".yellow(), e);
+                    }
+
+                    let stub_note = if stubs > 0 {
+                        format!(" (+{} unchanged endpoints as stubs)", stubs)
+                    } else {
+                        String::new()
+                    };
+                    println!(
+                        "{} Exported {} nodes{} and {} edges to {}",
+                        "Success:".green(),
+                        node_count,
+                        stub_note,
+                        patch.edges.len(),
+                        output.display()
+                    );
+                    if let Some(since) = since {
+                        println!("  Changes after {}", since);
+                    }
+                    if let Some(signature) = &patch.signature {
+                        println!("  Signed with key {}", signature.key_id());
                     }
                 }
 
-                DiffAction::Apply { files, dry_run } => {
+                DiffAction::Apply {
+                    files,
+                    dry_run,
+                    require_signed,
+                    all,
+                    path,
+                } => {
+                    let keys = deciduous::KeyStore::locate();
+                    let require_signed = require_signed || Config::load().sync.require_signed;
+                    let mut rejected = false;
                     let mut total_added = 0;
+                    let mut total_updated = 0;
                     let mut total_skipped = 0;
                     let mut total_edges_added = 0;
                     let mut total_edges_skipped = 0;
 
-                    for file in files {
-                        match deciduous::GraphPatch::load(&file) {
-                            Ok(patch) => match db.apply_patch(&patch, dry_run) {
-                                Ok(result) => {
-                                    if dry_run {
+                    let loaded: Vec<(PathBuf, Result<deciduous::GraphPatch, String>)> = if all {
+                        let patches_dir =
+                            path.unwrap_or_else(|| PathBuf::from(".deciduous/patches"));
+                        pending_patches(&db, &patches_dir)
+                    } else {
+                        files
+                            .into_iter()
+                            .map(|file| {
+                                let patch = deciduous::GraphPatch::load(&file);
+                                (file, patch)
+                            })
+                            .collect()
+                    };
+                    if all && loaded.is_empty() {
+                        println!("{} No new patches to apply", "Info:".cyan());
+                        return;
+                    }
+
+                    for (file, patch) in loaded {
+                        match patch {
+                            Ok(patch) => {
+                                if let Err(e) = patch.verify(&keys, require_signed) {
+                                    eprintln!(
+                                        "{} Rejecting {}: {}",
+                                        "Error:".red(),
+                                        file.display(),
+                                        e
+                                    );
+                                    rejected = true;
+                                    continue;
+                                }
+                                match db.apply_patch(&patch, dry_run) {
+                                    Ok(result) => {
+                                        if dry_run {
+                                            println!(
+                                                "{} {} (dry run)",
+                                                "Would apply:".cyan(),
+                                                file.display()
+                                            );
+                                        } else {
+                                            println!("{} {}", "Applied:".green(), file.display());
+                                        }
                                         println!(
-                                            "{} {} (dry run)",
-                                            "Would apply:".cyan(),
-                                            file.display()
+                                            "  Nodes: {} added, {} updated, {} merged, {} skipped",
+                                            result.nodes_added,
+                                            result.nodes_updated,
+                                            result.nodes_merged,
+                                            result.nodes_skipped
                                         );
-                                    } else {
-                                        println!("{} {}", "Applied:".green(), file.display());
-                                    }
-                                    println!(
-                                        "  Nodes: {} added, {} skipped",
-                                        result.nodes_added, result.nodes_skipped
-                                    );
-                                    println!(
-                                        "  Edges: {} added, {} skipped",
-                                        result.edges_added, result.edges_skipped
-                                    );
-                                    if !result.edges_failed.is_empty() {
                                         println!(
-                                            "  {} edges failed (missing nodes):",
-                                            result.edges_failed.len()
+                                            "  Edges: {} added, {} skipped",
+                                            result.edges_added, result.edges_skipped
+                                        );
+                                        if !patch.context.is_empty() {
+                                            println!(
+                                                "  Context: {} added, {} skipped",
+                                                result.context_added, result.context_skipped
+                                            );
+                                        }
+                                        if result.nodes_deleted > 0 || result.edges_deleted > 0 {
+                                            println!(
+                                                "  Deleted: {} nodes, {} edges",
+                                                result.nodes_deleted, result.edges_deleted
+                                            );
+                                        }
+                                        if !result.nodes_diverged.is_empty() {
+                                            println!(
+                                                "  {} nodes diverged (kept local copy):",
+                                                result.nodes_diverged.len()
+                                            );
+                                            for msg in &result.nodes_diverged {
+                                                println!("    - {}", msg);
+                                            }
+                                        }
+                                        if !result.conflicts.is_empty() {
+                                            println!(
+                                            "  {} {} nodes conflict (see 'deciduous diff conflicts'):",
+                                            "Warning:".yellow(),
+                                            result.conflicts.len()
                                         );
-                                        for msg in &result.edges_failed {
-                                            println!("    - {}", msg);
+                                            for msg in &result.conflicts {
+                                                println!("    - {}", msg);
+                                            }
+                                        }
+                                        if !result.nodes_failed.is_empty() {
+                                            println!(
+                                                "  {} nodes failed:",
+                                                result.nodes_failed.len()
+                                            );
+                                            for msg in &result.nodes_failed {
+                                                println!("    - {}", msg);
+                                            }
+                                        }
+                                        if !result.edges_failed.is_empty() {
+                                            println!(
+                                                "  {} edges failed:",
+                                                result.edges_failed.len()
+                                            );
+                                            for msg in &result.edges_failed {
+                                                println!("    - {}", msg);
+                                            }
+                                        }
+                                        if !result.context_failed.is_empty() {
+                                            println!(
+                                                "  {} context items failed:",
+                                                result.context_failed.len()
+                                            );
+                                            for msg in &result.context_failed {
+                                                println!("    - {}", msg);
+                                            }
+                                        }
+                                        if !dry_run {
+                                            if let Err(e) = db.record_applied_patch_result(
+                                                &patch,
+                                                &file.display().to_string(),
+                                                &result,
+                                            ) {
+                                                eprintln!(
+                                                    "{} Recording {}: {}",
+                                                    "Warning:".yellow(),
+                                                    file.display(),
+                                                    e
+                                                );
+                                            }
                                         }
+                                        total_added += result.nodes_added;
+                                        total_updated += result.nodes_updated;
+                                        total_skipped += result.nodes_skipped;
+                                        total_edges_added += result.edges_added;
+                                        total_edges_skipped += result.edges_skipped;
+                                    }
+                                    Err(e) => {
+                                        eprintln!(
+                                            "{} Applying {}: {}",
+                                            "Error:".red(),
+                                            file.display(),
+                                            e
+                                        );
                                     }
-                                    total_added += result.nodes_added;
-                                    total_skipped += result.nodes_skipped;
-                                    total_edges_added += result.edges_added;
-                                    total_edges_skipped += result.edges_skipped;
-                                }
-                                Err(e) => {
-                                    eprintln!(
-                                        "{} Applying {}: {}",
-                                        "Error:".red(),
-                                        file.display(),
-                                        e
-                                    );
                                 }
-                            },
+                            }
                             Err(e) => {
                                 eprintln!("{} Loading {}: {}", "Error:".red(), file.display(), e);
+                                rejected = true;
                             }
                         }
                     }
 
                     if !dry_run {
                         println!(
-                            "\n{} {} nodes added, {} skipped; {} edges added, {} skipped",
+                            "\n{} {} nodes added, {} updated, {} skipped; {} edges added, {} skipped",
                             "Total:".cyan(),
                             total_added,
+                            total_updated,
                             total_skipped,
                             total_edges_added,
                             total_edges_skipped
                         );
                     }
+                    if rejected {
+                        std::process::exit(1);
+                    }
                 }
 
                 DiffAction::Status { path } => {
@@ -1368,43 +2633,224 @@ fn main() {
                         return;
                     }
 
-                    // List all .json files in the directory
-                    let entries = match std::fs::read_dir(&patches_dir) {
-                        Ok(e) => e,
+                    let applied: std::collections::HashMap<String, deciduous::AppliedPatch> =
+                        match db.get_applied_patches() {
+                            Ok(applied) => {
+                                applied.into_iter().map(|a| (a.digest.clone(), a)).collect()
+                            }
+                            Err(e) => {
+                                eprintln!("{} {}", "Error:".red(), e);
+                                std::process::exit(1);
+                            }
+                        };
+
+                    println!("{} {}", "Patches in:".cyan(), patches_dir.display());
+                    let (mut n_applied, mut n_partial, mut n_unapplied) = (0, 0, 0);
+                    for path in list_patch_files(&patches_dir) {
+                        let Ok(patch) = deciduous::GraphPatch::load(&path) else {
+                            continue;
+                        };
+                        let state = match applied.get(&patch.effective_digest()) {
+                            Some(a) if a.is_partial() => {
+                                n_partial += 1;
+                                format!("{:<9}", "partial").yellow()
+                            }
+                            Some(_) => {
+                                n_applied += 1;
+                                format!("{:<9}", "applied").green()
+                            }
+                            None => {
+                                n_unapplied += 1;
+                                format!("{:<9}", "unapplied").cyan()
+                            }
+                        };
+                        println!(
+                            "  {} {} - {} nodes, {} edges (author: {}, branch: {})",
+                            state,
+                            path.file_name().unwrap_or_default().to_string_lossy(),
+                            patch.nodes.len(),
+                            patch.edges.len(),
+                            patch.author.as_deref().unwrap_or("unknown"),
+                            patch.branch.as_deref().unwrap_or("unknown")
+                        );
+                    }
+                    println!(
+                        "\n{} applied, {} partial, {} unapplied",
+                        n_applied, n_partial, n_unapplied
+                    );
+                    if n_partial + n_unapplied > 0 {
+                        println!("Apply them with: deciduous diff apply --all");
+                    }
+                }
+
+                DiffAction::Squash {
+                    files,
+                    output,
+                    sign,
+                } => {
+                    let keys = deciduous::KeyStore::locate();
+                    let mut patches = Vec::new();
+                    for file in &files {
+                        let patch = deciduous::GraphPatch::load(file).and_then(|patch| {
+                            patch.verify(&keys, false)?;
+                            Ok(patch)
+                        });
+                        match patch {
+                            Ok(patch) => patches.push(patch),
+                            Err(e) => {
+                                eprintln!("{} {}: {}", "Error:".red(), file.display(), e);
+                                std::process::exit(1);
+                            }
+                        }
+                    }
+
+                    let mut squashed = deciduous::squash_patches(&patches);
+                    if sign {
+                        sign_patch_or_exit(&mut squashed);
+                    }
+                    if let Err(e) = squashed.save(&output) {
+                        eprintln!("{} {}", "Error:".red(), e);
+                        std::process::exit(1);
+                    }
+
+                    let nodes_in: usize = patches.iter().map(|p| p.nodes.len()).sum();
+                    let edges_in: usize = patches.iter().map(|p| p.edges.len()).sum();
+                    println!(
+                        "{} {} patches into {}",
+                        "Squashed:".green(),
+                        patches.len(),
+                        output.display()
+                    );
+                    println!(
+                        "  Nodes: {} -> {} ({} stubs)",
+                        nodes_in,
+                        squashed.nodes.len(),
+                        squashed.stub_count()
+                    );
+                    println!("  Edges: {} -> {}", edges_in, squashed.edges.len());
+                    println!("  Tombstones: {}", squashed.tombstones.len());
+                }
+
+                DiffAction::Snapshot {
+                    output,
+                    author,
+                    sign,
+                } => {
+                    let author = author.or_else(|| db.author().map(String::from));
+                    let mut patch = match db.snapshot_patch(author) {
+                        Ok(patch) => patch,
                         Err(e) => {
-                            eprintln!("{} Reading directory: {}", "Error:".red(), e);
-                            return;
+                            eprintln!("{} {}", "Error:".red(), e);
+                            std::process::exit(1);
                         }
                     };
+                    if sign {
+                        sign_patch_or_exit(&mut patch);
+                    }
+                    if let Err(e) = patch.save(&output) {
+                        eprintln!("{} {}", "Error:".red(), e);
+                        std::process::exit(1);
+                    }
+                    println!(
+                        "{} Wrote snapshot of {} nodes, {} edges and {} tombstones to {}",
+                        "Success:".green(),
+                        patch.nodes.len(),
+                        patch.edges.len(),
+                        patch.tombstones.len(),
+                        output.display()
+                    );
+                }
 
-                    println!("{} {}", "Patches in:".cyan(), patches_dir.display());
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.extension().map(|e| e == "json").unwrap_or(false) {
-                            if let Ok(patch) = deciduous::GraphPatch::load(&path) {
-                                let author = patch.author.as_deref().unwrap_or("unknown");
-                                let branch = patch.branch.as_deref().unwrap_or("unknown");
+                DiffAction::Revert { patch } => {
+                    let record = match find_applied_patch(&db, &patch) {
+                        Ok(record) => record,
+                        Err(e) => {
+                            eprintln!("{} {}", "Error:".red(), e);
+                            std::process::exit(1);
+                        }
+                    };
+                    match db.revert_patch(&record.digest) {
+                        Ok(result) => {
+                            println!("{} {}", "Reverted:".green(), record.path);
+                            println!(
+                                "  Removed {} nodes and {} edges the patch added",
+                                result.nodes_removed, result.edges_removed
+                            );
+                            if result.dependent_edges_removed > 0 {
                                 println!(
-                                    "  {} - {} nodes, {} edges (author: {}, branch: {})",
-                                    path.file_name().unwrap_or_default().to_string_lossy(),
-                                    patch.nodes.len(),
-                                    patch.edges.len(),
-                                    author,
-                                    branch
+                                    "  Also removed {} later edges that pointed at those nodes",
+                                    result.dependent_edges_removed
                                 );
                             }
+                            if record.nodes_updated > 0 {
+                                println!(
+                                    "  {} {} nodes the patch updated were left as they are",
+                                    "Note:".cyan(),
+                                    record.nodes_updated
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("{} {}", "Error:".red(), e);
+                            std::process::exit(1);
                         }
                     }
                 }
 
-                DiffAction::Validate { files } => {
+                DiffAction::Validate {
+                    files,
+                    require_signed,
+                } => {
                     use std::collections::HashSet;
 
+                    let keys = deciduous::KeyStore::locate();
+                    let require_signed = require_signed || Config::load().sync.require_signed;
                     let mut any_errors = false;
 
                     for file in &files {
-                        match deciduous::GraphPatch::load(file) {
+                        let loaded = std::fs::read_to_string(file)
+                            .map_err(|e| format!("Failed to read patch file: {}", e))
+                            .and_then(|content| {
+                                let violations = patch_schema_violations(&content)?;
+                                Ok((content, violations))
+                            });
+                        let content = match loaded {
+                            Ok((_, violations)) if !violations.is_empty() => {
+                                any_errors = true;
+                                println!("{} {}", "Validating:".cyan(), file.display());
+                                println!(
+                                    "  {} {} schema violation(s):",
+                                    "Rejected:".red(),
+                                    violations.len()
+                                );
+                                for violation in &violations {
+                                    println!("    - {}", violation);
+                                }
+                                println!();
+                                continue;
+                            }
+                            Ok((content, _)) => content,
+                            Err(e) => {
+                                any_errors = true;
+                                eprintln!("{} {}: {}", "Error:".red(), file.display(), e);
+                                println!();
+                                continue;
+                            }
+                        };
+
+                        match deciduous::GraphPatch::from_json(&content) {
                             Ok(patch) => {
+                                let integrity = match patch.verify(&keys, require_signed) {
+                                    Ok(integrity) => integrity,
+                                    Err(e) => {
+                                        any_errors = true;
+                                        println!("{} {}", "Validating:".cyan(), file.display());
+                                        println!("  {} {}", "Rejected:".red(), e);
+                                        println!();
+                                        continue;
+                                    }
+                                };
+
                                 // Collect all node change_ids in the patch
                                 let node_ids: HashSet<&str> =
                                     patch.nodes.iter().map(|n| n.change_id.as_str()).collect();
@@ -1438,7 +2884,22 @@ fn main() {
                                 }
 
                                 println!("{} {}", "Validating:".cyan(), file.display());
-                                println!("  Nodes: {}", patch.nodes.len());
+                                if let Some(original) = patch.upgraded_from() {
+                                    println!(
+                                        "  Format: {} (upgraded to {})",
+                                        original,
+                                        deciduous::PATCH_VERSION
+                                    );
+                                }
+                                println!("  Integrity: {}", integrity.describe());
+                                match patch.stub_count() {
+                                    0 => println!("  Nodes: {}", patch.nodes.len()),
+                                    stubs => println!(
+                                        "  Nodes: {} ({} stubs for edge endpoints)",
+                                        patch.nodes.len(),
+                                        stubs
+                                    ),
+                                }
                                 println!(
                                     "  Edges: {} ({} valid, {} with missing refs)",
                                     patch.edges.len(),
@@ -1484,6 +2945,100 @@ fn main() {
                         std::process::exit(1);
                     }
                 }
+
+                DiffAction::Keygen { force } => {
+                    let keys = deciduous::KeyStore::locate();
+                    match keys.generate(force) {
+                        Ok(public_key) => {
+                            let key_id = deciduous::signing::key_id(&public_key);
+                            println!(
+                                "{} Created signing key {}",
+                                "Success:".green(),
+                                keys.dir()
+                                    .join(deciduous::signing::SIGNING_KEY_FILE)
+                                    .display()
+                            );
+                            println!("  Public key: {}", public_key);
+                            println!(
+                                "  Teammates trust your patches by copying {} into their .deciduous/keys/",
+                                keys.dir().join(format!("{}.pub", key_id)).display()
+                            );
+                        }
+                        Err(e) => {
+                            eprintln!("{} {}", "Error:".red(), e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+
+                DiffAction::Conflicts { resolve, id } => {
+                    let conflicts: Vec<_> = match db.get_unresolved_node_conflicts() {
+                        Ok(c) => c
+                            .into_iter()
+                            .filter(|c| id.map_or(true, |id| c.id == id))
+                            .collect(),
+                        Err(e) => {
+                            eprintln!("{} {}", "Error:".red(), e);
+                            std::process::exit(1);
+                        }
+                    };
+
+                    if conflicts.is_empty() {
+                        if let Some(id) = id {
+                            eprintln!("{} No unresolved conflict #{}", "Error:".red(), id);
+                            std::process::exit(1);
+                        }
+                        println!("{} No unresolved node conflicts", "Info:".cyan());
+                        return;
+                    }
+
+                    let Some(choice) = resolve else {
+                        for conflict in &conflicts {
+                            if let Err(e) = print_conflict(conflict) {
+                                eprintln!("{} {}", "Error:".red(), e);
+                            }
+                            println!();
+                        }
+                        println!(
+                            "Resolve with: deciduous diff conflicts --resolve ours|theirs|edit [--id N]"
+                        );
+                        return;
+                    };
+
+                    for conflict in &conflicts {
+                        let resolution = match choice.as_str() {
+                            "ours" => deciduous::ConflictResolution::Ours,
+                            "theirs" => deciduous::ConflictResolution::Theirs,
+                            _ => match edit_conflict_in_editor(conflict) {
+                                Ok(Some(content)) => deciduous::ConflictResolution::Edit(content),
+                                Ok(None) => {
+                                    println!(
+                                        "{} Left conflict #{} unresolved",
+                                        "Info:".cyan(),
+                                        conflict.id
+                                    );
+                                    continue;
+                                }
+                                Err(e) => {
+                                    eprintln!("{} {}", "Error:".red(), e);
+                                    std::process::exit(1);
+                                }
+                            },
+                        };
+                        match db.resolve_node_conflict(conflict, &resolution) {
+                            Ok(()) => println!(
+                                "{} conflict #{} ({})",
+                                "Resolved".green(),
+                                conflict.id,
+                                resolution.as_str()
+                            ),
+                            Err(e) => {
+                                eprintln!("{} Conflict #{}: {}", "Error:".red(), conflict.id, e);
+                                std::process::exit(1);
+                            }
+                        }
+                    }
+                }
             }
         }
 
@@ -1533,32 +3088,14 @@ fn main() {
                 .filter(|n| n.node_type == "action" || n.node_type == "outcome")
                 .filter(|n| {
                     // Check if already has commit
-                    !n.metadata_json
-                        .as_ref()
-                        .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
-                        .and_then(|v| {
-                            v.get("commit")
-                                .and_then(|c| c.as_str())
-                                .map(|s| !s.is_empty())
-                        })
-                        .unwrap_or(false)
+                    !n.commit().is_some_and(|c| !c.is_empty())
                 })
                 .collect();
 
             let with_commits = nodes
                 .iter()
                 .filter(|n| n.node_type == "action" || n.node_type == "outcome")
-                .filter(|n| {
-                    n.metadata_json
-                        .as_ref()
-                        .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
-                        .and_then(|v| {
-                            v.get("commit")
-                                .and_then(|c| c.as_str())
-                                .map(|s| !s.is_empty())
-                        })
-                        .unwrap_or(false)
-                })
+                .filter(|n| n.commit().is_some_and(|c| !c.is_empty()))
                 .count();
 
             println!(
@@ -1962,9 +3499,8 @@ fn main() {
 
                     for section in &syncable_sections {
                         // Check if section already has an issue
-                        if section.github_issue_number.is_some() {
+                        if let Some(issue_num) = section.github_issue_number {
                             // Update existing issue
-                            let issue_num = section.github_issue_number.unwrap();
                             let body = generate_issue_body(section);
 
                             if dry_run {
@@ -2543,6 +4079,216 @@ fn truncate(s: &str, max_len: usize) -> String {
     }
 }
 
+// =============================================================================
+// Graph vocabulary value parsers
+// =============================================================================
+
+/// Built-in variants of a ValueEnum plus extras registered in config
+fn vocabulary_values<T: clap::ValueEnum>(extra: Vec<String>) -> PossibleValuesParser {
+    let builtin = T::value_variants()
+        .iter()
+        .filter_map(|v| v.to_possible_value());
+    PossibleValuesParser::new(builtin.chain(extra.into_iter().map(PossibleValue::new)))
+}
+
+/// Config read once for all the parsers, since clap builds them per argument
+fn vocabulary_config() -> &'static Config {
+    static CONFIG: std::sync::OnceLock<Config> = std::sync::OnceLock::new();
+    CONFIG.get_or_init(Config::load)
+}
+
+fn node_type_values() -> PossibleValuesParser {
+    vocabulary_values::<NodeType>(vocabulary_config().types.node.clone())
+}
+
+fn edge_type_values() -> PossibleValuesParser {
+    vocabulary_values::<EdgeType>(vocabulary_config().types.edge.clone())
+}
+
+fn status_values() -> PossibleValuesParser {
+    vocabulary_values::<NodeStatus>(vocabulary_config().types.status.clone())
+}
+
+// =============================================================================
+// Edit command helpers
+// =============================================================================
+
+/// Editable view of a node, round-tripped through $EDITOR as TOML
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+struct EditableNode {
+    #[serde(rename = "type")]
+    node_type: String,
+    title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    confidence: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    files: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+}
+
+impl EditableNode {
+    fn from_node(node: &deciduous::DecisionNode) -> Self {
+        let meta = node.metadata();
+        Self {
+            node_type: node.node_type.clone(),
+            title: node.title.clone(),
+            description: node.description.clone(),
+            confidence: meta.confidence,
+            files: Some(meta.files).filter(|f| !f.is_empty()),
+            branch: meta.branch,
+        }
+    }
+}
+
+/// Open the node in $EDITOR as TOML and return the edited values.
+/// Returns Ok(None) if nothing changed. Fields removed in the editor are cleared.
+fn edit_node_in_editor(node: &deciduous::DecisionNode) -> Result<Option<EditableNode>, String> {
+    let original = EditableNode::from_node(node);
+    let toml_str =
+        toml::to_string(&original).map_err(|e| format!("Serializing node as TOML: {}", e))?;
+
+    let path = std::env::temp_dir().join(format!(
+        "deciduous-edit-{}-{}.toml",
+        node.id,
+        std::process::id()
+    ));
+    let header = format!(
+        "# Editing node {} ({}). Save and quit to apply; remove description, confidence, files or branch to clear it.\n",
+        node.id,
+        &node.change_id[..8.min(node.change_id.len())]
+    );
+    std::fs::write(&path, format!("{}{}", header, toml_str))
+        .map_err(|e| format!("Writing temp file: {}", e))?;
+
+    let edited = run_editor(&path)?;
+    let parsed: EditableNode =
+        toml::from_str(&edited).map_err(|e| format!("Parsing edited TOML: {}", e))?;
+
+    if parsed == original {
+        return Ok(None);
+    }
+
+    Ok(Some(EditableNode {
+        description: Some(parsed.description.unwrap_or_default()),
+        files: Some(parsed.files.unwrap_or_default()),
+        branch: Some(parsed.branch.unwrap_or_default()),
+        ..parsed
+    }))
+}
+
+/// Open `path` in $EDITOR, then return its contents and remove it
+fn run_editor(path: &std::path::Path) -> Result<String, String> {
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
+    let status = ProcessCommand::new(&editor).arg(path).status();
+    let edited = std::fs::read_to_string(path);
+    let _ = std::fs::remove_file(path);
+
+    match status {
+        Ok(s) if s.success() => {}
+        Ok(s) => return Err(format!("Editor '{}' exited with {}", editor, s)),
+        Err(e) => return Err(format!("Running editor '{}': {}", editor, e)),
+    }
+
+    edited.map_err(|e| format!("Reading edited file: {}", e))
+}
+
+// =============================================================================
+// Diff conflict helpers
+// =============================================================================
+
+/// Local, patch and (if known) common-ancestor content of a conflict
+fn conflict_contents(
+    conflict: &deciduous::NodeConflict,
+) -> Result<
+    (
+        deciduous::NodeContent,
+        deciduous::NodeContent,
+        Option<deciduous::NodeContent>,
+    ),
+    String,
+> {
+    let parse = |json: &str| {
+        deciduous::NodeContent::from_json(json)
+            .ok_or_else(|| format!("Conflict {} has unreadable content", conflict.id))
+    };
+    let ours = parse(&conflict.local_value)?;
+    let theirs = parse(&conflict.remote_value)?;
+    let base = conflict
+        .base_value
+        .as_deref()
+        .and_then(deciduous::NodeContent::from_json);
+    Ok((ours, theirs, base))
+}
+
+/// Print the fields a conflict disagrees on, side by side
+fn print_conflict(conflict: &deciduous::NodeConflict) -> Result<(), String> {
+    let (ours, theirs, base) = conflict_contents(conflict)?;
+    let fields = match &base {
+        Some(base) => deciduous::diff::merge_content(base, &ours, &theirs).conflicts,
+        None => ours.differing_fields(&theirs),
+    };
+
+    println!(
+        "{} node {} \"{}\" (detected {})",
+        format!("Conflict #{}", conflict.id).yellow().bold(),
+        &conflict.node_change_id[..8.min(conflict.node_change_id.len())],
+        ours.title,
+        conflict.detected_at
+    );
+    if base.is_none() {
+        println!(
+            "  {}",
+            "Common version unknown; showing all differences".dimmed()
+        );
+    }
+    for field in fields {
+        println!("  {}:", field.cyan());
+        println!("    ours:   {}", ours.field(&field));
+        println!("    theirs: {}", theirs.field(&field));
+    }
+    Ok(())
+}
+
+/// Open a conflict in $EDITOR as JSON with a pre-merged "resolved" entry to edit.
+/// Returns Ok(None) if "resolved" was removed, leaving the conflict open.
+fn edit_conflict_in_editor(
+    conflict: &deciduous::NodeConflict,
+) -> Result<Option<deciduous::NodeContent>, String> {
+    let (ours, theirs, base) = conflict_contents(conflict)?;
+    let resolved = match &base {
+        Some(base) => deciduous::diff::merge_content(base, &ours, &theirs).merged,
+        None => ours.clone(),
+    };
+
+    let doc = serde_json::json!({
+        "help": "Edit \"resolved\" and save to replace the node. Fields both sides changed start with our value. Delete \"resolved\" to leave the conflict open.",
+        "ours": ours,
+        "theirs": theirs,
+        "resolved": resolved,
+    });
+    let path = std::env::temp_dir().join(format!(
+        "deciduous-conflict-{}-{}.json",
+        conflict.id,
+        std::process::id()
+    ));
+    let json =
+        serde_json::to_string_pretty(&doc).map_err(|e| format!("Serializing conflict: {}", e))?;
+    std::fs::write(&path, json).map_err(|e| format!("Writing temp file: {}", e))?;
+
+    let edited = run_editor(&path)?;
+    let mut parsed: serde_json::Value =
+        serde_json::from_str(&edited).map_err(|e| format!("Parsing edited JSON: {}", e))?;
+    match parsed.get_mut("resolved").map(serde_json::Value::take) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value)
+            .map(Some)
+            .map_err(|e| format!("Parsing \"resolved\": {}", e)),
+    }
+}
+
 // =============================================================================
 // Audit command helpers
 // =============================================================================
@@ -2624,29 +4370,12 @@ fn keyword_match_score(node_title: &str, commit_message: &str) -> f64 {
 // Git history export helpers
 // =============================================================================
 
-/// Git commit info for timeline view (matches web/src/types/graph.ts GitCommit)
-#[derive(serde::Serialize)]
-struct GitCommit {
-    hash: String,
-    short_hash: String,
-    author: String,
-    date: String,
-    message: String,
-    files_changed: Option<u32>,
-}
-
 /// Extract all unique commit hashes from nodes' metadata_json
 fn extract_commit_hashes(nodes: &[deciduous::DecisionNode]) -> Vec<String> {
     let mut hashes = std::collections::HashSet::new();
     for node in nodes {
-        if let Some(ref meta_json) = node.metadata_json {
-            if let Ok(meta) = serde_json::from_str::<serde_json::Value>(meta_json) {
-                if let Some(commit) = meta.get("commit").and_then(|c| c.as_str()) {
-                    if !commit.is_empty() {
-                        hashes.insert(commit.to_string());
-                    }
-                }
-            }
+        if let Some(commit) = node.commit().filter(|c| !c.is_empty()) {
+            hashes.insert(commit);
         }
     }
     hashes.into_iter().collect()
@@ -2723,6 +4452,637 @@ fn export_git_history(
     Ok(commits.len())
 }
 
+/// Render a search snippet, bolding the `[matched]` terms
+fn highlight_snippet(snippet: &str) -> String {
+    let mut out = String::new();
+    let mut rest = snippet;
+    while let Some(start) = rest.find('[') {
+        let Some(len) = rest[start..].find(']') else {
+            break;
+        };
+        out.push_str(&rest[..start].dimmed().to_string());
+        out.push_str(&rest[start + 1..start + len].bold().to_string());
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(&rest.dimmed().to_string());
+    out
+}
+
+// =============================================================================
+// Schema migrations
+// =============================================================================
+
+fn run_migrate(status: bool, to: Option<u32>, down: bool) {
+    let db = match Database::open_unmigrated() {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("{} Failed to open database: {}", "Error:".red(), e);
+            std::process::exit(1);
+        }
+    };
+
+    if status {
+        match db.migration_status() {
+            Ok(status) => {
+                println!(
+                    "Schema migration {} of {}",
+                    status.current.to_string().cyan(),
+                    status.latest
+                );
+                if let Some(pinned) = status.pinned {
+                    println!(
+                        "  {}",
+                        format!(
+                            "Rolled back to {:04}; run 'deciduous migrate' before other commands",
+                            pinned
+                        )
+                        .yellow()
+                    );
+                }
+                for entry in status.entries {
+                    let state = match (&entry.applied_at, entry.known) {
+                        (Some(at), true) => format!("applied {}", at).green(),
+                        (Some(at), false) => {
+                            format!("applied {} by a newer deciduous", at).yellow()
+                        }
+                        (None, _) => "pending".dimmed(),
+                    };
+                    println!("  {:04} {:<24} {}", entry.version, entry.name, state);
+                }
+            }
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red(), e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let latest = deciduous::migrations::latest_version();
+    let result = if down {
+        db.migrate_down().map(|step| step.into_iter().collect())
+    } else {
+        db.migrate_pinned(to.unwrap_or(latest))
+    };
+    let pinned = down || to.is_some_and(|t| t < latest);
+
+    match result {
+        Ok(steps) if steps.is_empty() && down => {
+            println!("{} No migrations to roll back", "Info:".cyan());
+        }
+        Ok(steps) if steps.is_empty() => {
+            println!("{} Database schema is up to date", "Info:".cyan());
+        }
+        Ok(steps) => {
+            for step in steps {
+                let verb = match step.direction {
+                    deciduous::migrations::Direction::Up => "Applied".green(),
+                    deciduous::migrations::Direction::Down => "Reverted".yellow(),
+                };
+                println!("{} migration {:04} {}", verb, step.version, step.name);
+            }
+            if pinned {
+                println!(
+                    "{} Other commands refuse to run until 'deciduous migrate' brings the schema up to date",
+                    "Note:".yellow()
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("{} Migration failed: {}", "Error:".red(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_context(db: &Database, action: ContextAction) {
+    match action {
+        ContextAction::Add {
+            node,
+            context_type,
+            file,
+        } => {
+            let content = match &file {
+                Some(path) => std::fs::read_to_string(path)
+                    .map_err(|e| format!("Reading {}: {}", path.display(), e)),
+                None => {
+                    use std::io::Read;
+                    let mut buffer = String::new();
+                    std::io::stdin()
+                        .read_to_string(&mut buffer)
+                        .map(|_| buffer)
+                        .map_err(|e| format!("Reading stdin: {}", e))
+                }
+            };
+            let content = match content {
+                Ok(c) if c.trim().is_empty() => {
+                    eprintln!(
+                        "{} No content. Pipe it in or pass --file <path>.",
+                        "Error:".red()
+                    );
+                    std::process::exit(1);
+                }
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            };
+            let source = file.as_ref().map(|p| p.display().to_string());
+            match db.add_context(node, context_type, &content, source.as_deref()) {
+                Ok(_) => println!(
+                    "{} {} context to node {} ({} lines)",
+                    "Attached".green(),
+                    context_type,
+                    node,
+                    content.lines().count()
+                ),
+                Err(e) => {
+                    eprintln!("{} {}", "Error:".red(), e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        ContextAction::List { node } => match db.get_node_context(node) {
+            Ok(items) if items.is_empty() => println!("No context attached to node {}.", node),
+            Ok(items) => {
+                for item in items {
+                    let source = item
+                        .source()
+                        .map(|s| format!(" from {}", s))
+                        .unwrap_or_default();
+                    println!(
+                        "{} {}{}",
+                        format!("[{}]", item.context_type).cyan(),
+                        item.captured_at.dimmed(),
+                        source
+                    );
+                    for line in item.content().lines() {
+                        println!("  {}", line);
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red(), e);
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+/// Run a command with inherited stdin, streaming its output while keeping a
+/// copy for the command log. Exits with the command's exit code.
+fn run_logged_command(
+    db: &Database,
+    command: &[String],
+    node: Option<i32>,
+    description: Option<&str>,
+    outcome_on_failure: bool,
+) {
+    use std::process::Stdio;
+
+    let command_line = shell_join(command);
+    let working_dir = std::env::current_dir()
+        .ok()
+        .map(|d| d.display().to_string());
+    let log_id = match db.log_command(&command_line, description, working_dir.as_deref(), node) {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red(), e);
+            std::process::exit(1);
+        }
+    };
+
+    // Ctrl-C reaches the command as well. Let it exit and record that rather than
+    // dying and leaving the entry running; a second Ctrl-C quits right away.
+    let interrupted = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    for register in [
+        signal_hook::flag::register_conditional_shutdown(
+            signal_hook::consts::SIGINT,
+            130,
+            std::sync::Arc::clone(&interrupted),
+        ),
+        signal_hook::flag::register(
+            signal_hook::consts::SIGINT,
+            std::sync::Arc::clone(&interrupted),
+        ),
+    ] {
+        if let Err(e) = register {
+            eprintln!("{} Handling Ctrl-C: {}", "Warning:".yellow(), e);
+        }
+    }
+
+    let started = std::time::Instant::now();
+    let child = std::process::Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            // Same code a shell uses for a command it can't run
+            let message = format!("Running {}: {}", command[0], e);
+            let _ = db.complete_command(log_id, 127, None, Some(&message), 0);
+            eprintln!("{} {}", "Error:".red(), message);
+            std::process::exit(127);
+        }
+    };
+
+    let stdout = child
+        .stdout
+        .take()
+        .map(|pipe| std::thread::spawn(move || tee_output(pipe, std::io::stdout())));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|pipe| std::thread::spawn(move || tee_output(pipe, std::io::stderr())));
+    let status = child.wait();
+    let stdout = stdout.and_then(|t| t.join().ok()).unwrap_or_default();
+    let stderr = stderr.and_then(|t| t.join().ok()).unwrap_or_default();
+    let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    // No exit code means the command was killed by a signal
+    let exit_code = match status {
+        Ok(status) => status.code().unwrap_or(-1),
+        Err(e) => {
+            eprintln!("{} Waiting for {}: {}", "Error:".red(), command[0], e);
+            -1
+        }
+    };
+    if let Err(e) = db.complete_command(
+        log_id,
+        exit_code,
+        Some(&stdout).filter(|s| !s.is_empty()).map(String::as_str),
+        Some(&stderr).filter(|s| !s.is_empty()).map(String::as_str),
+        duration_ms,
+    ) {
+        eprintln!("{} Recording command: {}", "Error:".red(), e);
+    }
+
+    let linked = node
+        .map(|id| format!(" for node {}", id))
+        .unwrap_or_default();
+    if interrupted.load(std::sync::atomic::Ordering::SeqCst) {
+        eprintln!(
+            "{} command {} (interrupted, {}){}",
+            "Logged".yellow(),
+            log_id,
+            format_duration(duration_ms),
+            linked
+        );
+        std::process::exit(130);
+    }
+    if exit_code == 0 {
+        eprintln!(
+            "{} command {} ({}){}",
+            "Logged".green(),
+            log_id,
+            format_duration(duration_ms),
+            linked
+        );
+        return;
+    }
+    eprintln!(
+        "{} command {} (exit {}, {}){}",
+        "Logged".yellow(),
+        log_id,
+        exit_code,
+        format_duration(duration_ms),
+        linked
+    );
+
+    if outcome_on_failure {
+        let title = format!(
+            "Command failed: {} (exit {})",
+            truncate(&command_line, 80),
+            exit_code
+        );
+        let branch = deciduous::get_current_git_branch();
+        let outcome = db
+            .create_node_full(
+                "outcome",
+                &title,
+                None,
+                None,
+                None,
+                None,
+                None,
+                branch.as_deref(),
+            )
+            .and_then(|outcome| {
+                match node {
+                    Some(parent) => {
+                        db.create_edge(parent, outcome, "leads_to", Some("Command failed"))?;
+                    }
+                    None => db.link_command(log_id, outcome)?,
+                }
+                Ok(outcome)
+            });
+        match outcome {
+            Ok(id) => eprintln!("{} outcome node {}: {}", "Created".green(), id, title),
+            Err(e) => eprintln!("{} Creating outcome node: {}", "Error:".red(), e),
+        }
+    }
+    std::process::exit(if exit_code > 0 { exit_code } else { 1 });
+}
+
+/// Copy a child's output through to `out` as it arrives, returning the part that
+/// gets logged (the last `COMMAND_OUTPUT_LIMIT` bytes)
+fn tee_output(mut pipe: impl std::io::Read, mut out: impl std::io::Write) -> String {
+    let mut captured = deciduous::OutputTail::new(deciduous::COMMAND_OUTPUT_LIMIT);
+    let mut buffer = [0u8; 8192];
+    loop {
+        match pipe.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let _ = out.write_all(&buffer[..n]);
+                let _ = out.flush();
+                captured.push(&buffer[..n]);
+            }
+        }
+    }
+    captured.into_string()
+}
+
+/// Join arguments into a command line, quoting any that need it
+fn shell_join(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            let plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+            if plain {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Human-readable duration, e.g. "850ms" or "12.3s"
+fn format_duration(ms: i32) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+fn run_session(db: &Database, action: SessionAction) {
+    use deciduous::tui::views::sessions::short_timestamp;
+
+    let result = match action {
+        SessionAction::Start { name } => db.start_session(name.as_deref()).map(|session| {
+            println!(
+                "{} session {}{}",
+                "Started".green(),
+                session.id,
+                session_name(&session)
+            );
+            println!("New nodes will be attached until 'deciduous session end'.");
+        }),
+        SessionAction::End { summary } => db.end_session(summary.as_deref()).and_then(|session| {
+            let nodes = db.get_session_nodes(session.id)?;
+            println!(
+                "{} session {}{} ({} nodes)",
+                "Ended".green(),
+                session.id,
+                session_name(&session),
+                nodes.len()
+            );
+            Ok(())
+        }),
+        SessionAction::List => db.get_sessions().and_then(|sessions| {
+            if sessions.is_empty() {
+                println!("No sessions. Start one with: deciduous session start \"name\"");
+                return Ok(());
+            }
+            let counts = db.get_session_node_counts()?;
+            println!(
+                "{:<5} {:<17} {:<7} {:<6} NAME",
+                "ID", "STARTED", "STATE", "NODES"
+            );
+            println!("{}", "-".repeat(70));
+            for session in sessions {
+                let state = if session.is_open() {
+                    "open".green()
+                } else {
+                    "ended".dimmed()
+                };
+                println!(
+                    "{:<5} {:<17} {:<7} {:<6} {}",
+                    session.id,
+                    short_timestamp(&session.started_at),
+                    state,
+                    counts.get(&session.id).copied().unwrap_or(0),
+                    session.name.as_deref().unwrap_or("-")
+                );
+            }
+            Ok(())
+        }),
+        SessionAction::Show { id } => {
+            let session = match id {
+                Some(id) => db.get_session(id),
+                None => db.get_open_session(),
+            };
+            session.and_then(|session| {
+                let Some(session) = session else {
+                    let what = id.map_or("No session is open".to_string(), |id| {
+                        format!("Session {} does not exist", id)
+                    });
+                    return Err(deciduous::db::DbError::Validation(format!(
+                        "{}. Run 'deciduous session list' to see sessions.",
+                        what
+                    )));
+                };
+                print_session(&session, &db.get_session_nodes(session.id)?);
+                Ok(())
+            })
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("{} {}", "Error:".red(), e);
+        std::process::exit(1);
+    }
+}
+
+fn print_session(session: &deciduous::DecisionSession, nodes: &[deciduous::DecisionNode]) {
+    use deciduous::tui::views::sessions::short_timestamp;
+
+    println!(
+        "{}",
+        format!("Session {}{}", session.id, session_name(session)).cyan()
+    );
+    println!("  Started: {}", short_timestamp(&session.started_at));
+    match &session.ended_at {
+        Some(ended) => println!("  Ended:   {}", short_timestamp(ended)),
+        None => println!("  Ended:   {}", "still open".green()),
+    }
+    if let Some(summary) = &session.summary {
+        println!("  Summary: {}", summary);
+    }
+    println!("  Nodes:   {}", nodes.len());
+    for node in nodes {
+        let root = if session.root_node_id == Some(node.id) {
+            " (root)".dimmed().to_string()
+        } else {
+            String::new()
+        };
+        println!(
+            "    {} [{}]{}",
+            deciduous::traverse::node_label(node),
+            node.status,
+            root
+        );
+    }
+}
+
+fn session_name(session: &deciduous::DecisionSession) -> String {
+    session
+        .name
+        .as_ref()
+        .map(|n| format!(" \"{}\"", n))
+        .unwrap_or_default()
+}
+
+/// Print a path as alternating node and edge lines
+fn print_path(path: &[deciduous::PathStep]) {
+    let Some(first) = path.first() else {
+        return;
+    };
+    println!("  {}", deciduous::traverse::node_label(&first.from));
+    for step in path {
+        let rationale = step
+            .rationale
+            .as_deref()
+            .map(|r| format!(": {}", r))
+            .unwrap_or_default();
+        println!(
+            "    {} {}{}",
+            "↓".dimmed(),
+            step.edge_type.yellow(),
+            rationale.dimmed()
+        );
+        println!("  {}", deciduous::traverse::node_label(&step.to));
+    }
+}
+
+/// Schema violations in raw patch JSON, after upgrading it to the current format
+fn patch_schema_violations(
+    content: &str,
+) -> Result<Vec<deciduous::json_schema::SchemaViolation>, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse patch JSON: {}", e))?;
+    deciduous::upgrade_patch_json(&mut value)?;
+    Ok(deciduous::json_schema::validate(
+        &deciduous::json_schema::patch_schema(),
+        &value,
+    ))
+}
+
+/// Sign a patch with the local signing key, exiting if there isn't one
+fn sign_patch_or_exit(patch: &mut deciduous::GraphPatch) {
+    match deciduous::KeyStore::locate().signing_key() {
+        Ok(key) => patch.sign(&key),
+        Err(e) => {
+            eprintln!("{} {}", "Error:".red(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Patch files (`*.json`) in a directory, sorted by name
+fn list_patch_files(dir: &std::path::Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Patches in a directory that aren't fully applied yet, in dependency order.
+/// Files that fail to load are kept (at the end) so their errors get reported.
+fn pending_patches(
+    db: &Database,
+    dir: &std::path::Path,
+) -> Vec<(PathBuf, Result<deciduous::GraphPatch, String>)> {
+    let applied: std::collections::HashMap<String, deciduous::AppliedPatch> = db
+        .get_applied_patches()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|a| (a.digest.clone(), a))
+        .collect();
+
+    let mut files = Vec::new();
+    let mut patches = Vec::new();
+    let mut failed = Vec::new();
+    for file in list_patch_files(dir) {
+        match deciduous::GraphPatch::load(&file) {
+            Ok(patch) => {
+                let done = applied
+                    .get(&patch.effective_digest())
+                    .is_some_and(|a| !a.is_partial());
+                if !done {
+                    files.push(file);
+                    patches.push(patch);
+                }
+            }
+            Err(e) => failed.push((file, Err(e))),
+        }
+    }
+
+    let order = deciduous::order_patches(&patches);
+    let mut slots: Vec<Option<(PathBuf, deciduous::GraphPatch)>> =
+        files.into_iter().zip(patches).map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|i| slots[i].take())
+        .map(|(file, patch)| (file, Ok(patch)))
+        .chain(failed)
+        .collect()
+}
+
+/// Find an applied patch by its file or by (a prefix of) its digest
+fn find_applied_patch(db: &Database, spec: &str) -> Result<deciduous::AppliedPatch, String> {
+    let applied = db.get_applied_patches().map_err(|e| e.to_string())?;
+    let path = std::path::Path::new(spec);
+    if path.is_file() {
+        let digest = deciduous::GraphPatch::load(path)?.effective_digest();
+        return applied
+            .into_iter()
+            .find(|a| a.digest == digest)
+            .ok_or_else(|| format!("{} is not recorded as applied", spec));
+    }
+
+    let mut matches: Vec<_> = applied
+        .into_iter()
+        .filter(|a| a.digest.starts_with(spec))
+        .collect();
+    match matches.len() {
+        0 => Err(format!(
+            "No applied patch file or digest matches '{}' (see 'deciduous diff status')",
+            spec
+        )),
+        1 => Ok(matches.remove(0)),
+        n => Err(format!(
+            "'{}' matches {} applied patches; use a longer digest",
+            spec, n
+        )),
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -2731,6 +5091,20 @@ fn export_git_history(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_shell_join_quotes_when_needed() {
+        let args: Vec<String> = ["cargo", "test", "--", "--nocapture", "it's a test", ""]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            shell_join(&args),
+            "cargo test -- --nocapture 'it'\\''s a test' ''"
+        );
+        assert_eq!(format_duration(850), "850ms");
+        assert_eq!(format_duration(12_345), "12.3s");
+    }
+
     // === keyword_match_score Tests ===
 
     #[test]
@@ -0,0 +1,630 @@
+//! Model Context Protocol server over stdio
+//!
+//! `deciduous mcp` → reads JSON-RPC 2.0 messages from stdin, one per line, and
+//! writes one response line per request to stdout. Assistants call the tools
+//! below instead of shelling out to the CLI and parsing its output. Stdout
+//! carries only protocol messages; diagnostics go to stderr.
+
+use crate::db::{Database, DecisionGraph, DecisionNode, SearchFilters};
+use crate::export::filter_graph_from_roots;
+use crate::tui::state::{build_chains, get_recent_chains};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+/// MCP revision this server implements
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Chains listed by the `deciduous://chains/recent` resource
+const RECENT_CHAINS: usize = 10;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const RESOURCE_NOT_FOUND: i64 = -32002;
+
+/// Arguments of the `add_node` tool
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct AddNodeArgs {
+    /// goal, decision, option, action, outcome, observation or a configured type
+    node_type: String,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    /// Confidence 0-100
+    #[serde(default)]
+    confidence: Option<u8>,
+    /// Verbatim user prompt that led to this node
+    #[serde(default)]
+    prompt: Option<String>,
+    /// Comma-separated files the node concerns
+    #[serde(default)]
+    files: Option<String>,
+    /// Git branch (defaults to the current branch)
+    #[serde(default)]
+    branch: Option<String>,
+}
+
+/// Arguments of the `link` tool
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct LinkArgs {
+    from: i32,
+    to: i32,
+    /// leads_to (default), requires, chosen, rejected, blocks, enables or a configured type
+    #[serde(default)]
+    edge_type: Option<String>,
+    #[serde(default)]
+    rationale: Option<String>,
+}
+
+/// Arguments of the `set_status` tool
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct SetStatusArgs {
+    node_id: i32,
+    /// pending, active, completed, rejected or a configured status
+    status: String,
+}
+
+/// Arguments of the `search` tool
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct SearchArgs {
+    /// Words to match against titles, descriptions, prompts and files
+    query: String,
+    #[serde(default)]
+    node_type: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    branch: Option<String>,
+    /// All of these tags must be present
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Maximum number of hits (default 20)
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// Arguments of the `get_subgraph` tool
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct GetSubgraphArgs {
+    /// Nodes whose descendants to return
+    root_ids: Vec<i32>,
+    /// Also return context attached to the nodes
+    #[serde(default)]
+    include_context: bool,
+}
+
+/// A tool's name, description and input schema
+struct Tool {
+    name: &'static str,
+    description: &'static str,
+    input_schema: fn() -> Value,
+}
+
+fn input_schema<T: JsonSchema>() -> Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(T)).unwrap_or_default();
+    if let Some(schema) = schema.as_object_mut() {
+        schema.remove("$schema");
+        schema.remove("title");
+    }
+    schema
+}
+
+const TOOLS: &[Tool] = &[
+    Tool {
+        name: "add_node",
+        description: "Add a node to the decision graph. Returns the created node.",
+        input_schema: input_schema::<AddNodeArgs>,
+    },
+    Tool {
+        name: "link",
+        description: "Connect two nodes with an edge. Returns the edge id.",
+        input_schema: input_schema::<LinkArgs>,
+    },
+    Tool {
+        name: "set_status",
+        description: "Change a node's status. Returns the updated node.",
+        input_schema: input_schema::<SetStatusArgs>,
+    },
+    Tool {
+        name: "search",
+        description: "Full-text search over nodes, best matches first.",
+        input_schema: input_schema::<SearchArgs>,
+    },
+    Tool {
+        name: "get_subgraph",
+        description: "Nodes reachable from the given roots, with the edges between them.",
+        input_schema: input_schema::<GetSubgraphArgs>,
+    },
+];
+
+/// URIs and descriptions of the readable resources
+const RESOURCES: &[(&str, &str, &str)] = &[
+    (
+        "deciduous://chains/recent",
+        "Recent chains",
+        "The most recently updated goals with all their descendants",
+    ),
+    (
+        "deciduous://graph",
+        "Decision graph",
+        "Every node and edge (the same data as graph-data.json)",
+    ),
+];
+
+/// A JSON-RPC error response
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Serves MCP requests against a database
+pub struct McpServer {
+    db: Database,
+}
+
+impl McpServer {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Answer requests from `input` until it closes
+    pub fn serve(&self, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                writeln!(output, "{}", response)?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one message; notifications get no response
+    pub fn handle_line(&self, line: &str) -> Option<String> {
+        let response = match serde_json::from_str::<Value>(line) {
+            Ok(message) => self.handle(&message)?,
+            Err(e) => error_response(
+                Value::Null,
+                RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)),
+            ),
+        };
+        Some(response.to_string())
+    }
+
+    /// Handle one parsed message; notifications get no response
+    pub fn handle(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Some(error_response(
+                id.unwrap_or(Value::Null),
+                RpcError::new(INVALID_REQUEST, "Invalid request: missing method"),
+            ));
+        };
+        // Notifications (no id) such as notifications/initialized need no reply
+        let id = id?;
+        let params = message.get("params").cloned().unwrap_or(json!({}));
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {}, "resources": {} },
+                "serverInfo": { "name": "deciduous", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({
+                "tools": TOOLS.iter().map(|t| json!({
+                    "name": t.name,
+                    "description": t.description,
+                    "inputSchema": (t.input_schema)(),
+                })).collect::<Vec<_>>(),
+            })),
+            "tools/call" => self.call_tool(&params),
+            "resources/list" => Ok(json!({
+                "resources": RESOURCES.iter().map(|(uri, name, description)| json!({
+                    "uri": uri,
+                    "name": name,
+                    "description": description,
+                    "mimeType": "application/json",
+                })).collect::<Vec<_>>(),
+            })),
+            "resources/read" => self.read_resource(&params),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method not found: {}", method),
+            )),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => error_response(id, e),
+        })
+    }
+
+    /// Run a tool. Failures of the tool itself (unknown node, bad type) are
+    /// reported in the result with `isError` so the model can see and fix them.
+    fn call_tool(&self, params: &Value) -> Result<Value, RpcError> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing tool name"))?;
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        let outcome = match name {
+            "add_node" => self.add_node(parse_args(name, arguments)?),
+            "link" => self.link(parse_args(name, arguments)?),
+            "set_status" => self.set_status(parse_args(name, arguments)?),
+            "search" => self.search(parse_args(name, arguments)?),
+            "get_subgraph" => self.get_subgraph(parse_args(name, arguments)?),
+            _ => {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    format!("Unknown tool: {}", name),
+                ))
+            }
+        };
+
+        Ok(match outcome {
+            Ok(value) => json!({
+                "content": [{ "type": "text", "text": pretty(&value) }],
+                "isError": false,
+            }),
+            Err(message) => json!({
+                "content": [{ "type": "text", "text": message }],
+                "isError": true,
+            }),
+        })
+    }
+
+    fn add_node(&self, args: AddNodeArgs) -> Result<Value, String> {
+        let branch = args.branch.or_else(crate::db::get_current_git_branch);
+        let id = self
+            .db
+            .create_node_full(
+                &args.node_type,
+                &args.title,
+                args.description.as_deref(),
+                args.confidence.map(|c| c.min(100)),
+                None,
+                args.prompt.as_deref(),
+                args.files.as_deref(),
+                branch.as_deref(),
+            )
+            .map_err(|e| e.to_string())?;
+        self.node_json(id)
+    }
+
+    fn link(&self, args: LinkArgs) -> Result<Value, String> {
+        let edge_type = args.edge_type.as_deref().unwrap_or("leads_to");
+        let id = self
+            .db
+            .create_edge(args.from, args.to, edge_type, args.rationale.as_deref())
+            .map_err(|e| e.to_string())?;
+        Ok(json!({ "id": id, "from": args.from, "to": args.to, "edge_type": edge_type }))
+    }
+
+    fn set_status(&self, args: SetStatusArgs) -> Result<Value, String> {
+        self.require_node(args.node_id)?;
+        self.db
+            .update_node_status(args.node_id, &args.status)
+            .map_err(|e| e.to_string())?;
+        self.node_json(args.node_id)
+    }
+
+    fn search(&self, args: SearchArgs) -> Result<Value, String> {
+        let filters = SearchFilters {
+            node_type: args.node_type,
+            status: args.status,
+            branch: args.branch,
+            tags: args.tags,
+            limit: Some(args.limit.unwrap_or(20)),
+        };
+        let hits = self
+            .db
+            .search(&args.query, &filters)
+            .map_err(|e| e.to_string())?;
+        serde_json::to_value(hits).map_err(|e| e.to_string())
+    }
+
+    fn get_subgraph(&self, args: GetSubgraphArgs) -> Result<Value, String> {
+        for &id in &args.root_ids {
+            self.require_node(id)?;
+        }
+        let mut graph = self.graph()?;
+        if args.include_context {
+            graph.context = self.db.get_all_context().map_err(|e| e.to_string())?;
+        }
+        serde_json::to_value(filter_graph_from_roots(&graph, &args.root_ids))
+            .map_err(|e| e.to_string())
+    }
+
+    fn read_resource(&self, params: &Value) -> Result<Value, RpcError> {
+        let uri = params
+            .get("uri")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing resource uri"))?;
+        let contents = match uri {
+            "deciduous://chains/recent" => self.recent_chains(),
+            "deciduous://graph" => self
+                .graph()
+                .and_then(|g| serde_json::to_value(g).map_err(|e| e.to_string())),
+            _ => {
+                return Err(RpcError::new(
+                    RESOURCE_NOT_FOUND,
+                    format!("Resource not found: {}", uri),
+                ))
+            }
+        }
+        .map_err(|e| RpcError::new(INTERNAL_ERROR, e))?;
+
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": "application/json",
+                "text": pretty(&contents),
+            }],
+        }))
+    }
+
+    fn recent_chains(&self) -> Result<Value, String> {
+        let graph = self.graph()?;
+        let chains = build_chains(&graph.nodes, &graph.edges);
+        Ok(get_recent_chains(&chains, RECENT_CHAINS)
+            .into_iter()
+            .map(|chain| {
+                json!({
+                    "root": chain.root,
+                    "last_updated": chain.last_updated().to_rfc3339(),
+                    "nodes": chain.nodes,
+                })
+            })
+            .collect())
+    }
+
+    fn graph(&self) -> Result<DecisionGraph, String> {
+        self.db.get_graph().map_err(|e| e.to_string())
+    }
+
+    fn require_node(&self, id: i32) -> Result<DecisionNode, String> {
+        match self.db.get_node(id) {
+            Ok(Some(node)) => Ok(node),
+            Ok(None) => Err(format!(
+                "Node {} does not exist. Use search to find existing nodes.",
+                id
+            )),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn node_json(&self, id: i32) -> Result<Value, String> {
+        serde_json::to_value(self.require_node(id)?).map_err(|e| e.to_string())
+    }
+}
+
+fn parse_args<T: DeserializeOwned>(tool: &str, arguments: Value) -> Result<T, RpcError> {
+    serde_json::from_value(arguments).map_err(|e| {
+        RpcError::new(
+            INVALID_PARAMS,
+            format!("Invalid arguments for {}: {}", tool, e),
+        )
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> (tempfile::TempDir, McpServer) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("test.db").to_str().unwrap()).unwrap();
+        (dir, McpServer::new(db))
+    }
+
+    /// Feed a script of requests through `serve` and parse the response lines
+    fn run_script(server: &McpServer, requests: &[Value]) -> Vec<Value> {
+        let input: String = requests.iter().map(|r| format!("{}\n", r)).collect();
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    fn call(id: i64, tool: &str, arguments: Value) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": tool, "arguments": arguments },
+        })
+    }
+
+    fn tool_output(response: &Value) -> Value {
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_handshake_and_listing() {
+        let (_dir, server) = server();
+        let responses = run_script(
+            &server,
+            &[
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+                json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+                json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+                json!({"jsonrpc": "2.0", "id": 3, "method": "resources/list"}),
+                json!({"jsonrpc": "2.0", "id": 4, "method": "bogus"}),
+            ],
+        );
+
+        // The notification gets no response
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["result"]["protocolVersion"], PROTOCOL_VERSION);
+        let tools: Vec<&str> = responses[1]["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            tools,
+            vec!["add_node", "link", "set_status", "search", "get_subgraph"]
+        );
+        let schema = &responses[1]["result"]["tools"][0]["inputSchema"];
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["node_type", "title"]));
+        assert_eq!(
+            responses[2]["result"]["resources"][0]["uri"],
+            "deciduous://chains/recent"
+        );
+        assert_eq!(responses[3]["id"], 4);
+        assert_eq!(responses[3]["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_tools_drive_the_database() {
+        let (_dir, server) = server();
+        let responses = run_script(
+            &server,
+            &[
+                call(
+                    1,
+                    "add_node",
+                    json!({"node_type": "goal", "title": "Add caching"}),
+                ),
+                call(
+                    2,
+                    "add_node",
+                    json!({"node_type": "action", "title": "Cache responses", "confidence": 80}),
+                ),
+                call(
+                    3,
+                    "link",
+                    json!({"from": 1, "to": 2, "rationale": "First step"}),
+                ),
+                call(
+                    4,
+                    "set_status",
+                    json!({"node_id": 2, "status": "completed"}),
+                ),
+                call(5, "search", json!({"query": "cache"})),
+                call(6, "get_subgraph", json!({"root_ids": [1]})),
+            ],
+        );
+
+        assert_eq!(tool_output(&responses[0])["id"], 1);
+        assert_eq!(tool_output(&responses[1])["node_type"], "action");
+        assert_eq!(tool_output(&responses[2])["edge_type"], "leads_to");
+        assert_eq!(tool_output(&responses[3])["status"], "completed");
+        let hits = tool_output(&responses[4]);
+        assert_eq!(hits.as_array().unwrap().len(), 2);
+        let subgraph = tool_output(&responses[5]);
+        assert_eq!(subgraph["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(subgraph["edges"].as_array().unwrap().len(), 1);
+        assert!(responses.iter().all(|r| r["result"]["isError"] == false));
+    }
+
+    #[test]
+    fn test_tool_errors_are_reported_to_the_model() {
+        let (_dir, server) = server();
+        let responses = run_script(
+            &server,
+            &[
+                call(1, "add_node", json!({"node_type": "gaol", "title": "Typo"})),
+                call(
+                    2,
+                    "set_status",
+                    json!({"node_id": 7, "status": "completed"}),
+                ),
+                call(3, "link", json!({"from": "one"})),
+                call(4, "delete_everything", json!({})),
+            ],
+        );
+
+        // Database errors come back as tool results the model can act on
+        assert_eq!(responses[0]["result"]["isError"], true);
+        let text = responses[0]["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap();
+        assert!(text.contains("Did you mean 'goal'?"), "{}", text);
+        assert_eq!(responses[1]["result"]["isError"], true);
+        // Malformed calls are protocol errors
+        assert_eq!(responses[2]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(responses[3]["error"]["code"], INVALID_PARAMS);
+
+        let garbage = server.handle_line("{not json").unwrap();
+        assert!(garbage.contains(&PARSE_ERROR.to_string()));
+    }
+
+    #[test]
+    fn test_read_recent_chains() {
+        let (_dir, server) = server();
+        let goal = server
+            .db
+            .create_node("goal", "Ship search", None, None, None)
+            .unwrap();
+        let action = server
+            .db
+            .create_node("action", "Add FTS index", None, None, None)
+            .unwrap();
+        server
+            .db
+            .create_edge(goal, action, "leads_to", None)
+            .unwrap();
+
+        let responses = run_script(
+            &server,
+            &[
+                json!({"jsonrpc": "2.0", "id": 1, "method": "resources/read",
+                       "params": {"uri": "deciduous://chains/recent"}}),
+                json!({"jsonrpc": "2.0", "id": 2, "method": "resources/read",
+                       "params": {"uri": "deciduous://nope"}}),
+            ],
+        );
+        let text = responses[0]["result"]["contents"][0]["text"]
+            .as_str()
+            .unwrap();
+        let chains: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(chains[0]["root"]["title"], "Ship search");
+        assert_eq!(chains[0]["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(responses[1]["error"]["code"], RESOURCE_NOT_FOUND);
+    }
+}
@@ -0,0 +1,637 @@
+//! Versioned schema migrations
+//!
+//! SQL lives in `migrations/NNNN_name/{up,down}.sql` and is embedded at compile time.
+//! Every applied migration is recorded in `schema_versions` with its zero-padded
+//! number as the `version`, so that table doubles as the migration history. The
+//! `x.y.z` row written for `CURRENT_SCHEMA` describes the feature set and is not a
+//! migration.
+//!
+//! Opening a database migrates it to the latest version, unless `deciduous migrate`
+//! rolled it back: that pins it (in `migration_pin`) until it is migrated up again.
+
+use crate::db::{DbError, Result, StoredSchema};
+use crate::schema::schema_versions;
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Text};
+use diesel::sqlite::SqliteConnection;
+use uuid::Uuid;
+
+/// A numbered schema change with its forward and reverse SQL
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    /// Tables this migration introduces (stored as the history row's `features`)
+    pub tables: &'static [&'static str],
+    up: &'static str,
+    down: &'static str,
+    /// Rust step run before `up`, for data fixes plain SQL can't express
+    before_up: Option<fn(&mut SqliteConnection) -> Result<()>>,
+}
+
+/// All migrations in order. Append new ones; never edit or renumber applied ones.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "decision_graph",
+        tables: &[
+            "decision_nodes",
+            "decision_edges",
+            "decision_context",
+            "decision_sessions",
+            "session_nodes",
+        ],
+        up: include_str!("../migrations/0001_decision_graph/up.sql"),
+        down: include_str!("../migrations/0001_decision_graph/down.sql"),
+        before_up: Some(add_legacy_change_ids),
+    },
+    Migration {
+        version: 2,
+        name: "command_log",
+        tables: &["command_log"],
+        up: include_str!("../migrations/0002_command_log/up.sql"),
+        down: include_str!("../migrations/0002_command_log/down.sql"),
+        before_up: None,
+    },
+    Migration {
+        version: 3,
+        name: "roadmap",
+        tables: &[
+            "roadmap_items",
+            "roadmap_sync_state",
+            "roadmap_conflicts",
+            "github_issue_cache",
+        ],
+        up: include_str!("../migrations/0003_roadmap/up.sql"),
+        down: include_str!("../migrations/0003_roadmap/down.sql"),
+        before_up: None,
+    },
+    Migration {
+        version: 4,
+        name: "tombstones_and_aliases",
+        tables: &["decision_tombstones", "change_id_aliases"],
+        up: include_str!("../migrations/0004_tombstones_and_aliases/up.sql"),
+        down: include_str!("../migrations/0004_tombstones_and_aliases/down.sql"),
+        before_up: None,
+    },
+    Migration {
+        version: 5,
+        name: "node_search",
+        tables: &["decision_nodes_fts"],
+        up: include_str!("../migrations/0005_node_search/up.sql"),
+        down: include_str!("../migrations/0005_node_search/down.sql"),
+        before_up: None,
+    },
+    Migration {
+        version: 6,
+        name: "node_sync",
+        tables: &["node_sync_bases", "node_conflicts"],
+        up: include_str!("../migrations/0006_node_sync/up.sql"),
+        down: include_str!("../migrations/0006_node_sync/down.sql"),
+        before_up: None,
+    },
+    Migration {
+        version: 7,
+        name: "patch_exports",
+        tables: &["patch_exports"],
+        up: include_str!("../migrations/0007_patch_exports/up.sql"),
+        down: include_str!("../migrations/0007_patch_exports/down.sql"),
+        before_up: None,
+    },
+    Migration {
+        version: 8,
+        name: "applied_patches",
+        tables: &["applied_patches", "applied_patch_items"],
+        up: include_str!("../migrations/0008_applied_patches/up.sql"),
+        down: include_str!("../migrations/0008_applied_patches/down.sql"),
+        before_up: None,
+    },
+    Migration {
+        version: 9,
+        name: "authors",
+        tables: &[],
+        up: include_str!("../migrations/0009_authors/up.sql"),
+        down: include_str!("../migrations/0009_authors/down.sql"),
+        before_up: None,
+    },
+    Migration {
+        version: 10,
+        name: "sync_ancestry",
+        tables: &[],
+        up: include_str!("../migrations/0010_sync_ancestry/up.sql"),
+        down: include_str!("../migrations/0010_sync_ancestry/down.sql"),
+        before_up: None,
+    },
+    Migration {
+        version: 11,
+        name: "export_filters",
+        tables: &[],
+        up: include_str!("../migrations/0011_export_filters/up.sql"),
+        down: include_str!("../migrations/0011_export_filters/down.sql"),
+        before_up: None,
+    },
+];
+
+/// Highest migration this binary knows about
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// Which way a migration was run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+/// A migration that was applied or reverted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStep {
+    pub version: u32,
+    pub name: &'static str,
+    pub direction: Direction,
+}
+
+/// One row of `deciduous migrate --status`
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MigrationEntry {
+    pub version: u32,
+    pub name: String,
+    /// When it was applied, or None if pending
+    pub applied_at: Option<String>,
+    /// False for migrations recorded by a newer deciduous
+    pub known: bool,
+}
+
+/// Applied and pending migrations for a database
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MigrationStatus {
+    /// Highest applied migration (0 = none)
+    pub current: u32,
+    /// Highest migration this binary knows about
+    pub latest: u32,
+    /// Version an explicit rollback left the database at, if it is still pinned
+    pub pinned: Option<u32>,
+    pub entries: Vec<MigrationEntry>,
+}
+
+/// Applied migrations as (version, name, applied_at), oldest first
+fn applied(conn: &mut SqliteConnection) -> Result<Vec<(u32, String, String)>> {
+    conn.batch_execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_versions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+            version TEXT NOT NULL UNIQUE,
+            name TEXT NOT NULL,
+            features TEXT NOT NULL,
+            introduced_at TEXT NOT NULL
+        )
+        "#,
+    )?;
+
+    let rows: Vec<StoredSchema> = schema_versions::table
+        .select(StoredSchema::as_select())
+        .load(conn)?;
+    let mut versions: Vec<(u32, String, String)> = rows
+        .into_iter()
+        .filter_map(|r| Some((r.version.parse().ok()?, r.name, r.introduced_at)))
+        .collect();
+    versions.sort_by_key(|(v, _, _)| *v);
+    Ok(versions)
+}
+
+/// Fail if the database was migrated by a newer deciduous
+fn check_not_newer(current: u32) -> Result<()> {
+    let latest = latest_version();
+    if current > latest {
+        return Err(DbError::Validation(format!(
+            "Database is at schema migration {} but this deciduous only supports up to {}. \
+             Upgrade deciduous to open it.",
+            current, latest
+        )));
+    }
+    Ok(())
+}
+
+/// Report applied and pending migrations
+pub fn status(conn: &mut SqliteConnection) -> Result<MigrationStatus> {
+    let applied = applied(conn)?;
+    let mut entries: Vec<MigrationEntry> = MIGRATIONS
+        .iter()
+        .map(|m| MigrationEntry {
+            version: m.version,
+            name: m.name.to_string(),
+            applied_at: applied
+                .iter()
+                .find(|(v, _, _)| *v == m.version)
+                .map(|(_, _, at)| at.clone()),
+            known: true,
+        })
+        .collect();
+    for (version, name, at) in &applied {
+        if !MIGRATIONS.iter().any(|m| m.version == *version) {
+            entries.push(MigrationEntry {
+                version: *version,
+                name: name.clone(),
+                applied_at: Some(at.clone()),
+                known: false,
+            });
+        }
+    }
+    entries.sort_by_key(|e| e.version);
+
+    Ok(MigrationStatus {
+        current: applied.last().map_or(0, |(v, _, _)| *v),
+        latest: latest_version(),
+        pinned: pinned_version(conn)?,
+        entries,
+    })
+}
+
+/// Apply or revert migrations until the database is at `target`.
+///
+/// Runs in a single immediate transaction, so a failing migration leaves the
+/// database untouched. Refuses to touch a database newer than this binary.
+/// Reaching the latest migration lifts any pin.
+pub fn migrate_to(conn: &mut SqliteConnection, target: u32) -> Result<Vec<MigrationStep>> {
+    if target > latest_version() {
+        return Err(DbError::Validation(format!(
+            "Unknown migration {}. The latest migration is {}.",
+            target,
+            latest_version()
+        )));
+    }
+
+    let is_pending = |applied: &[(u32, String, String)], m: &Migration| {
+        let done = applied.iter().any(|(v, _, _)| *v == m.version);
+        if m.version <= target {
+            !done
+        } else {
+            done
+        }
+    };
+
+    // Cheap read first so opening an up-to-date database doesn't take the write lock
+    let before = applied(conn)?;
+    check_not_newer(before.last().map_or(0, |(v, _, _)| *v))?;
+    if target == latest_version() && pinned_version(conn)?.is_some() {
+        set_pin(conn, None)?;
+    }
+    if !MIGRATIONS.iter().any(|m| is_pending(&before, m)) {
+        return Ok(Vec::new());
+    }
+
+    conn.immediate_transaction(|conn| {
+        let applied = applied(conn)?;
+        check_not_newer(applied.last().map_or(0, |(v, _, _)| *v))?;
+        let now = chrono::Local::now().to_rfc3339();
+        let mut steps = Vec::new();
+
+        for m in MIGRATIONS.iter().rev().filter(|m| m.version > target) {
+            if !is_pending(&applied, m) {
+                continue;
+            }
+            conn.batch_execute(m.down)?;
+            diesel::delete(
+                schema_versions::table
+                    .filter(schema_versions::version.eq(format!("{:04}", m.version))),
+            )
+            .execute(conn)?;
+            steps.push(MigrationStep {
+                version: m.version,
+                name: m.name,
+                direction: Direction::Down,
+            });
+        }
+
+        for m in MIGRATIONS.iter().filter(|m| m.version <= target) {
+            if !is_pending(&applied, m) {
+                continue;
+            }
+            if let Some(before_up) = m.before_up {
+                before_up(conn)?;
+            }
+            conn.batch_execute(m.up)?;
+            diesel::insert_into(schema_versions::table)
+                .values((
+                    schema_versions::version.eq(format!("{:04}", m.version)),
+                    schema_versions::name.eq(m.name),
+                    schema_versions::features
+                        .eq(serde_json::to_string(m.tables).unwrap_or_default()),
+                    schema_versions::introduced_at.eq(&now),
+                ))
+                .execute(conn)?;
+            steps.push(MigrationStep {
+                version: m.version,
+                name: m.name,
+                direction: Direction::Up,
+            });
+        }
+
+        Ok(steps)
+    })
+}
+
+/// Revert the most recently applied migration, if any, and pin the database there
+pub fn rollback_last(conn: &mut SqliteConnection) -> Result<Option<MigrationStep>> {
+    let applied = applied(conn)?;
+    let Some((current, _, _)) = applied.last() else {
+        return Ok(None);
+    };
+    check_not_newer(*current)?;
+    let previous = applied
+        .iter()
+        .rev()
+        .nth(1)
+        .map_or(0, |(version, _, _)| *version);
+    let step = migrate_to(conn, previous)?.into_iter().next();
+    set_pin(conn, Some(previous))?;
+    Ok(step)
+}
+
+/// Migrate to `target` on request, pinning the database there if it is below the
+/// latest migration
+pub fn migrate_pinned(conn: &mut SqliteConnection, target: u32) -> Result<Vec<MigrationStep>> {
+    let steps = migrate_to(conn, target)?;
+    if target < latest_version() {
+        set_pin(conn, Some(target))?;
+    }
+    Ok(steps)
+}
+
+/// Fail if `deciduous migrate` rolled the database back. Opening it would otherwise
+/// migrate it straight back up, or run against tables this binary doesn't expect.
+pub fn check_not_pinned(conn: &mut SqliteConnection) -> Result<()> {
+    match pinned_version(conn)? {
+        Some(pinned) => Err(DbError::Validation(format!(
+            "Database was rolled back to schema migration {} with 'deciduous migrate'. \
+             Run 'deciduous migrate' to bring it up to date again.",
+            pinned
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[derive(QueryableByName)]
+struct PinnedVersion {
+    #[diesel(sql_type = Integer)]
+    version: i32,
+}
+
+/// The migration an explicit rollback pinned the database at, if any.
+///
+/// Only reads: the table is created the first time something is pinned, so opening
+/// a database never writes to it.
+fn pinned_version(conn: &mut SqliteConnection) -> Result<Option<u32>> {
+    let table: Option<ColumnName> = diesel::sql_query(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'migration_pin'",
+    )
+    .get_result(conn)
+    .optional()?;
+    if table.is_none() {
+        return Ok(None);
+    }
+    let pin: Option<PinnedVersion> =
+        diesel::sql_query("SELECT version FROM migration_pin WHERE id = 1")
+            .get_result(conn)
+            .optional()?;
+    Ok(pin.map(|p| p.version as u32))
+}
+
+fn set_pin(conn: &mut SqliteConnection, version: Option<u32>) -> Result<()> {
+    conn.batch_execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS migration_pin (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            version INTEGER NOT NULL,
+            pinned_at TEXT NOT NULL
+        )
+        "#,
+    )?;
+    match version {
+        Some(version) => diesel::sql_query(
+            "INSERT OR REPLACE INTO migration_pin (id, version, pinned_at) VALUES (1, ?, ?)",
+        )
+        .bind::<Integer, _>(version as i32)
+        .bind::<Text, _>(chrono::Local::now().to_rfc3339())
+        .execute(conn)?,
+        None => diesel::sql_query("DELETE FROM migration_pin").execute(conn)?,
+    };
+    Ok(())
+}
+
+// ============================================================================
+// Rust migration steps
+// ============================================================================
+
+#[derive(QueryableByName)]
+struct ColumnName {
+    #[diesel(sql_type = Text)]
+    name: String,
+}
+
+#[derive(QueryableByName)]
+struct NodeId {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+}
+
+fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    let columns: Vec<ColumnName> =
+        diesel::sql_query(format!("PRAGMA table_info({})", table)).load(conn)?;
+    Ok(columns.into_iter().map(|c| c.name).collect())
+}
+
+/// Databases created before multi-user sync have decision tables without change_id
+/// columns. Add and backfill them so the `up` SQL (and its indexes) applies.
+fn add_legacy_change_ids(conn: &mut SqliteConnection) -> Result<()> {
+    let node_columns = table_columns(conn, "decision_nodes")?;
+    if node_columns.is_empty() {
+        return Ok(()); // Fresh database
+    }
+
+    if !node_columns.iter().any(|c| c == "change_id") {
+        conn.batch_execute("ALTER TABLE decision_nodes ADD COLUMN change_id TEXT")?;
+        let nodes: Vec<NodeId> = diesel::sql_query("SELECT id FROM decision_nodes").load(conn)?;
+        for node in nodes {
+            diesel::sql_query("UPDATE decision_nodes SET change_id = ? WHERE id = ?")
+                .bind::<Text, _>(Uuid::new_v4().to_string())
+                .bind::<Integer, _>(node.id)
+                .execute(conn)?;
+        }
+        conn.batch_execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_nodes_change_id_unique ON decision_nodes(change_id)",
+        )?;
+    }
+
+    let edge_columns = table_columns(conn, "decision_edges")?;
+    if !edge_columns.is_empty() && !edge_columns.iter().any(|c| c == "from_change_id") {
+        conn.batch_execute(
+            r#"
+            ALTER TABLE decision_edges ADD COLUMN from_change_id TEXT;
+            ALTER TABLE decision_edges ADD COLUMN to_change_id TEXT;
+            UPDATE decision_edges SET
+                from_change_id = (SELECT change_id FROM decision_nodes WHERE id = decision_edges.from_node_id),
+                to_change_id = (SELECT change_id FROM decision_nodes WHERE id = decision_edges.to_node_id);
+            "#,
+        )?;
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn connect(path: &std::path::Path) -> SqliteConnection {
+        SqliteConnection::establish(path.to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_versions_are_sequential() {
+        for (i, m) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(m.version as usize, i + 1, "{} is out of order", m.name);
+        }
+    }
+
+    #[test]
+    fn test_fresh_database_records_all_migrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        Database::new(path.to_str().unwrap()).unwrap();
+
+        let status = status(&mut connect(&path)).unwrap();
+        assert_eq!(status.current, latest_version());
+        assert!(status.entries.iter().all(|e| e.applied_at.is_some()));
+    }
+
+    #[test]
+    fn test_down_and_up_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let db = Database::new(path.to_str().unwrap()).unwrap();
+        db.create_node("goal", "Survives", None, None, None)
+            .unwrap();
+        drop(db);
+
+        let mut conn = connect(&path);
+        let step = rollback_last(&mut conn).unwrap().unwrap();
+        assert_eq!(step.version, latest_version());
+        assert_eq!(step.direction, Direction::Down);
+        assert_eq!(status(&mut conn).unwrap().current, latest_version() - 1);
+        for dropped in MIGRATIONS.last().unwrap().tables {
+            assert!(table_columns(&mut conn, dropped).unwrap().is_empty());
+        }
+        let export_columns = table_columns(&mut conn, "patch_exports").unwrap();
+        assert!(!export_columns.contains(&"filter".to_string()));
+
+        let steps = migrate_to(&mut conn, latest_version()).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].direction, Direction::Up);
+        assert!(migrate_to(&mut conn, latest_version()).unwrap().is_empty());
+
+        let db = Database::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(db.get_all_nodes().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rollback_pins_until_migrated_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        Database::new(path.to_str().unwrap()).unwrap();
+
+        let mut conn = connect(&path);
+        // Checking for a pin on open leaves the schema alone
+        assert_eq!(status(&mut conn).unwrap().pinned, None);
+        assert!(table_columns(&mut conn, "migration_pin")
+            .unwrap()
+            .is_empty());
+
+        rollback_last(&mut conn).unwrap();
+        let err = Database::new(path.to_str().unwrap()).err().unwrap();
+        assert!(err.to_string().contains("deciduous migrate"));
+        assert_eq!(status(&mut conn).unwrap().current, latest_version() - 1);
+
+        // Staying below latest keeps the pin; reaching latest lifts it
+        migrate_pinned(&mut conn, 1).unwrap();
+        assert_eq!(status(&mut conn).unwrap().pinned, Some(1));
+        migrate_pinned(&mut conn, latest_version()).unwrap();
+        assert_eq!(status(&mut conn).unwrap().pinned, None);
+        Database::new(path.to_str().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_refuses_newer_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        Database::new(path.to_str().unwrap()).unwrap();
+
+        connect(&path)
+            .batch_execute(
+                "INSERT INTO schema_versions (version, name, features, introduced_at)
+                 VALUES ('9999', 'from_the_future', '[]', '2030-01-01T00:00:00Z')",
+            )
+            .unwrap();
+
+        let err = Database::new(path.to_str().unwrap()).err().unwrap();
+        assert!(err.to_string().contains("Upgrade deciduous"));
+        let status = status(&mut connect(&path)).unwrap();
+        assert_eq!(status.current, 9999);
+        assert!(!status.entries.last().unwrap().known);
+    }
+
+    #[test]
+    fn test_unknown_target_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = connect(&dir.path().join("test.db"));
+        assert!(migrate_to(&mut conn, latest_version() + 1).is_err());
+    }
+
+    #[test]
+    fn test_adopts_legacy_database_without_change_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.db");
+        connect(&path)
+            .batch_execute(
+                r#"
+                CREATE TABLE decision_nodes (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                    node_type TEXT NOT NULL,
+                    title TEXT NOT NULL,
+                    description TEXT,
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    metadata_json TEXT
+                );
+                CREATE TABLE decision_edges (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                    from_node_id INTEGER NOT NULL,
+                    to_node_id INTEGER NOT NULL,
+                    edge_type TEXT NOT NULL,
+                    weight REAL DEFAULT 1.0,
+                    rationale TEXT,
+                    created_at TEXT NOT NULL
+                );
+                INSERT INTO decision_nodes (node_type, title, created_at, updated_at)
+                    VALUES ('goal', 'Old goal', 't', 't'), ('action', 'Old action', 't', 't');
+                INSERT INTO decision_edges (from_node_id, to_node_id, edge_type, created_at)
+                    VALUES (1, 2, 'leads_to', 't');
+                "#,
+            )
+            .unwrap();
+
+        let db = Database::new(path.to_str().unwrap()).unwrap();
+        let nodes = db.get_all_nodes().unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes.iter().all(|n| !n.change_id.is_empty()));
+        let edges = db.get_all_edges().unwrap();
+        assert_eq!(edges[0].from_change_id.as_ref(), Some(&nodes[0].change_id));
+    }
+}
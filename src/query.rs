@@ -0,0 +1,591 @@
+//! Graph query language for `deciduous query`
+//!
+//! A query is a list of whitespace-separated terms that must all match:
+//!
+//! ```text
+//! type:decision status:pending branch:feature/* created:>2025-01-01
+//! confidence:<50 descendants-of:12 has:commit -tag:wontfix "cache layer"
+//! ```
+//!
+//! Prefix a term with `-` to negate it. Comma-separated values match any of them
+//! (`type:goal,decision`). Bare words match the title or description.
+
+use crate::db::{edit_distance, DecisionGraph, DecisionNode};
+use crate::export::parse_node_range;
+use std::collections::{HashMap, HashSet};
+
+/// Keys understood by the query language
+pub const QUERY_KEYS: [&str; 13] = [
+    "type",
+    "status",
+    "branch",
+    "tag",
+    "attr",
+    "title",
+    "id",
+    "confidence",
+    "created",
+    "updated",
+    "has",
+    "descendants-of",
+    "ancestors-of",
+];
+
+/// Fields accepted by `has:`
+pub const HAS_FIELDS: [&str; 9] = [
+    "commit",
+    "prompt",
+    "files",
+    "description",
+    "branch",
+    "confidence",
+    "tags",
+    "parent",
+    "children",
+];
+
+/// Error parsing a query
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryError {
+    /// Unbalanced quotes or an empty term
+    Syntax(String),
+    /// `key:` is not a known filter
+    UnknownKey(String),
+    /// The value doesn't fit the key
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::Syntax(msg) => write!(f, "Query syntax error: {}", msg),
+            QueryError::UnknownKey(key) => {
+                let suggestion = QUERY_KEYS
+                    .iter()
+                    .map(|k| (edit_distance(key, k), *k))
+                    .filter(|(d, _)| *d <= 2)
+                    .min_by_key(|(d, _)| *d)
+                    .map(|(_, k)| format!(" Did you mean '{}'?", k))
+                    .unwrap_or_default();
+                write!(
+                    f,
+                    "Unknown query key '{}'.{} Valid keys: {}",
+                    key,
+                    suggestion,
+                    QUERY_KEYS.join(", ")
+                )
+            }
+            QueryError::InvalidValue { key, value, reason } => {
+                write!(f, "Invalid value '{}' for '{}': {}", value, key, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Comparison operator for numeric and date terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    /// Split a leading operator off `value` (no operator means equality)
+    fn split(value: &str) -> (Cmp, &str) {
+        for (prefix, cmp) in [
+            (">=", Cmp::Ge),
+            ("<=", Cmp::Le),
+            (">", Cmp::Gt),
+            ("<", Cmp::Lt),
+            ("=", Cmp::Eq),
+        ] {
+            if let Some(rest) = value.strip_prefix(prefix) {
+                return (cmp, rest);
+            }
+        }
+        (Cmp::Eq, value)
+    }
+
+    fn test<T: PartialOrd + ?Sized>(self, left: &T, right: &T) -> bool {
+        match self {
+            Cmp::Eq => left == right,
+            Cmp::Lt => left < right,
+            Cmp::Le => left <= right,
+            Cmp::Gt => left > right,
+            Cmp::Ge => left >= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Type(Vec<String>),
+    Status(Vec<String>),
+    Branch(Vec<String>),
+    Tag(Vec<String>),
+    Attr(String, Option<String>),
+    Title(String),
+    Text(String),
+    Ids(Vec<i32>),
+    Id(Cmp, i32),
+    Confidence(Cmp, u8),
+    Created(Cmp, String),
+    Updated(Cmp, String),
+    Has(String),
+    DescendantsOf(i32),
+    AncestorsOf(i32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    negated: bool,
+    filter: Filter,
+}
+
+/// A parsed query; see the module docs for the syntax
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+impl std::str::FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, QueryError> {
+        Query::parse(s)
+    }
+}
+
+impl Query {
+    /// Parse a query string. An empty query matches every node.
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let terms = tokenize(input)?
+            .into_iter()
+            .map(|token| parse_term(&token))
+            .collect::<Result<_, _>>()?;
+        Ok(Query { terms })
+    }
+
+    /// Nodes of `graph` matching every term, in graph order
+    pub fn evaluate<'g>(&self, graph: &'g DecisionGraph) -> Vec<&'g DecisionNode> {
+        let mut parents: HashMap<i32, Vec<i32>> = HashMap::new();
+        let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+        for edge in &graph.edges {
+            children
+                .entry(edge.from_node_id)
+                .or_default()
+                .push(edge.to_node_id);
+            parents
+                .entry(edge.to_node_id)
+                .or_default()
+                .push(edge.from_node_id);
+        }
+
+        // Traversal terms are resolved once up front
+        let reachable: Vec<Option<HashSet<i32>>> = self
+            .terms
+            .iter()
+            .map(|term| match term.filter {
+                Filter::DescendantsOf(id) => Some(reachable_from(id, &children)),
+                Filter::AncestorsOf(id) => Some(reachable_from(id, &parents)),
+                _ => None,
+            })
+            .collect();
+
+        graph
+            .nodes
+            .iter()
+            .filter(|node| {
+                self.terms.iter().zip(&reachable).all(|(term, reach)| {
+                    let hit = match reach {
+                        Some(set) => set.contains(&node.id),
+                        None => term.filter.matches(node, &parents, &children),
+                    };
+                    hit != term.negated
+                })
+            })
+            .collect()
+    }
+
+    /// `graph` reduced to the matching nodes and the edges between them
+    pub fn filter_graph(&self, graph: &DecisionGraph) -> DecisionGraph {
+        let ids: Vec<i32> = self.evaluate(graph).iter().map(|n| n.id).collect();
+        crate::export::filter_graph_by_ids(graph, &ids)
+    }
+}
+
+impl Filter {
+    fn matches(
+        &self,
+        node: &DecisionNode,
+        parents: &HashMap<i32, Vec<i32>>,
+        children: &HashMap<i32, Vec<i32>>,
+    ) -> bool {
+        match self {
+            Filter::Type(types) => types.contains(&node.node_type),
+            Filter::Status(statuses) => statuses.contains(&node.status),
+            Filter::Branch(globs) => node
+                .branch()
+                .is_some_and(|b| globs.iter().any(|g| glob_match(g, &b))),
+            Filter::Tag(tags) => {
+                let node_tags = node.tags();
+                tags.iter().any(|t| node_tags.contains(t))
+            }
+            Filter::Attr(key, value) => {
+                let attributes = node.metadata().attributes;
+                match (attributes.get(key), value) {
+                    (Some(actual), Some(expected)) => glob_match(expected, actual),
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                }
+            }
+            Filter::Title(text) => contains_ci(&node.title, text),
+            Filter::Text(text) => {
+                contains_ci(&node.title, text)
+                    || node
+                        .description
+                        .as_deref()
+                        .is_some_and(|d| contains_ci(d, text))
+            }
+            Filter::Ids(ids) => ids.contains(&node.id),
+            Filter::Id(cmp, id) => cmp.test(&node.id, id),
+            Filter::Confidence(cmp, value) => {
+                node.confidence().is_some_and(|c| cmp.test(&c, value))
+            }
+            Filter::Created(cmp, date) => compare_date(&node.created_at, *cmp, date),
+            Filter::Updated(cmp, date) => compare_date(&node.updated_at, *cmp, date),
+            Filter::Has(field) => match field.as_str() {
+                "commit" => node.commit().is_some_and(|c| !c.is_empty()),
+                "prompt" => node.prompt().is_some_and(|p| !p.is_empty()),
+                "files" => !node.files().is_empty(),
+                "description" => node.description.as_deref().is_some_and(|d| !d.is_empty()),
+                "branch" => node.branch().is_some(),
+                "confidence" => node.confidence().is_some(),
+                "tags" => !node.tags().is_empty(),
+                "parent" => parents.contains_key(&node.id),
+                "children" => children.contains_key(&node.id),
+                _ => false,
+            },
+            // Resolved in `Query::evaluate`
+            Filter::DescendantsOf(_) | Filter::AncestorsOf(_) => false,
+        }
+    }
+}
+
+/// Split on whitespace outside double quotes, dropping the quotes
+fn tokenize(input: &str) -> Result<Vec<String>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() || quoted {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                quoted = false;
+            }
+            c => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(QueryError::Syntax("unterminated quote".to_string()));
+    }
+    if !current.is_empty() || quoted {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+fn parse_term(token: &str) -> Result<Term, QueryError> {
+    let (negated, body) = match token.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token),
+    };
+    if body.is_empty() {
+        return Err(QueryError::Syntax("empty search term".to_string()));
+    }
+
+    let Some((key, value)) = body.split_once(':') else {
+        return Ok(Term {
+            negated,
+            filter: Filter::Text(body.to_string()),
+        });
+    };
+
+    let invalid = |reason: &str| QueryError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        reason: reason.to_string(),
+    };
+    if value.is_empty() {
+        return Err(invalid("missing value"));
+    }
+    let list = || value.split(',').map(str::to_string).collect::<Vec<_>>();
+    let node_id = |v: &str| v.parse::<i32>().map_err(|_| invalid("expected a node ID"));
+
+    let filter = match key {
+        "type" => Filter::Type(list()),
+        "status" => Filter::Status(list()),
+        "branch" => Filter::Branch(list()),
+        "tag" => Filter::Tag(list()),
+        "attr" => match value.split_once('=') {
+            Some((k, v)) => Filter::Attr(k.to_string(), Some(v.to_string())),
+            None => Filter::Attr(value.to_string(), None),
+        },
+        "title" => Filter::Title(value.to_string()),
+        "id" => match Cmp::split(value) {
+            (Cmp::Eq, rest) if !value.starts_with('=') => {
+                let ids = parse_node_range(rest);
+                if ids.is_empty() {
+                    return Err(invalid("expected IDs such as 5, 1-10 or >20"));
+                }
+                Filter::Ids(ids)
+            }
+            (cmp, rest) => Filter::Id(cmp, node_id(rest)?),
+        },
+        "confidence" => {
+            let (cmp, rest) = Cmp::split(value);
+            let n = rest
+                .parse::<u8>()
+                .ok()
+                .filter(|n| *n <= 100)
+                .ok_or_else(|| invalid("expected 0-100, optionally prefixed by <, <=, >, >="))?;
+            Filter::Confidence(cmp, n)
+        }
+        "created" | "updated" => {
+            let (cmp, rest) = Cmp::split(value);
+            let date = parse_date(rest)
+                .ok_or_else(|| invalid("expected YYYY, YYYY-MM, YYYY-MM-DD or Nd (days ago)"))?;
+            if key == "created" {
+                Filter::Created(cmp, date)
+            } else {
+                Filter::Updated(cmp, date)
+            }
+        }
+        "has" => {
+            if !HAS_FIELDS.contains(&value) {
+                return Err(invalid(&format!(
+                    "expected one of {}",
+                    HAS_FIELDS.join(", ")
+                )));
+            }
+            Filter::Has(value.to_string())
+        }
+        "descendants-of" => Filter::DescendantsOf(node_id(value)?),
+        "ancestors-of" => Filter::AncestorsOf(node_id(value)?),
+        _ => return Err(QueryError::UnknownKey(key.to_string())),
+    };
+
+    Ok(Term { negated, filter })
+}
+
+/// Accept a date prefix (YYYY, YYYY-MM, YYYY-MM-DD) or `Nd` for N days ago
+fn parse_date(value: &str) -> Option<String> {
+    if let Some(days) = value.strip_suffix('d').and_then(|n| n.parse::<i64>().ok()) {
+        let date = chrono::Local::now().date_naive() - chrono::Duration::days(days);
+        return Some(date.format("%Y-%m-%d").to_string());
+    }
+    let valid = match value.len() {
+        4 => value.parse::<u16>().is_ok(),
+        7 => chrono::NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d").is_ok(),
+        10 => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+        _ => false,
+    };
+    valid.then(|| value.to_string())
+}
+
+/// Compare an RFC 3339 timestamp against a date prefix at the prefix's precision
+fn compare_date(timestamp: &str, cmp: Cmp, date: &str) -> bool {
+    timestamp
+        .get(..date.len())
+        .is_some_and(|prefix| cmp.test(prefix, date))
+}
+
+fn contains_ci(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if text.len() < first.len() + last.len() || !text.starts_with(first) || !text.ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Nodes reachable from `start` through `adjacency`, excluding `start` itself
+fn reachable_from(start: i32, adjacency: &HashMap<i32, Vec<i32>>) -> HashSet<i32> {
+    let mut seen = HashSet::new();
+    let mut to_visit = adjacency.get(&start).cloned().unwrap_or_default();
+    while let Some(id) = to_visit.pop() {
+        if id != start && seen.insert(id) {
+            if let Some(next) = adjacency.get(&id) {
+                to_visit.extend(next);
+            }
+        }
+    }
+    seen
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{edge, graph, node_at};
+
+    fn node(id: i32, node_type: &str, status: &str, created: &str, meta: &str) -> DecisionNode {
+        DecisionNode {
+            metadata_json: Some(meta.to_string()),
+            ..node_at(id, node_type, status, created)
+        }
+    }
+
+    /// 1 -> 2 -> 3, 1 -> 4, 5 isolated
+    fn sample_graph() -> DecisionGraph {
+        let mut graph = graph(
+            vec![
+                node(
+                    1,
+                    "goal",
+                    "pending",
+                    "2024-12-01T10:00:00Z",
+                    r#"{"confidence": 90, "branch": "main"}"#,
+                ),
+                node(
+                    2,
+                    "decision",
+                    "pending",
+                    "2025-01-05T10:00:00Z",
+                    r#"{"confidence": 40, "branch": "feature/cache", "tags": ["perf"]}"#,
+                ),
+                node(
+                    3,
+                    "action",
+                    "completed",
+                    "2025-02-01T10:00:00Z",
+                    r#"{"commit": "abc1234", "branch": "feature/cache", "attributes": {"owner": "sam"}}"#,
+                ),
+                node(4, "decision", "rejected", "2025-02-10T10:00:00Z", r#"{}"#),
+                node(5, "observation", "pending", "2025-03-01T10:00:00Z", r#"{}"#),
+            ],
+            vec![edge(1, 2), edge(2, 3), edge(1, 4)],
+        );
+        graph.nodes[2].title = "Add cache layer".to_string();
+        graph.nodes[4].description = Some("Cache hit rate is low".to_string());
+        graph
+    }
+
+    fn ids(query: &str) -> Vec<i32> {
+        let graph = sample_graph();
+        Query::parse(query)
+            .unwrap()
+            .evaluate(&graph)
+            .iter()
+            .map(|n| n.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_field_filters() {
+        assert_eq!(ids(""), vec![1, 2, 3, 4, 5]);
+        assert_eq!(ids("type:decision"), vec![2, 4]);
+        assert_eq!(ids("type:goal,action"), vec![1, 3]);
+        assert_eq!(ids("type:decision status:pending"), vec![2]);
+        assert_eq!(ids("branch:feature/*"), vec![2, 3]);
+        assert_eq!(ids("tag:perf"), vec![2]);
+        assert_eq!(ids("attr:owner=sam"), vec![3]);
+        assert_eq!(ids("attr:owner"), vec![3]);
+        assert_eq!(ids("has:commit"), vec![3]);
+        assert_eq!(ids("-has:parent"), vec![1, 5]);
+        assert_eq!(ids("cache"), vec![3, 5]);
+        assert_eq!(ids("title:\"cache layer\""), vec![3]);
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert_eq!(ids("confidence:<50"), vec![2]);
+        assert_eq!(ids("confidence:>=40"), vec![1, 2]);
+        assert_eq!(ids("created:>2025-01-01"), vec![2, 3, 4, 5]);
+        assert_eq!(ids("created:2025-02"), vec![3, 4]);
+        assert_eq!(ids("created:<2025"), vec![1]);
+        assert_eq!(ids("id:2-3,5"), vec![2, 3, 5]);
+        assert_eq!(ids("id:>3"), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_traversal_and_negation() {
+        assert_eq!(ids("descendants-of:1"), vec![2, 3, 4]);
+        assert_eq!(ids("descendants-of:1 type:decision"), vec![2, 4]);
+        assert_eq!(ids("ancestors-of:3"), vec![1, 2]);
+        assert_eq!(ids("-status:pending"), vec![3, 4]);
+        assert_eq!(ids("descendants-of:99"), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            Query::parse("stauts:pending"),
+            Err(QueryError::UnknownKey(_))
+        ));
+        assert!(Query::parse("stauts:pending")
+            .unwrap_err()
+            .to_string()
+            .contains("Did you mean 'status'?"));
+        assert!(Query::parse("confidence:high").is_err());
+        assert!(Query::parse("confidence:>101").is_err());
+        assert!(Query::parse("created:yesterday").is_err());
+        assert!(Query::parse("has:everything").is_err());
+        assert!(Query::parse("type:").is_err());
+        assert!(Query::parse("title:\"open").is_err());
+        assert!(Query::parse("descendants-of:abc").is_err());
+    }
+
+    #[test]
+    fn test_relative_dates() {
+        let query = Query::parse("created:>7d").unwrap();
+        let graph = sample_graph();
+        assert!(query.evaluate(&graph).is_empty());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("feature/*", "feature/cache"));
+        assert!(glob_match("*cache*", "feature/cache-v2"));
+        assert!(glob_match("main", "main"));
+        assert!(!glob_match("main", "main2"));
+        assert!(!glob_match("feature/*", "fix/cache"));
+        assert!(!glob_match("a*a", "a"));
+    }
+}
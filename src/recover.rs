@@ -0,0 +1,673 @@
+//! Context recovery pack
+//!
+//! `deciduous recover` → the open goals, pending decisions, recent outcomes and
+//! most recently updated chains, trimmed to fit a token budget. The selection
+//! depends only on the graph and the options, so the same graph always yields
+//! the same pack.
+
+use crate::db::{DecisionEdge, DecisionGraph, DecisionNode};
+use crate::tui::state::{build_chains, sort_chains_by_recency};
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Token budget used when none is given
+pub const DEFAULT_BUDGET: usize = 4000;
+
+/// Most entries listed in each of the open goals and pending decisions sections
+const SECTION_LIMIT: usize = 10;
+
+/// Most entries in the recent outcomes section
+const OUTCOME_LIMIT: usize = 5;
+
+/// Most nodes shown per chain (the most recently updated ones and the nodes
+/// leading to them)
+const CHAIN_NODE_LIMIT: usize = 15;
+
+/// Tokens held back for the closing summary line
+const FOOTER_RESERVE: usize = 30;
+
+/// Rough token count: about four characters per token for English and code
+pub fn estimate_tokens(text: &str) -> usize {
+    (text.chars().count() + 3) / 4
+}
+
+/// Output format of a pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PackFormat {
+    #[default]
+    Markdown,
+    /// Compact JSON (no pretty-printing, to save tokens)
+    Json,
+}
+
+/// What to put in a pack
+#[derive(Debug, Clone)]
+pub struct RecoverOptions {
+    /// Upper bound on the estimated tokens of the rendered pack
+    pub budget: usize,
+    /// Only nodes on this branch
+    pub branch: Option<String>,
+    /// Only nodes updated at or after this RFC 3339 time
+    pub since: Option<String>,
+    pub format: PackFormat,
+}
+
+impl Default for RecoverOptions {
+    fn default() -> Self {
+        Self {
+            budget: DEFAULT_BUDGET,
+            branch: None,
+            since: None,
+            format: PackFormat::Markdown,
+        }
+    }
+}
+
+/// A node as shown in a pack
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PackNode {
+    pub id: i32,
+    pub node_type: String,
+    pub title: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub updated_at: String,
+    /// Distance from the chain's root (chain members only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+}
+
+impl PackNode {
+    fn new(node: &DecisionNode, depth: Option<usize>) -> Self {
+        Self {
+            id: node.id,
+            node_type: node.node_type.clone(),
+            title: node.title.clone(),
+            status: node.status.clone(),
+            confidence: node.confidence(),
+            branch: node.branch(),
+            updated_at: node.updated_at.clone(),
+            depth,
+        }
+    }
+}
+
+/// A goal with the descendants that fit in the budget
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PackChain {
+    pub root: PackNode,
+    pub last_updated: String,
+    /// The most recently updated descendants and the path from the root to each,
+    /// depth-first so every node follows its parent
+    pub nodes: Vec<PackNode>,
+    /// Descendants left out
+    pub omitted: usize,
+}
+
+/// The selected context, ready to render
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecoveryPack {
+    pub budget: usize,
+    pub estimated_tokens: usize,
+    pub branch: Option<String>,
+    pub since: Option<String>,
+    pub open_goals: Vec<PackNode>,
+    pub pending_decisions: Vec<PackNode>,
+    pub recent_outcomes: Vec<PackNode>,
+    pub chains: Vec<PackChain>,
+    /// Section entries and whole chains left out to stay within the budget
+    /// (nodes cut from a kept chain are counted on the chain)
+    pub omitted: usize,
+}
+
+/// Tracks how much of the budget is spent
+struct Budget {
+    limit: usize,
+    used: usize,
+}
+
+impl Budget {
+    /// Spend `cost` if it fits
+    fn take(&mut self, cost: usize) -> bool {
+        if self.used + cost > self.limit {
+            return false;
+        }
+        self.used += cost;
+        true
+    }
+
+    /// Give back part of an earlier `take` that turned out not to be needed
+    fn release(&mut self, cost: usize) {
+        self.used = self.used.saturating_sub(cost);
+    }
+}
+
+/// Select the most relevant context from `graph` within the budget.
+///
+/// Sections fill in priority order (open goals, pending decisions, recent
+/// outcomes, then chains most recently updated first); whatever doesn't fit is
+/// counted in `omitted`.
+pub fn build_pack(graph: &DecisionGraph, options: &RecoverOptions) -> RecoveryPack {
+    let since = options
+        .since
+        .as_deref()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok());
+    let nodes: Vec<DecisionNode> = graph
+        .nodes
+        .iter()
+        .filter(|n| {
+            options
+                .branch
+                .as_ref()
+                .map_or(true, |b| n.branch().as_ref() == Some(b))
+        })
+        .filter(|n| since.map_or(true, |since| updated_since(n, since)))
+        .cloned()
+        .collect();
+
+    let mut pack = RecoveryPack {
+        budget: options.budget,
+        estimated_tokens: 0,
+        branch: options.branch.clone(),
+        since: options.since.clone(),
+        open_goals: Vec::new(),
+        pending_decisions: Vec::new(),
+        recent_outcomes: Vec::new(),
+        chains: Vec::new(),
+        omitted: 0,
+    };
+    let fixed = match options.format {
+        PackFormat::Markdown => estimate_tokens(&title_line(&pack)),
+        PackFormat::Json => estimate_tokens(&render_json(&pack)),
+    };
+    let mut budget = Budget {
+        limit: options.budget.saturating_sub(FOOTER_RESERVE),
+        used: fixed,
+    };
+
+    let open = |n: &&DecisionNode| n.status == "pending" || n.status == "active";
+    let goals = most_recent(nodes.iter().filter(|n| n.node_type == "goal").filter(open));
+    let decisions = most_recent(
+        nodes
+            .iter()
+            .filter(|n| n.node_type == "decision")
+            .filter(open),
+    );
+    let outcomes = most_recent(nodes.iter().filter(|n| n.node_type == "outcome"));
+
+    // The branch is implied on every line when the pack is scoped to one
+    let show_branch = options.branch.is_none();
+    let mut select = |heading: &str, candidates: Vec<&DecisionNode>, limit: usize| {
+        let mut kept = Vec::new();
+        for node in candidates.iter().take(limit) {
+            let entry = PackNode::new(node, None);
+            let mut cost = entry_cost(&entry, show_branch, options.format);
+            if kept.is_empty() {
+                cost += estimate_tokens(heading);
+            }
+            if budget.take(cost) {
+                kept.push(entry);
+            } else {
+                pack.omitted += 1;
+            }
+        }
+        pack.omitted += candidates.len().saturating_sub(limit);
+        kept
+    };
+    pack.open_goals = select(OPEN_GOALS, goals, SECTION_LIMIT);
+    pack.pending_decisions = select(PENDING_DECISIONS, decisions, SECTION_LIMIT);
+    pack.recent_outcomes = select(RECENT_OUTCOMES, outcomes, OUTCOME_LIMIT);
+
+    let chains = sort_chains_by_recency(&build_chains(&nodes, &graph.edges));
+    for chain in chains {
+        let mut packed = PackChain {
+            root: PackNode::new(&chain.root, Some(0)),
+            last_updated: chain.last_updated().to_rfc3339(),
+            nodes: Vec::new(),
+            omitted: 0,
+        };
+        let (members, total) = chain_members(packed.root.id, &nodes, &graph.edges);
+        let mut header = chain_cost(&packed, options.format);
+        if pack.chains.is_empty() {
+            header += estimate_tokens(RECENT_CHAINS);
+        }
+        // Room for the "… N more" line, in case anything ends up left out
+        let reserve = omission_cost(packed.root.id, total, options.format);
+        if !budget.take(header + reserve) {
+            pack.omitted += 1;
+            continue;
+        }
+
+        // Members are depth-first, so stopping at the first that doesn't fit never
+        // leaves a node without its parent
+        for entry in members {
+            if !budget.take(entry_cost(&entry, show_branch, options.format)) {
+                break;
+            }
+            packed.nodes.push(entry);
+        }
+        packed.omitted = total - packed.nodes.len();
+        budget.release(reserve - omission_cost(packed.root.id, packed.omitted, options.format));
+        pack.chains.push(packed);
+    }
+
+    pack.estimated_tokens = budget.used;
+    pack
+}
+
+impl RecoveryPack {
+    pub fn render(&self, format: PackFormat) -> String {
+        match format {
+            PackFormat::Markdown => render_markdown(self),
+            PackFormat::Json => render_json(self),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.open_goals.is_empty()
+            && self.pending_decisions.is_empty()
+            && self.recent_outcomes.is_empty()
+            && self.chains.is_empty()
+    }
+}
+
+// Section headings
+const OPEN_GOALS: &str = "\n## Open goals\n";
+const PENDING_DECISIONS: &str = "\n## Pending decisions\n";
+const RECENT_OUTCOMES: &str = "\n## Recent outcomes\n";
+const RECENT_CHAINS: &str = "\n## Recent chains\n";
+
+fn updated_since(node: &DecisionNode, since: DateTime<FixedOffset>) -> bool {
+    DateTime::parse_from_rfc3339(&node.updated_at).is_ok_and(|t| t >= since)
+}
+
+/// Newest first; ties broken by id so the order never depends on the input order
+fn most_recent<'a>(nodes: impl Iterator<Item = &'a DecisionNode>) -> Vec<&'a DecisionNode> {
+    let mut nodes: Vec<&DecisionNode> = nodes.collect();
+    nodes.sort_by(|a, b| {
+        b.updated_at
+            .cmp(&a.updated_at)
+            .then_with(|| b.id.cmp(&a.id))
+    });
+    nodes
+}
+
+/// Up to [`CHAIN_NODE_LIMIT`] of a chain's descendants, depth-first, along with
+/// how many descendants it has.
+///
+/// The most recently updated descendants are picked first, each together with the
+/// nodes between it and the root, so every entry is shown under its parent.
+fn chain_members(
+    root_id: i32,
+    nodes: &[DecisionNode],
+    edges: &[DecisionEdge],
+) -> (Vec<PackNode>, usize) {
+    let by_id: HashMap<i32, &DecisionNode> = nodes.iter().map(|n| (n.id, n)).collect();
+
+    // Each descendant hangs under the node it was first reached from
+    let mut parent: HashMap<i32, i32> = HashMap::new();
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    let mut seen = HashSet::from([root_id]);
+    let mut queue = VecDeque::from([root_id]);
+    while let Some(id) = queue.pop_front() {
+        for edge in edges.iter().filter(|e| e.from_node_id == id) {
+            let child = edge.to_node_id;
+            if by_id.contains_key(&child) && seen.insert(child) {
+                parent.insert(child, id);
+                children.entry(id).or_default().push(child);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    let mut keep: HashSet<i32> = HashSet::new();
+    for node in most_recent(parent.keys().map(|id| by_id[id])) {
+        let path: Vec<i32> = std::iter::successors(Some(node.id), |id| parent.get(id).copied())
+            .take_while(|id| *id != root_id)
+            .filter(|id| !keep.contains(id))
+            .collect();
+        if keep.len() + path.len() <= CHAIN_NODE_LIMIT {
+            keep.extend(path);
+        }
+    }
+
+    let mut members = Vec::new();
+    let mut stack: Vec<(i32, usize)> = Vec::new();
+    let push_children = |stack: &mut Vec<(i32, usize)>, id: i32, depth: usize| {
+        if let Some(kids) = children.get(&id) {
+            stack.extend(kids.iter().rev().map(|&kid| (kid, depth + 1)));
+        }
+    };
+    push_children(&mut stack, root_id, 0);
+    while let Some((id, depth)) = stack.pop() {
+        if keep.contains(&id) {
+            members.push(PackNode::new(by_id[&id], Some(depth)));
+            push_children(&mut stack, id, depth);
+        }
+    }
+    (members, parent.len())
+}
+
+fn entry_cost(entry: &PackNode, show_branch: bool, format: PackFormat) -> usize {
+    match format {
+        PackFormat::Markdown => estimate_tokens(&entry_line(entry, show_branch)),
+        // The entry plus its separating comma
+        PackFormat::Json => estimate_tokens(&serde_json::to_string(entry).unwrap_or_default()) + 1,
+    }
+}
+
+/// Cost of noting that `omitted` of a chain's members were left out
+fn omission_cost(root_id: i32, omitted: usize, format: PackFormat) -> usize {
+    match (omitted, format) {
+        (0, _) => 0,
+        (_, PackFormat::Markdown) => estimate_tokens(&omission_line(root_id, omitted)),
+        // The count replaces the 0 already charged with the chain
+        (_, PackFormat::Json) => estimate_tokens(&omitted.to_string()),
+    }
+}
+
+/// Cost of a chain before any of its members are added
+fn chain_cost(chain: &PackChain, format: PackFormat) -> usize {
+    match format {
+        PackFormat::Markdown => estimate_tokens(&chain_heading(chain)),
+        PackFormat::Json => estimate_tokens(&serde_json::to_string(chain).unwrap_or_default()) + 1,
+    }
+}
+
+fn title_line(pack: &RecoveryPack) -> String {
+    let mut scope = Vec::new();
+    if let Some(branch) = &pack.branch {
+        scope.push(format!("branch {}", branch));
+    }
+    if let Some(since) = &pack.since {
+        scope.push(format!("since {}", since));
+    }
+    if scope.is_empty() {
+        "# Context recovery\n".to_string()
+    } else {
+        format!("# Context recovery ({})\n", scope.join(", "))
+    }
+}
+
+fn chain_heading(chain: &PackChain) -> String {
+    format!(
+        "\n### #{} {} [{}] · updated {}\n",
+        chain.root.id,
+        chain.root.title,
+        chain.root.status,
+        short_date(&chain.last_updated)
+    )
+}
+
+/// One list item: chain members are indented by depth and show their type
+fn entry_line(entry: &PackNode, show_branch: bool) -> String {
+    let mut details = vec![entry.status.clone()];
+    if let Some(confidence) = entry.confidence {
+        details.push(format!("{}%", confidence));
+    }
+    if show_branch {
+        if let Some(branch) = &entry.branch {
+            details.push(branch.clone());
+        }
+    }
+    details.push(short_date(&entry.updated_at).to_string());
+
+    match entry.depth {
+        Some(depth) => format!(
+            "{}- #{} {}: {} [{}]\n",
+            "  ".repeat(depth.saturating_sub(1)),
+            entry.id,
+            entry.node_type,
+            entry.title,
+            details.join(", ")
+        ),
+        None => format!("- #{} {} [{}]\n", entry.id, entry.title, details.join(", ")),
+    }
+}
+
+fn omission_line(root_id: i32, omitted: usize) -> String {
+    format!("- … {} more (deciduous tree {})\n", omitted, root_id)
+}
+
+fn short_date(timestamp: &str) -> &str {
+    timestamp.get(..10).unwrap_or(timestamp)
+}
+
+fn render_markdown(pack: &RecoveryPack) -> String {
+    let show_branch = pack.branch.is_none();
+    let mut out = title_line(pack);
+    for (heading, entries) in [
+        (OPEN_GOALS, &pack.open_goals),
+        (PENDING_DECISIONS, &pack.pending_decisions),
+        (RECENT_OUTCOMES, &pack.recent_outcomes),
+    ] {
+        if entries.is_empty() {
+            continue;
+        }
+        out.push_str(heading);
+        for entry in entries {
+            out.push_str(&entry_line(entry, show_branch));
+        }
+    }
+
+    if !pack.chains.is_empty() {
+        out.push_str(RECENT_CHAINS);
+    }
+    for chain in &pack.chains {
+        out.push_str(&chain_heading(chain));
+        for entry in &chain.nodes {
+            out.push_str(&entry_line(entry, show_branch));
+        }
+        if chain.omitted > 0 {
+            out.push_str(&omission_line(chain.root.id, chain.omitted));
+        }
+    }
+
+    if pack.is_empty() {
+        out.push_str("\nNo matching nodes.\n");
+    }
+    out.push_str(&format!(
+        "\n_~{} of {} tokens",
+        pack.estimated_tokens, pack.budget
+    ));
+    if pack.omitted > 0 {
+        out.push_str(&format!(
+            "; {} entries omitted (raise --budget to see more)",
+            pack.omitted
+        ));
+    }
+    out.push_str("_\n");
+    out
+}
+
+fn render_json(pack: &RecoveryPack) -> String {
+    serde_json::to_string(pack).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{edge, graph, node_at};
+
+    /// Node updated on day `day` of January 2025
+    fn node(id: i32, node_type: &str, status: &str, day: u32) -> DecisionNode {
+        node_at(
+            id,
+            node_type,
+            status,
+            &format!("2025-01-{:02}T00:00:00Z", day),
+        )
+    }
+
+    /// Old goal 1 -> 2 -> 3, recent goal 4 -> 5 -> 6, and a finished goal 7
+    fn sample_graph() -> DecisionGraph {
+        graph(
+            vec![
+                node(1, "goal", "active", 1),
+                node(2, "decision", "pending", 2),
+                node(3, "outcome", "completed", 3),
+                node(4, "goal", "pending", 10),
+                node(5, "decision", "completed", 11),
+                node(6, "action", "pending", 12),
+                node(7, "goal", "completed", 5),
+            ],
+            vec![edge(1, 2), edge(2, 3), edge(4, 5), edge(5, 6)],
+        )
+    }
+
+    #[test]
+    fn test_sections_and_chain_order() {
+        let pack = build_pack(&sample_graph(), &RecoverOptions::default());
+
+        let ids = |nodes: &[PackNode]| nodes.iter().map(|n| n.id).collect::<Vec<_>>();
+        assert_eq!(ids(&pack.open_goals), vec![4, 1]);
+        assert_eq!(ids(&pack.pending_decisions), vec![2]);
+        assert_eq!(ids(&pack.recent_outcomes), vec![3]);
+        // Most recently updated chain first
+        let roots: Vec<i32> = pack.chains.iter().map(|c| c.root.id).collect();
+        assert_eq!(roots, vec![4, 7, 1]);
+        assert_eq!(ids(&pack.chains[0].nodes), vec![5, 6]);
+        assert_eq!(pack.omitted, 0);
+
+        let markdown = pack.render(PackFormat::Markdown);
+        assert!(markdown.starts_with("# Context recovery\n"));
+        assert!(markdown.contains("## Open goals\n- #4 Node 4 [pending, 2025-01-10]\n"));
+        assert!(markdown.contains("### #4 Node 4 [pending] · updated 2025-01-12\n"));
+        assert!(markdown.contains("- #5 decision: Node 5 [completed, 2025-01-11]\n  - #6"));
+    }
+
+    #[test]
+    fn test_budget_is_respected_and_output_deterministic() {
+        // Many chains that are each cut short, so every one carries a "… N more" line
+        let mut wide = graph(vec![], vec![]);
+        for goal in 0..200 {
+            let root = 1000 + goal * 20;
+            wide.nodes.push(node(root, "goal", "pending", 1));
+            for child in root + 1..=root + 16 {
+                wide.nodes.push(node(child, "action", "pending", 2));
+                wide.edges.push(edge(root, child));
+            }
+        }
+        for format in [PackFormat::Markdown, PackFormat::Json] {
+            for budget in [1000, 4000, 20000] {
+                let options = RecoverOptions {
+                    budget,
+                    format,
+                    ..Default::default()
+                };
+                let rendered = build_pack(&wide, &options).render(format);
+                assert!(
+                    estimate_tokens(&rendered) <= budget,
+                    "{:?} at {} used {}",
+                    format,
+                    budget,
+                    estimate_tokens(&rendered)
+                );
+            }
+        }
+
+        let mut graph = sample_graph();
+        for id in 10..60 {
+            graph.nodes.push(node(id, "action", "pending", 20));
+            graph.edges.push(edge(4, id));
+        }
+        // The newest node sits under an old one, which has to come along with it
+        graph.nodes.push(node(60, "decision", "pending", 1));
+        graph.nodes.push(node(61, "action", "pending", 30));
+        graph.edges.push(edge(4, 60));
+        graph.edges.push(edge(60, 61));
+
+        for format in [PackFormat::Markdown, PackFormat::Json] {
+            for budget in [60, 200, 500, 4000] {
+                let options = RecoverOptions {
+                    budget,
+                    format,
+                    ..Default::default()
+                };
+                let pack = build_pack(&graph, &options);
+                let rendered = pack.render(format);
+                assert!(
+                    estimate_tokens(&rendered) <= budget,
+                    "{:?} at {} used {}",
+                    format,
+                    budget,
+                    estimate_tokens(&rendered)
+                );
+                assert_eq!(build_pack(&graph, &options).render(format), rendered);
+            }
+        }
+
+        // A chain with more than CHAIN_NODE_LIMIT descendants is cut short
+        let pack = build_pack(&graph, &RecoverOptions::default());
+        let chain = &pack.chains[0];
+        assert_eq!(chain.nodes.len(), CHAIN_NODE_LIMIT);
+        assert_eq!(chain.omitted, 54 - CHAIN_NODE_LIMIT);
+        let tail: Vec<_> = chain.nodes[CHAIN_NODE_LIMIT - 2..]
+            .iter()
+            .map(|n| (n.id, n.depth))
+            .collect();
+        assert_eq!(tail, vec![(60, Some(1)), (61, Some(2))]);
+        let markdown = pack.render(PackFormat::Markdown);
+        assert!(markdown.contains("- … 39 more (deciduous tree 4)"));
+
+        let tiny = build_pack(
+            &graph,
+            &RecoverOptions {
+                budget: 60,
+                ..Default::default()
+            },
+        );
+        assert!(tiny.omitted > 0);
+        assert!(tiny
+            .render(PackFormat::Markdown)
+            .contains("entries omitted (raise --budget to see more)"));
+    }
+
+    #[test]
+    fn test_branch_and_since_filters() {
+        let mut graph = sample_graph();
+        graph.nodes[3].metadata_json = Some(r#"{"branch":"feature-x"}"#.to_string());
+        graph.nodes[4].metadata_json = Some(r#"{"branch":"feature-x"}"#.to_string());
+
+        let pack = build_pack(
+            &graph,
+            &RecoverOptions {
+                branch: Some("feature-x".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(pack.open_goals.len(), 1);
+        assert_eq!(pack.chains.len(), 1);
+        // Node 6 is on another branch, so the chain stops at 5
+        assert_eq!(pack.chains[0].nodes.len(), 1);
+        let markdown = pack.render(PackFormat::Markdown);
+        assert!(markdown.starts_with("# Context recovery (branch feature-x)\n"));
+        // The branch isn't repeated on every line
+        assert!(!markdown.contains(", feature-x"));
+
+        let pack = build_pack(
+            &graph,
+            &RecoverOptions {
+                since: Some("2025-01-10T00:00:00Z".to_string()),
+                ..Default::default()
+            },
+        );
+        let goals: Vec<i32> = pack.open_goals.iter().map(|n| n.id).collect();
+        assert_eq!(goals, vec![4]);
+        assert!(pack.recent_outcomes.is_empty());
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let pack = build_pack(&graph(vec![], vec![]), &RecoverOptions::default());
+        assert!(pack.is_empty());
+        let markdown = pack.render(PackFormat::Markdown);
+        assert!(markdown.contains("No matching nodes."));
+        let json: serde_json::Value = serde_json::from_str(&pack.render(PackFormat::Json)).unwrap();
+        assert_eq!(json["budget"], DEFAULT_BUDGET);
+    }
+}
@@ -21,6 +21,7 @@ diesel::table! {
         created_at -> Text,
         updated_at -> Text,
         metadata_json -> Nullable<Text>,
+        author -> Nullable<Text>,
     }
 }
 
@@ -35,6 +36,91 @@ diesel::table! {
         weight -> Nullable<Double>,
         rationale -> Nullable<Text>,
         created_at -> Text,
+        author -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    decision_tombstones (id) {
+        id -> Integer,
+        kind -> Text,
+        change_id -> Text,
+        from_change_id -> Nullable<Text>,
+        to_change_id -> Nullable<Text>,
+        edge_type -> Nullable<Text>,
+        deleted_at -> Text,
+    }
+}
+
+diesel::table! {
+    change_id_aliases (alias) {
+        alias -> Text,
+        change_id -> Text,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    node_sync_bases (change_id) {
+        change_id -> Text,
+        content_hash -> Text,
+        parent_hash -> Nullable<Text>,
+        content -> Text,
+        synced_at -> Text,
+        ancestor_hashes -> Text,
+    }
+}
+
+diesel::table! {
+    patch_exports (id) {
+        id -> Integer,
+        path -> Text,
+        exported_at -> Text,
+        since -> Nullable<Text>,
+        node_count -> Integer,
+        edge_count -> Integer,
+        filter -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    applied_patches (digest) {
+        digest -> Text,
+        path -> Text,
+        author -> Nullable<Text>,
+        created_at -> Text,
+        applied_at -> Text,
+        nodes_added -> Integer,
+        nodes_updated -> Integer,
+        edges_added -> Integer,
+        nodes_failed -> Integer,
+        edges_failed -> Integer,
+    }
+}
+
+diesel::table! {
+    applied_patch_items (id) {
+        id -> Integer,
+        patch_digest -> Text,
+        kind -> Text,
+        change_id -> Text,
+        from_change_id -> Nullable<Text>,
+        to_change_id -> Nullable<Text>,
+        edge_type -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    node_conflicts (id) {
+        id -> Integer,
+        node_change_id -> Text,
+        base_hash -> Nullable<Text>,
+        base_value -> Nullable<Text>,
+        local_value -> Text,
+        remote_value -> Text,
+        resolution -> Nullable<Text>,
+        detected_at -> Text,
+        resolved_at -> Nullable<Text>,
     }
 }
 
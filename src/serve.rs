@@ -2,7 +2,7 @@
 //!
 //! `deciduous serve` → starts server, opens browser, shows graph
 
-use crate::db::{Database, DecisionGraph, RoadmapItem};
+use crate::db::{Database, DecisionGraph, RoadmapItem, SearchFilters, SearchHit};
 use serde::Serialize;
 use tiny_http::{Header, Method, Request, Response, Server};
 
@@ -62,14 +62,52 @@ fn handle_request(request: Request) -> std::io::Result<()> {
             request.respond(response)
         }
 
-        // API: Get decision graph
+        // API: Get decision graph (GET /api/graph?include=context)
         (&Method::Get, "/api/graph") => {
-            let graph = get_decision_graph();
-            let json = serde_json::to_string(&ApiResponse::success(graph))?;
+            let query = url.split_once('?').map(|(_, q)| q).unwrap_or("");
+            let (json, status) = match parse_graph_params(query) {
+                Ok(includes) => (
+                    serde_json::to_string(&ApiResponse::success(get_decision_graph(&includes)))?,
+                    200,
+                ),
+                Err(e) => (
+                    serde_json::to_string(&ApiResponse::<DecisionGraph> {
+                        ok: false,
+                        data: None,
+                        error: Some(e),
+                    })?,
+                    400,
+                ),
+            };
 
-            let response = Response::from_string(json).with_header(
-                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
-            );
+            let response = Response::from_string(json)
+                .with_status_code(status)
+                .with_header(
+                    Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+                );
+            request.respond(response)
+        }
+
+        // API: Full-text node search (GET /api/search?q=caching&type=decision&limit=20)
+        (&Method::Get, "/api/search") => {
+            let query = url.split_once('?').map(|(_, q)| q).unwrap_or("");
+            let (json, status) = match search_nodes(query) {
+                Ok(hits) => (serde_json::to_string(&ApiResponse::success(hits))?, 200),
+                Err(e) => (
+                    serde_json::to_string(&ApiResponse::<Vec<SearchHit>> {
+                        ok: false,
+                        data: None,
+                        error: Some(e),
+                    })?,
+                    400,
+                ),
+            };
+
+            let response = Response::from_string(json)
+                .with_status_code(status)
+                .with_header(
+                    Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+                );
             request.respond(response)
         }
 
@@ -106,28 +144,103 @@ fn handle_request(request: Request) -> std::io::Result<()> {
     }
 }
 
-fn get_decision_graph() -> DecisionGraph {
+/// Optional parts of the /api/graph response
+#[derive(Debug, Default, PartialEq)]
+struct GraphIncludes {
+    context: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct GraphParams {
+    /// Comma-separated extras, e.g. "context"
+    include: Option<String>,
+}
+
+fn parse_graph_params(query: &str) -> Result<GraphIncludes, String> {
+    let params: GraphParams = serde_urlencoded::from_str(query)
+        .map_err(|e| format!("Invalid graph parameters: {}", e))?;
+    let mut includes = GraphIncludes::default();
+    for part in params.include.iter().flat_map(|i| i.split(',')) {
+        match part.trim() {
+            "" => {}
+            "context" => includes.context = true,
+            other => {
+                return Err(format!(
+                    "Unknown include '{}'. Valid values: context",
+                    other
+                ))
+            }
+        }
+    }
+    Ok(includes)
+}
+
+fn get_decision_graph(includes: &GraphIncludes) -> DecisionGraph {
     // Load config for external repo support
     let config = crate::config::Config::load();
     let include_config = config.github.commit_repo.is_some();
     let config_opt = if include_config { Some(config) } else { None };
 
+    let empty = || DecisionGraph {
+        nodes: vec![],
+        edges: vec![],
+        config: config_opt.clone(),
+        context: vec![],
+    };
     match Database::open() {
-        Ok(db) => db
-            .get_graph_with_config(config_opt.clone())
-            .unwrap_or_else(|_| DecisionGraph {
-                nodes: vec![],
-                edges: vec![],
-                config: config_opt.clone(),
-            }),
-        Err(_) => DecisionGraph {
-            nodes: vec![],
-            edges: vec![],
-            config: config_opt,
-        },
+        Ok(db) => {
+            let mut graph = db
+                .get_graph_with_config(config_opt.clone())
+                .unwrap_or_else(|_| empty());
+            if includes.context {
+                graph.context = db.get_all_context().unwrap_or_default();
+            }
+            graph
+        }
+        Err(_) => empty(),
     }
 }
 
+#[derive(serde::Deserialize)]
+struct SearchParams {
+    q: String,
+    #[serde(rename = "type")]
+    node_type: Option<String>,
+    status: Option<String>,
+    branch: Option<String>,
+    /// Comma-separated; all must match
+    tag: Option<String>,
+    limit: Option<usize>,
+}
+
+fn parse_search_params(query: &str) -> Result<(String, SearchFilters), String> {
+    let params: SearchParams = serde_urlencoded::from_str(query)
+        .map_err(|e| format!("Invalid search parameters: {}", e))?;
+    let filters = SearchFilters {
+        node_type: params.node_type,
+        status: params.status,
+        branch: params.branch,
+        tags: params
+            .tag
+            .map(|t| {
+                t.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        limit: Some(params.limit.unwrap_or(50)),
+    };
+    Ok((params.q, filters))
+}
+
+fn search_nodes(query: &str) -> Result<Vec<SearchHit>, String> {
+    let (q, filters) = parse_search_params(query)?;
+    let db = Database::open().map_err(|e| format!("Database error: {}", e))?;
+    db.search(&q, &filters)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
 fn get_command_log() -> Vec<crate::db::CommandLog> {
     match Database::open() {
         Ok(db) => db.get_recent_commands(100).unwrap_or_default(),
@@ -211,6 +324,32 @@ fn handle_toggle_checkbox(mut request: Request) -> std::io::Result<()> {
 mod tests {
     use super::*;
 
+    // === Search Params Tests ===
+
+    #[test]
+    fn test_parse_search_params() {
+        let (q, filters) =
+            parse_search_params("q=cache%20layer&type=action&tag=perf,debt&limit=5").unwrap();
+        assert_eq!(q, "cache layer");
+        assert_eq!(filters.node_type.as_deref(), Some("action"));
+        assert_eq!(filters.tags, vec!["perf", "debt"]);
+        assert_eq!(filters.limit, Some(5));
+
+        let (_, filters) = parse_search_params("q=x").unwrap();
+        assert_eq!(filters.limit, Some(50));
+        assert!(parse_search_params("type=goal").is_err());
+        assert!(parse_search_params("q=x&limit=lots").is_err());
+    }
+
+    #[test]
+    fn test_parse_graph_params() {
+        assert_eq!(parse_graph_params("").unwrap(), GraphIncludes::default());
+        assert!(parse_graph_params("include=context").unwrap().context);
+        assert!(parse_graph_params("include=%20context,").unwrap().context);
+        let err = parse_graph_params("include=context,comments").unwrap_err();
+        assert!(err.contains("Unknown include 'comments'"));
+    }
+
     // === ApiResponse Tests ===
 
     #[test]
@@ -0,0 +1,309 @@
+//! Patch signing keys and detached signatures
+//!
+//! Exported patches carry a SHA-256 digest of their content, optionally signed
+//! with an ed25519 key. Keys live in `.deciduous/keys/`: `signing.key` holds this
+//! machine's secret key, and every `*.pub` file is a public key trusted to sign
+//! patches (your own plus the ones teammates hand you).
+
+use crate::db::Database;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File name of the secret key inside the keys directory
+pub const SIGNING_KEY_FILE: &str = "signing.key";
+
+/// Detached ed25519 signature over a patch digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PatchSignature {
+    /// Signer's public key (hex)
+    pub public_key: String,
+    /// Signature over the patch digest (hex)
+    pub signature: String,
+}
+
+impl PatchSignature {
+    /// Sign a patch digest
+    pub fn create(key: &SigningKey, digest: &str) -> Self {
+        Self {
+            public_key: to_hex(key.verifying_key().as_bytes()),
+            signature: to_hex(&key.sign(digest.as_bytes()).to_bytes()),
+        }
+    }
+
+    /// Check the signature against a patch digest
+    pub fn verify(&self, digest: &str) -> Result<(), String> {
+        let public_key = parse_public_key(&self.public_key)?;
+        let bytes: [u8; 64] = from_hex(&self.signature)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| "malformed signature".to_string())?;
+        public_key
+            .verify(digest.as_bytes(), &Signature::from_bytes(&bytes))
+            .map_err(|_| format!("signature by key {} does not match", self.key_id()))
+    }
+
+    /// Short identifier of the signing key
+    pub fn key_id(&self) -> &str {
+        key_id(&self.public_key)
+    }
+}
+
+/// What verifying a patch established
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchIntegrity {
+    /// 1.0 patch, written before digests existed
+    Unsealed,
+    /// Digest matches, no signature
+    Sealed,
+    /// Digest matches and is signed; `trusted` if the key is in the keys directory
+    Signed { key_id: String, trusted: bool },
+}
+
+impl PatchIntegrity {
+    pub fn is_signed(&self) -> bool {
+        matches!(self, PatchIntegrity::Signed { .. })
+    }
+
+    /// One-line description for CLI output
+    pub fn describe(&self) -> String {
+        match self {
+            PatchIntegrity::Unsealed => "no digest (1.0 patch)".to_string(),
+            PatchIntegrity::Sealed => "digest ok, unsigned".to_string(),
+            PatchIntegrity::Signed { key_id, trusted } => format!(
+                "digest ok, signed by {}{}",
+                key_id,
+                if *trusted { "" } else { " (untrusted key)" }
+            ),
+        }
+    }
+}
+
+/// The `.deciduous/keys` directory
+#[derive(Debug, Clone)]
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+impl KeyStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Keys directory next to the active database
+    pub fn locate() -> Self {
+        let db_path = Database::db_path();
+        let base = db_path.parent().unwrap_or_else(|| Path::new("."));
+        Self::new(base.join("keys"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Create a new signing key and trust its public half.
+    ///
+    /// Refuses to replace an existing key unless `force` is set.
+    pub fn generate(&self, force: bool) -> Result<String, String> {
+        let key_path = self.dir.join(SIGNING_KEY_FILE);
+        if key_path.exists() && !force {
+            return Err(format!(
+                "{} already exists (use --force to replace it)",
+                key_path.display()
+            ));
+        }
+
+        let mut secret = [0u8; 32];
+        getrandom::getrandom(&mut secret).map_err(|e| format!("Failed to generate key: {}", e))?;
+        let key = SigningKey::from_bytes(&secret);
+        let public_key = to_hex(key.verifying_key().as_bytes());
+
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+        // Public keys may be committed for teammates; the secret key never should be
+        let gitignore = self.dir.join(".gitignore");
+        let ignored = std::fs::read_to_string(&gitignore).unwrap_or_default();
+        if !ignored.lines().any(|line| line.trim() == SIGNING_KEY_FILE) {
+            let separator = if ignored.is_empty() || ignored.ends_with('\n') {
+                ""
+            } else {
+                "\n"
+            };
+            std::fs::write(
+                &gitignore,
+                format!("{}{}{}\n", ignored, separator, SIGNING_KEY_FILE),
+            )
+            .map_err(|e| format!("Failed to write {}: {}", gitignore.display(), e))?;
+        }
+        write_secret_key(&key_path, &format!("{}\n", to_hex(&secret)))
+            .map_err(|e| format!("Failed to write {}: {}", key_path.display(), e))?;
+
+        let pub_path = self.dir.join(format!("{}.pub", key_id(&public_key)));
+        std::fs::write(&pub_path, format!("{}\n", public_key))
+            .map_err(|e| format!("Failed to write {}: {}", pub_path.display(), e))?;
+        Ok(public_key)
+    }
+
+    /// Load this machine's signing key
+    pub fn signing_key(&self) -> Result<SigningKey, String> {
+        let key_path = self.dir.join(SIGNING_KEY_FILE);
+        let content = std::fs::read_to_string(&key_path).map_err(|_| {
+            format!(
+                "No signing key at {} (create one with 'deciduous diff keygen')",
+                key_path.display()
+            )
+        })?;
+        let secret: [u8; 32] = from_hex(content.trim())
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| format!("Malformed signing key in {}", key_path.display()))?;
+        Ok(SigningKey::from_bytes(&secret))
+    }
+
+    /// Public keys (hex) from every `*.pub` file in the directory
+    pub fn trusted_keys(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut keys: Vec<String> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "pub"))
+            .filter_map(|p| std::fs::read_to_string(p).ok())
+            .map(|s| s.trim().to_lowercase())
+            .filter(|k| parse_public_key(k).is_ok())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    pub fn is_trusted(&self, public_key: &str) -> bool {
+        let public_key = public_key.to_lowercase();
+        self.trusted_keys().contains(&public_key)
+    }
+}
+
+/// Short identifier for a hex public key (its first 16 hex digits)
+pub fn key_id(public_key: &str) -> &str {
+    public_key.get(..16).unwrap_or(public_key)
+}
+
+/// Write a secret key file that is owner-only from the moment it exists.
+///
+/// An existing file is removed first, since the mode only applies on creation.
+fn write_secret_key(path: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()
+}
+
+fn parse_public_key(hex: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = from_hex(hex)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| "malformed public key".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| "invalid public key".to_string())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let sig = PatchSignature::create(&key, "abc123");
+        assert!(sig.verify("abc123").is_ok());
+        assert!(sig.verify("abc124").is_err());
+
+        let mut forged = sig.clone();
+        forged.public_key = to_hex(
+            SigningKey::from_bytes(&[8u8; 32])
+                .verifying_key()
+                .as_bytes(),
+        );
+        assert!(forged.verify("abc123").is_err());
+
+        forged.signature = "zz".to_string();
+        assert_eq!(forged.verify("abc123").unwrap_err(), "malformed signature");
+    }
+
+    #[test]
+    fn test_keystore_generate_and_trust() {
+        let tmp = TempDir::new().unwrap();
+        let store = KeyStore::new(tmp.path().join("keys"));
+        assert!(store.signing_key().is_err());
+        assert!(store.trusted_keys().is_empty());
+
+        let public_key = store.generate(false).unwrap();
+        assert!(store.generate(false).is_err());
+        assert!(store.is_trusted(&public_key));
+        assert_eq!(
+            to_hex(store.signing_key().unwrap().verifying_key().as_bytes()),
+            public_key
+        );
+
+        let replaced = store.generate(true).unwrap();
+        assert_ne!(replaced, public_key);
+        // The old public key file stays, so earlier patches still verify
+        assert_eq!(store.trusted_keys().len(), 2);
+    }
+
+    #[test]
+    fn test_keystore_generate_protects_secret_key() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("keys");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(".gitignore"), "*.tmp").unwrap();
+        let store = KeyStore::new(dir.clone());
+        store.generate(false).unwrap();
+        store.generate(true).unwrap();
+
+        // An existing .gitignore gets the entry appended, exactly once
+        let ignored = std::fs::read_to_string(dir.join(".gitignore")).unwrap();
+        assert_eq!(ignored, format!("*.tmp\n{}\n", SIGNING_KEY_FILE));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.join(SIGNING_KEY_FILE))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(from_hex(&to_hex(&[0, 15, 255])), Some(vec![0, 15, 255]));
+        assert_eq!(from_hex("abc"), None);
+        assert_eq!(from_hex("zz"), None);
+    }
+}
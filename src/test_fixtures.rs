@@ -0,0 +1,50 @@
+//! Decision graph fixtures shared by unit tests
+
+use crate::db::{DecisionEdge, DecisionGraph, DecisionNode};
+
+/// Pending node titled "Node {id}", created and updated 2025-01-01
+pub fn node(id: i32, node_type: &str) -> DecisionNode {
+    node_at(id, node_type, "pending", "2025-01-01T00:00:00Z")
+}
+
+/// Node with the given status, created and updated at `at`
+pub fn node_at(id: i32, node_type: &str, status: &str, at: &str) -> DecisionNode {
+    DecisionNode {
+        id,
+        change_id: format!("change-{}", id),
+        node_type: node_type.to_string(),
+        title: format!("Node {}", id),
+        description: None,
+        status: status.to_string(),
+        created_at: at.to_string(),
+        updated_at: at.to_string(),
+        metadata_json: None,
+        author: None,
+    }
+}
+
+/// `leads_to` edge with id `from * 100 + to`
+pub fn edge(from: i32, to: i32) -> DecisionEdge {
+    DecisionEdge {
+        id: from * 100 + to,
+        from_node_id: from,
+        to_node_id: to,
+        from_change_id: None,
+        to_change_id: None,
+        edge_type: "leads_to".to_string(),
+        weight: Some(1.0),
+        rationale: None,
+        created_at: "2025-01-01T00:00:00Z".to_string(),
+        author: None,
+    }
+}
+
+/// Graph without config or context
+pub fn graph(nodes: Vec<DecisionNode>, edges: Vec<DecisionEdge>) -> DecisionGraph {
+    DecisionGraph {
+        nodes,
+        edges,
+        config: None,
+        context: vec![],
+    }
+}
@@ -0,0 +1,465 @@
+//! Graph traversal for `deciduous tree`, `ancestors` and `path`
+//!
+//! Works on an in-memory `DecisionGraph`. The graph is a DAG in practice, but
+//! nothing enforces that, so every walk guards against cycles.
+
+use crate::db::{DecisionEdge, DecisionGraph, DecisionNode};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+/// Maximum number of paths `find_paths` returns in `all` mode
+pub const MAX_PATHS: usize = 100;
+
+/// Maximum edges `find_paths` follows in `all` mode before settling for what it found.
+/// Only nodes that can reach the target are explored, but cycles can still make
+/// the number of simple paths explode.
+const MAX_PATH_EXPANSIONS: usize = 100_000;
+
+/// Which way to walk from the starting node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Follow outgoing edges (children)
+    Descendants,
+    /// Follow incoming edges (parents)
+    Ancestors,
+}
+
+/// A node in a descendant or ancestor tree
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeNode {
+    pub node: DecisionNode,
+    /// Type of the edge linking this node to the one above it (None at the root)
+    pub edge_type: Option<String>,
+    pub rationale: Option<String>,
+    /// Already shown earlier in the tree; its children are not repeated
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub repeated: bool,
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    /// Nodes in the tree with nothing further to walk to (roots when walking ancestors)
+    pub fn leaves(&self) -> Vec<&DecisionNode> {
+        let mut out = Vec::new();
+        let mut seen = HashSet::new();
+        self.collect_leaves(&mut out, &mut seen);
+        out
+    }
+
+    fn collect_leaves<'a>(&'a self, out: &mut Vec<&'a DecisionNode>, seen: &mut HashSet<i32>) {
+        if self.repeated {
+            return;
+        }
+        if self.children.is_empty() {
+            if seen.insert(self.node.id) {
+                out.push(&self.node);
+            }
+            return;
+        }
+        for child in &self.children {
+            child.collect_leaves(out, seen);
+        }
+    }
+}
+
+/// One hop of a path between two nodes
+#[derive(Debug, Clone, Serialize)]
+pub struct PathStep {
+    pub from: DecisionNode,
+    pub to: DecisionNode,
+    pub edge_type: String,
+    pub rationale: Option<String>,
+}
+
+/// Adjacency lists keyed by node ID, in edge order
+struct Adjacency<'g> {
+    outgoing: HashMap<i32, Vec<&'g DecisionEdge>>,
+    incoming: HashMap<i32, Vec<&'g DecisionEdge>>,
+    nodes: HashMap<i32, &'g DecisionNode>,
+}
+
+impl<'g> Adjacency<'g> {
+    fn new(graph: &'g DecisionGraph) -> Self {
+        let mut outgoing: HashMap<i32, Vec<&DecisionEdge>> = HashMap::new();
+        let mut incoming: HashMap<i32, Vec<&DecisionEdge>> = HashMap::new();
+        for edge in &graph.edges {
+            outgoing.entry(edge.from_node_id).or_default().push(edge);
+            incoming.entry(edge.to_node_id).or_default().push(edge);
+        }
+        let nodes = graph.nodes.iter().map(|n| (n.id, n)).collect();
+        Self {
+            outgoing,
+            incoming,
+            nodes,
+        }
+    }
+
+    /// (edge, neighbour ID) pairs leaving `id` in `direction`
+    fn neighbours(&self, id: i32, direction: Direction) -> Vec<(&'g DecisionEdge, i32)> {
+        let (map, other): (_, fn(&DecisionEdge) -> i32) = match direction {
+            Direction::Descendants => (&self.outgoing, |e| e.to_node_id),
+            Direction::Ancestors => (&self.incoming, |e| e.from_node_id),
+        };
+        map.get(&id)
+            .map(|edges| {
+                edges
+                    .iter()
+                    .filter(|e| self.nodes.contains_key(&other(e)))
+                    .map(|e| (*e, other(e)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Build the tree of nodes reachable from `root_id`, or None if the node doesn't exist.
+///
+/// A node reachable along several routes is expanded the first time it is met
+/// (depth-first) and marked `repeated` afterwards.
+pub fn build_tree(
+    graph: &DecisionGraph,
+    root_id: i32,
+    direction: Direction,
+    max_depth: Option<usize>,
+) -> Option<TreeNode> {
+    let adjacency = Adjacency::new(graph);
+    let root = *adjacency.nodes.get(&root_id)?;
+    let mut seen = HashSet::new();
+    Some(expand(
+        &adjacency, root, None, direction, 0, max_depth, &mut seen,
+    ))
+}
+
+fn expand(
+    adjacency: &Adjacency,
+    node: &DecisionNode,
+    via: Option<&DecisionEdge>,
+    direction: Direction,
+    depth: usize,
+    max_depth: Option<usize>,
+    seen: &mut HashSet<i32>,
+) -> TreeNode {
+    let repeated = !seen.insert(node.id);
+    let children = if repeated || max_depth.is_some_and(|max| depth >= max) {
+        Vec::new()
+    } else {
+        adjacency
+            .neighbours(node.id, direction)
+            .into_iter()
+            .map(|(edge, id)| {
+                expand(
+                    adjacency,
+                    adjacency.nodes[&id],
+                    Some(edge),
+                    direction,
+                    depth + 1,
+                    max_depth,
+                    seen,
+                )
+            })
+            .collect()
+    };
+
+    TreeNode {
+        node: node.clone(),
+        edge_type: via.map(|e| e.edge_type.clone()),
+        rationale: via.and_then(|e| e.rationale.clone()),
+        repeated,
+        children,
+    }
+}
+
+/// Directed paths from `from` to `to`, following edges forwards.
+///
+/// Returns the shortest path (fewest hops), or with `all` every simple path up to
+/// `MAX_PATHS`, shortest first. Empty when there is no path; a node's path to
+/// itself has no steps.
+pub fn find_paths(graph: &DecisionGraph, from: i32, to: i32, all: bool) -> Vec<Vec<PathStep>> {
+    let adjacency = Adjacency::new(graph);
+    if !adjacency.nodes.contains_key(&from) || !adjacency.nodes.contains_key(&to) {
+        return Vec::new();
+    }
+
+    let mut paths: Vec<Vec<&DecisionEdge>> = if all {
+        let mut search = PathSearch {
+            adjacency: &adjacency,
+            to,
+            reaches_to: reaching(&adjacency, to),
+            on_path: HashSet::from([from]),
+            path: Vec::new(),
+            found: Vec::new(),
+            budget: MAX_PATH_EXPANSIONS,
+        };
+        search.walk(from);
+        search.found
+    } else {
+        shortest_path(&adjacency, from, to).into_iter().collect()
+    };
+    paths.sort_by_key(|p| p.len());
+
+    paths
+        .into_iter()
+        .map(|edges| {
+            edges
+                .into_iter()
+                .map(|e| PathStep {
+                    from: adjacency.nodes[&e.from_node_id].clone(),
+                    to: adjacency.nodes[&e.to_node_id].clone(),
+                    edge_type: e.edge_type.clone(),
+                    rationale: e.rationale.clone(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn shortest_path<'g>(
+    adjacency: &Adjacency<'g>,
+    from: i32,
+    to: i32,
+) -> Option<Vec<&'g DecisionEdge>> {
+    if from == to {
+        return Some(Vec::new());
+    }
+    let mut came_from: HashMap<i32, &DecisionEdge> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(id) = queue.pop_front() {
+        for (edge, next) in adjacency.neighbours(id, Direction::Descendants) {
+            if next == from || came_from.contains_key(&next) {
+                continue;
+            }
+            came_from.insert(next, edge);
+            if next == to {
+                let mut path = Vec::new();
+                let mut current = to;
+                while let Some(edge) = came_from.get(&current) {
+                    path.push(*edge);
+                    current = edge.from_node_id;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            queue.push_back(next);
+        }
+    }
+    None
+}
+
+/// Every node with a directed path to `to`, including `to` itself
+fn reaching(adjacency: &Adjacency, to: i32) -> HashSet<i32> {
+    let mut seen = HashSet::from([to]);
+    let mut queue = VecDeque::from([to]);
+    while let Some(id) = queue.pop_front() {
+        for (_, prev) in adjacency.neighbours(id, Direction::Ancestors) {
+            if seen.insert(prev) {
+                queue.push_back(prev);
+            }
+        }
+    }
+    seen
+}
+
+/// Depth-first enumeration of simple paths, only descending into nodes that can
+/// still reach the target
+struct PathSearch<'a, 'g> {
+    adjacency: &'a Adjacency<'g>,
+    to: i32,
+    reaches_to: HashSet<i32>,
+    on_path: HashSet<i32>,
+    path: Vec<&'g DecisionEdge>,
+    found: Vec<Vec<&'g DecisionEdge>>,
+    /// Edges left to follow
+    budget: usize,
+}
+
+impl PathSearch<'_, '_> {
+    fn walk(&mut self, current: i32) {
+        if current == self.to {
+            self.found.push(self.path.clone());
+            return;
+        }
+        for (edge, next) in self.adjacency.neighbours(current, Direction::Descendants) {
+            if self.found.len() >= MAX_PATHS || self.budget == 0 {
+                return;
+            }
+            if !self.reaches_to.contains(&next) || !self.on_path.insert(next) {
+                continue;
+            }
+            self.budget -= 1;
+            self.path.push(edge);
+            self.walk(next);
+            self.path.pop();
+            self.on_path.remove(&next);
+        }
+    }
+}
+
+/// One-line label for a node: `#12 decision: Pick a cache`
+pub fn node_label(node: &DecisionNode) -> String {
+    format!("#{} {}: {}", node.id, node.node_type, node.title)
+}
+
+/// Render a tree with box-drawing connectors, one node per line
+pub fn render_tree(tree: &TreeNode) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", node_label(&tree.node));
+    for (i, child) in tree.children.iter().enumerate() {
+        render_branch(&mut out, child, "", i + 1 == tree.children.len());
+    }
+    out
+}
+
+fn render_branch(out: &mut String, tree: &TreeNode, prefix: &str, last: bool) {
+    let connector = if last { "└─" } else { "├─" };
+    let edge = tree.edge_type.as_deref().unwrap_or("");
+    let _ = write!(
+        out,
+        "{}{} {} ─ {}",
+        prefix,
+        connector,
+        edge,
+        node_label(&tree.node)
+    );
+    if let Some(rationale) = &tree.rationale {
+        let _ = write!(out, "  ({})", rationale);
+    }
+    if tree.repeated {
+        out.push_str("  [see above]");
+    }
+    out.push('\n');
+
+    let child_prefix = format!("{}{}", prefix, if last { "   " } else { "│  " });
+    for (i, child) in tree.children.iter().enumerate() {
+        render_branch(out, child, &child_prefix, i + 1 == tree.children.len());
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{edge, graph, node};
+
+    /// Edge of the given type, with a rationale
+    fn typed_edge(from: i32, to: i32, edge_type: &str, rationale: &str) -> DecisionEdge {
+        DecisionEdge {
+            edge_type: edge_type.to_string(),
+            rationale: Some(rationale.to_string()),
+            ..edge(from, to)
+        }
+    }
+
+    /// Diamond: 1 -> 2 -> 4, 1 -> 3 -> 4, plus 4 -> 5 and a second goal 6 -> 3
+    fn sample_graph() -> DecisionGraph {
+        graph(
+            vec![
+                node(1, "goal"),
+                node(2, "decision"),
+                node(3, "option"),
+                node(4, "action"),
+                node(5, "outcome"),
+                node(6, "goal"),
+            ],
+            vec![
+                typed_edge(1, 2, "leads_to", "needs a choice"),
+                edge(1, 3),
+                typed_edge(2, 4, "chosen", "fastest"),
+                edge(3, 4),
+                edge(4, 5),
+                edge(6, 3),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_descendant_tree_marks_repeats() {
+        let graph = sample_graph();
+        let tree = build_tree(&graph, 1, Direction::Descendants, None).unwrap();
+        assert_eq!(tree.children.len(), 2);
+        let via_decision = &tree.children[0].children[0];
+        assert_eq!(via_decision.node.id, 4);
+        assert_eq!(via_decision.edge_type.as_deref(), Some("chosen"));
+        assert!(!via_decision.repeated);
+        let via_option = &tree.children[1].children[0];
+        assert!(via_option.repeated);
+        assert!(via_option.children.is_empty());
+
+        let shallow = build_tree(&graph, 1, Direction::Descendants, Some(1)).unwrap();
+        assert!(shallow.children.iter().all(|c| c.children.is_empty()));
+        assert!(build_tree(&graph, 99, Direction::Descendants, None).is_none());
+    }
+
+    #[test]
+    fn test_ancestor_tree_reaches_root_goals() {
+        let graph = sample_graph();
+        let tree = build_tree(&graph, 5, Direction::Ancestors, None).unwrap();
+        let roots: Vec<i32> = tree.leaves().iter().map(|n| n.id).collect();
+        assert_eq!(roots, vec![1, 6]);
+    }
+
+    #[test]
+    fn test_shortest_and_all_paths() {
+        let graph = sample_graph();
+        let shortest = find_paths(&graph, 1, 5, false);
+        assert_eq!(shortest.len(), 1);
+        let hops: Vec<(i32, i32)> = shortest[0].iter().map(|s| (s.from.id, s.to.id)).collect();
+        assert_eq!(hops, vec![(1, 2), (2, 4), (4, 5)]);
+        assert_eq!(shortest[0][0].rationale.as_deref(), Some("needs a choice"));
+
+        let all = find_paths(&graph, 1, 5, true);
+        assert_eq!(all.len(), 2);
+        assert!(find_paths(&graph, 5, 1, false).is_empty());
+        assert!(find_paths(&graph, 1, 99, true).is_empty());
+    }
+
+    #[test]
+    fn test_all_paths_skips_branches_that_never_reach_target() {
+        // 1 -> 2, plus 30 fully connected layers hanging off 1 that never reach 2:
+        // 2^30 dead-end paths that must not be walked
+        let mut graph = graph(vec![node(1, "goal"), node(2, "outcome")], vec![edge(1, 2)]);
+        let layer = |i: i32| [10 + 2 * i, 11 + 2 * i];
+        for i in 0..30 {
+            for id in layer(i) {
+                graph.nodes.push(node(id, "action"));
+                let parents = if i == 0 {
+                    vec![1]
+                } else {
+                    layer(i - 1).to_vec()
+                };
+                for parent in parents {
+                    graph.edges.push(edge(parent, id));
+                }
+            }
+        }
+
+        let all = find_paths(&graph, 1, 2, true);
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].len(), 1);
+    }
+
+    #[test]
+    fn test_cycles_terminate() {
+        let mut graph = sample_graph();
+        graph.edges.push(edge(5, 1));
+        let tree = build_tree(&graph, 1, Direction::Descendants, None).unwrap();
+        assert!(render_tree(&tree).contains("[see above]"));
+        assert_eq!(find_paths(&graph, 2, 1, true).len(), 1);
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let graph = sample_graph();
+        let tree = build_tree(&graph, 2, Direction::Descendants, None).unwrap();
+        assert_eq!(
+            render_tree(&tree),
+            "#2 decision: Node 2\n\
+             └─ chosen ─ #4 action: Node 4  (fastest)\n   \
+             └─ leads_to ─ #5 outcome: Node 5\n"
+        );
+    }
+}
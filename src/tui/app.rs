@@ -11,7 +11,8 @@ use syntect::parsing::SyntaxSet;
 
 use super::types;
 use super::views::roadmap::RoadmapState;
-use crate::{Database, DecisionEdge, DecisionGraph, DecisionNode};
+use super::views::sessions::{SessionEntry, SessionsState};
+use crate::{CommandLog, Database, DecisionContext, DecisionEdge, DecisionGraph, DecisionNode};
 
 // Lazy static syntax highlighting resources
 lazy_static::lazy_static! {
@@ -30,6 +31,7 @@ pub enum View {
     Timeline,
     Dag,
     Roadmap,
+    Sessions,
 }
 
 /// Current input focus
@@ -174,6 +176,8 @@ pub struct App {
 
     // Graph data
     pub graph: DecisionGraph,
+    /// Logged commands linked to nodes (shown in the detail panel)
+    pub commands: Vec<CommandLog>,
     pub filtered_nodes: Vec<DecisionNode>,
 
     // View state
@@ -232,6 +236,9 @@ pub struct App {
 
     // Roadmap view state
     pub roadmap_state: RoadmapState,
+
+    // Sessions view state
+    pub sessions_state: SessionsState,
 }
 
 impl App {
@@ -244,7 +251,9 @@ impl App {
         };
 
         let actual_path = Database::db_path();
-        let graph = db.get_graph()?;
+        let mut graph = db.get_graph()?;
+        graph.context = db.get_all_context()?;
+        let commands = db.get_linked_commands()?;
         let filtered_nodes = graph.nodes.clone();
 
         // Sort by created_at descending (newest first)
@@ -255,6 +264,7 @@ impl App {
             db,
             db_path: actual_path,
             graph,
+            commands,
             filtered_nodes,
             current_view: View::Timeline,
             selected_index: 0,
@@ -287,6 +297,7 @@ impl App {
             detail_in_files: false,
             pending_editor_files: None,
             roadmap_state: RoadmapState::new(),
+            sessions_state: SessionsState::new(),
         })
     }
 
@@ -297,6 +308,8 @@ impl App {
     /// Reload the graph from database
     pub fn reload_graph(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         self.graph = self.db.get_graph()?;
+        self.graph.context = self.db.get_all_context()?;
+        self.commands = self.db.get_linked_commands()?;
         self.apply_filters();
         Ok(())
     }
@@ -354,6 +367,16 @@ impl App {
         )
     }
 
+    /// Get context items attached to a node
+    pub fn get_node_context(&self, node_id: i32) -> Vec<&DecisionContext> {
+        types::get_node_context(node_id, &self.graph.context)
+    }
+
+    /// Get logged commands run for a node
+    pub fn get_node_commands(&self, node_id: i32) -> Vec<&CommandLog> {
+        types::get_node_commands(node_id, &self.commands)
+    }
+
     /// Get node by ID
     pub fn get_node_by_id(&self, id: i32) -> Option<&DecisionNode> {
         self.graph.nodes.iter().find(|n| n.id == id)
@@ -383,6 +406,18 @@ impl App {
         types::get_branch(node)
     }
 
+    /// Parse metadata and extract tags
+    /// Delegates to types::get_tags for consistency
+    pub fn get_tags(node: &DecisionNode) -> Vec<String> {
+        types::get_tags(node)
+    }
+
+    /// Parse metadata and extract key/value attributes
+    /// Delegates to types::get_attributes for consistency
+    pub fn get_attributes(node: &DecisionNode) -> Vec<(String, String)> {
+        types::get_attributes(node)
+    }
+
     /// Parse metadata and extract prompt
     /// Delegates to types::get_prompt for consistency
     pub fn get_prompt(node: &DecisionNode) -> Option<String> {
@@ -456,11 +491,33 @@ impl App {
                 self.load_roadmap_items();
                 View::Roadmap
             }
-            View::Roadmap => View::Timeline,
+            View::Roadmap => {
+                // Load sessions when switching to sessions view
+                self.load_sessions();
+                View::Sessions
+            }
+            View::Sessions => View::Timeline,
             View::Dag => View::Timeline, // DAG view disabled
         };
     }
 
+    /// Load sessions and their nodes from database
+    pub fn load_sessions(&mut self) {
+        let entries = self.db.get_sessions().and_then(|sessions| {
+            sessions
+                .into_iter()
+                .map(|session| {
+                    let nodes = self.db.get_session_nodes(session.id)?;
+                    Ok(SessionEntry { session, nodes })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        });
+        match entries {
+            Ok(entries) => self.sessions_state.set_entries(entries),
+            Err(e) => self.set_status(format!("Failed to load sessions: {}", e)),
+        }
+    }
+
     /// Load roadmap items from database
     pub fn load_roadmap_items(&mut self) {
         match self.db.get_all_roadmap_items() {
@@ -571,24 +628,7 @@ impl App {
 
     /// Cycle through type filters
     pub fn cycle_type_filter(&mut self) {
-        let types = [
-            "goal",
-            "decision",
-            "option",
-            "action",
-            "outcome",
-            "observation",
-        ];
-        self.type_filter = match &self.type_filter {
-            None => Some(types[0].to_string()),
-            Some(current) => {
-                let idx = types.iter().position(|t| t == current);
-                match idx {
-                    Some(i) if i + 1 < types.len() => Some(types[i + 1].to_string()),
-                    _ => None,
-                }
-            }
-        };
+        self.type_filter = super::state::cycle_type_filter(self.type_filter.as_deref());
         self.apply_filters();
     }
 
@@ -107,6 +107,7 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) -> bool {
                 match app.current_view {
                     View::Timeline => app.jump_to_top(),
                     View::Roadmap => app.roadmap_state.jump_to_top(),
+                    View::Sessions => app.sessions_state.jump_to_top(),
                     View::Dag => {} // DAG doesn't have a selection to jump
                 }
                 return false;
@@ -122,6 +123,7 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) -> bool {
         View::Timeline => handle_timeline_keys(app, key),
         View::Dag => handle_dag_keys(app, key),
         View::Roadmap => handle_roadmap_keys(app, key),
+        View::Sessions => handle_sessions_keys(app, key),
     }
 }
 
@@ -249,10 +251,8 @@ fn handle_timeline_keys(app: &mut App, key: KeyEvent) -> bool {
         KeyCode::Tab => app.toggle_view(),
 
         // Escape clears selection or exits modes
-        KeyCode::Esc => {
-            if app.detail_expanded {
-                app.detail_expanded = false;
-            }
+        KeyCode::Esc if app.detail_expanded => {
+            app.detail_expanded = false;
         }
 
         _ => {}
@@ -411,10 +411,56 @@ fn handle_roadmap_keys(app: &mut App, key: KeyEvent) -> bool {
         }
 
         // Close detail panel
-        KeyCode::Esc => {
-            if app.roadmap_state.show_detail {
-                app.roadmap_state.show_detail = false;
-            }
+        KeyCode::Esc if app.roadmap_state.show_detail => {
+            app.roadmap_state.show_detail = false;
+        }
+
+        _ => {}
+    }
+    false
+}
+
+fn handle_sessions_keys(app: &mut App, key: KeyEvent) -> bool {
+    match key.code {
+        // Quit
+        KeyCode::Char('q') => return true,
+
+        // Help
+        KeyCode::Char('?') => {
+            app.show_help = true;
+        }
+
+        // Navigation
+        KeyCode::Char('j') | KeyCode::Down => app.sessions_state.move_down(),
+        KeyCode::Char('k') | KeyCode::Up => app.sessions_state.move_up(),
+
+        // Jump to top (gg - handled via pending_g in normal_mode)
+        KeyCode::Char('g') => {
+            app.pending_g = true;
+        }
+
+        // Jump to bottom (G)
+        KeyCode::Char('G') => {
+            app.sessions_state.jump_to_bottom();
+        }
+
+        // Toggle node list for the selected session (Enter)
+        KeyCode::Enter => {
+            app.sessions_state.toggle_detail();
+        }
+
+        // Switch view (Tab cycles through main views)
+        KeyCode::Tab => app.toggle_view(),
+
+        // Refresh sessions
+        KeyCode::Char('r') => {
+            app.load_sessions();
+            app.set_status("Sessions refreshed".to_string());
+        }
+
+        // Close detail panel
+        KeyCode::Esc if app.sessions_state.show_detail => {
+            app.sessions_state.show_detail = false;
         }
 
         _ => {}
@@ -498,11 +544,9 @@ fn handle_modal(app: &mut App, key: KeyEvent) -> bool {
             app.modal_scroll.offset = app.modal_scroll.total_lines.saturating_sub(10);
         }
         // Open file in editor (for file/diff modals)
-        KeyCode::Char('o') => {
-            if app.get_modal_file_path().is_some() {
-                app.open_modal_file();
-                app.close_modal();
-            }
+        KeyCode::Char('o') if app.get_modal_file_path().is_some() => {
+            app.open_modal_file();
+            app.close_modal();
         }
         _ => {}
     }
@@ -108,10 +108,8 @@ fn run_event_loop<B: Backend + std::io::Write>(
         let timeout = tick_rate.saturating_sub(last_tick.elapsed());
         if poll(timeout)? {
             match read()? {
-                Event::Key(key) => {
-                    if handle_event(app, key) {
-                        return Ok(()); // Quit signal
-                    }
+                Event::Key(key) if handle_event(app, key) => {
+                    return Ok(()); // Quit signal
                 }
                 Event::Mouse(mouse) => {
                     app.handle_mouse(mouse);
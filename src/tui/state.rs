@@ -9,7 +9,7 @@
 //!
 //! The "imperative shell" (app.rs, events.rs) handles I/O and calls these pure functions.
 
-use crate::{DecisionEdge, DecisionNode};
+use crate::{DecisionEdge, DecisionNode, NodeType};
 use std::collections::{HashSet, VecDeque};
 
 // =============================================================================
@@ -319,18 +319,11 @@ pub fn filter_branch_matches(branches: &[String], query: &str) -> Vec<String> {
 
 /// Cycle through type filters
 pub fn cycle_type_filter(current: Option<&str>) -> Option<String> {
-    const TYPES: &[&str] = &[
-        "goal",
-        "decision",
-        "option",
-        "action",
-        "outcome",
-        "observation",
-    ];
+    const TYPES: [NodeType; 6] = NodeType::ALL;
     match current {
         None => Some(TYPES[0].to_string()),
         Some(c) => {
-            let idx = TYPES.iter().position(|t| *t == c);
+            let idx = TYPES.iter().position(|t| t.as_str() == c);
             match idx {
                 Some(i) if i + 1 < TYPES.len() => Some(TYPES[i + 1].to_string()),
                 _ => None,
@@ -391,6 +384,7 @@ mod tests {
             created_at: format!("2024-12-10T12:00:0{}Z", id),
             updated_at: format!("2024-12-10T12:00:0{}Z", id),
             metadata_json: metadata.map(|s| s.to_string()),
+            author: None,
         }
     }
 
@@ -405,6 +399,7 @@ mod tests {
             weight: Some(1.0),
             rationale: None,
             created_at: "2024-12-10T12:00:00Z".to_string(),
+            author: None,
         }
     }
 
@@ -580,6 +575,7 @@ mod tests {
             created_at: "2024-12-10T12:00:00Z".to_string(),
             updated_at: updated_at.to_string(),
             metadata_json: None,
+            author: None,
         }
     }
 
@@ -831,6 +827,7 @@ mod proptests {
                     updated_at: "2024-01-01".to_string(),
                     metadata_json: None,
                     change_id: format!("change-{}", i),
+                    author: None,
                 })
                 .collect();
 
@@ -858,6 +855,7 @@ mod proptests {
                     updated_at: "2024-01-01".to_string(),
                     metadata_json: None,
                     change_id: format!("change-{}", i),
+                    author: None,
                 })
                 .collect();
 
@@ -878,6 +876,7 @@ mod proptests {
                     updated_at: "2024-01-01".to_string(),
                     metadata_json: None,
                     change_id: format!("change-{}", i),
+                    author: None,
                 })
                 .collect();
 
@@ -10,87 +10,14 @@
 //!
 //! All three MUST stay in sync for consistent behavior.
 
-use crate::{DecisionEdge, DecisionNode};
-use serde_json::Value;
-
-// =============================================================================
-// Node Types - matches schema CHECK constraint
-// =============================================================================
-
-/// Valid node types in the decision graph
-#[rustfmt::skip]
-pub const NODE_TYPES: &[&str] = &["goal", "decision", "option", "action", "outcome", "observation"];
-
-/// Valid node statuses
-pub const NODE_STATUSES: &[&str] = &["pending", "active", "completed", "rejected"];
-
-// =============================================================================
-// Edge Types - matches schema CHECK constraint
-// =============================================================================
-
-/// Valid edge types connecting nodes
-#[rustfmt::skip]
-pub const EDGE_TYPES: &[&str] = &["leads_to", "requires", "chosen", "rejected", "blocks", "enables"];
+use crate::{CommandLog, DecisionContext, DecisionEdge, DecisionNode, EdgeType, NodeType};
 
 // =============================================================================
 // Metadata - stored as JSON string in metadata_json field
 // =============================================================================
 
-/// Parsed node metadata from metadata_json field
-#[derive(Debug, Clone, Default)]
-pub struct NodeMetadata {
-    /// Confidence score 0-100
-    pub confidence: Option<i32>,
-    /// Git commit hash (full 40 chars)
-    pub commit: Option<String>,
-    /// User prompt that triggered this decision
-    pub prompt: Option<String>,
-    /// Associated files
-    pub files: Vec<String>,
-    /// Git branch this node was created on
-    pub branch: Option<String>,
-}
-
-impl NodeMetadata {
-    /// Parse metadata from JSON string
-    pub fn from_json(json: &str) -> Self {
-        serde_json::from_str::<Value>(json)
-            .ok()
-            .map(|v| Self {
-                confidence: v
-                    .get("confidence")
-                    .and_then(|c| c.as_i64())
-                    .map(|c| c as i32),
-                commit: v
-                    .get("commit")
-                    .and_then(|c| c.as_str())
-                    .map(|s| s.to_string()),
-                prompt: v
-                    .get("prompt")
-                    .and_then(|p| p.as_str())
-                    .map(|s| s.to_string()),
-                files: v
-                    .get("files")
-                    .and_then(|f| f.as_array())
-                    .map(|arr| {
-                        arr.iter()
-                            .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                            .collect()
-                    })
-                    .unwrap_or_default(),
-                branch: v
-                    .get("branch")
-                    .and_then(|b| b.as_str())
-                    .map(|s| s.to_string()),
-            })
-            .unwrap_or_default()
-    }
-
-    /// Parse metadata from Option<String>
-    pub fn from_option(json: Option<&String>) -> Self {
-        json.map(|s| Self::from_json(s)).unwrap_or_default()
-    }
-}
+/// Parsed node metadata from metadata_json field (canonical type lives in db.rs)
+pub use crate::db::NodeMetadata;
 
 // =============================================================================
 // Helper Functions - Mirror web/src/types/graph.ts functions
@@ -98,27 +25,37 @@ impl NodeMetadata {
 
 /// Extract confidence from a node (mirrors getConfidence in TypeScript)
 pub fn get_confidence(node: &DecisionNode) -> Option<i32> {
-    NodeMetadata::from_option(node.metadata_json.as_ref()).confidence
+    node.confidence().map(i32::from)
 }
 
 /// Extract commit hash from a node (mirrors getCommit in TypeScript)
 pub fn get_commit(node: &DecisionNode) -> Option<String> {
-    NodeMetadata::from_option(node.metadata_json.as_ref()).commit
+    node.commit()
 }
 
 /// Extract branch from a node (mirrors getBranch in TypeScript)
 pub fn get_branch(node: &DecisionNode) -> Option<String> {
-    NodeMetadata::from_option(node.metadata_json.as_ref()).branch
+    node.branch()
 }
 
 /// Extract files from a node (mirrors getFiles in TypeScript)
 pub fn get_files(node: &DecisionNode) -> Vec<String> {
-    NodeMetadata::from_option(node.metadata_json.as_ref()).files
+    node.files()
 }
 
 /// Extract prompt from a node (mirrors getPrompt in TypeScript)
 pub fn get_prompt(node: &DecisionNode) -> Option<String> {
-    NodeMetadata::from_option(node.metadata_json.as_ref()).prompt
+    node.prompt()
+}
+
+/// Extract tags from a node (mirrors getTags in TypeScript)
+pub fn get_tags(node: &DecisionNode) -> Vec<String> {
+    node.tags()
+}
+
+/// Extract key/value attributes from a node
+pub fn get_attributes(node: &DecisionNode) -> Vec<(String, String)> {
+    node.metadata().attributes.into_iter().collect()
 }
 
 /// Get short commit hash (7 chars) (mirrors shortCommit in TypeScript)
@@ -151,14 +88,14 @@ pub fn truncate(s: &str, max_len: usize) -> String {
     }
 }
 
-/// Type guard for valid node type
+/// Type guard for built-in node types (see `NodeType`)
 pub fn is_node_type(value: &str) -> bool {
-    NODE_TYPES.contains(&value)
+    value.parse::<NodeType>().is_ok()
 }
 
-/// Type guard for valid edge type
+/// Type guard for built-in edge types (see `EdgeType`)
 pub fn is_edge_type(value: &str) -> bool {
-    EDGE_TYPES.contains(&value)
+    value.parse::<EdgeType>().is_ok()
 }
 
 /// Get all unique branches from a list of nodes
@@ -179,6 +116,19 @@ pub fn get_outgoing_edges(node_id: i32, edges: &[DecisionEdge]) -> Vec<&Decision
     edges.iter().filter(|e| e.from_node_id == node_id).collect()
 }
 
+/// Get context items attached to a node
+pub fn get_node_context(node_id: i32, context: &[DecisionContext]) -> Vec<&DecisionContext> {
+    context.iter().filter(|c| c.node_id == node_id).collect()
+}
+
+/// Get logged commands run for a node
+pub fn get_node_commands(node_id: i32, commands: &[CommandLog]) -> Vec<&CommandLog> {
+    commands
+        .iter()
+        .filter(|c| c.decision_node_id == Some(node_id))
+        .collect()
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -203,6 +153,7 @@ mod tests {
             created_at: "2024-12-10T12:00:00Z".to_string(),
             updated_at: "2024-12-10T12:00:00Z".to_string(),
             metadata_json: metadata_json.map(|s| s.to_string()),
+            author: None,
         }
     }
 
@@ -217,6 +168,7 @@ mod tests {
             weight: Some(1.0),
             rationale: None,
             created_at: "2024-12-10T12:00:00Z".to_string(),
+            author: None,
         }
     }
 
@@ -382,4 +334,49 @@ mod tests {
         let outgoing_from_3 = get_outgoing_edges(3, &edges);
         assert!(outgoing_from_3.is_empty());
     }
+
+    #[test]
+    fn test_get_node_context() {
+        let context: Vec<DecisionContext> = [(1, 2), (2, 3), (3, 2)]
+            .into_iter()
+            .map(|(id, node_id)| DecisionContext {
+                id,
+                node_id,
+                context_type: "error".to_string(),
+                content_json: crate::build_context_json("boom", None),
+                captured_at: "2024-01-01T00:00:00Z".to_string(),
+            })
+            .collect();
+
+        let ids: Vec<i32> = get_node_context(2, &context).iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+        assert!(get_node_context(1, &context).is_empty());
+    }
+
+    #[test]
+    fn test_get_node_commands() {
+        let commands: Vec<CommandLog> = [(1, Some(2)), (2, None), (3, Some(2))]
+            .into_iter()
+            .map(|(id, decision_node_id)| CommandLog {
+                id,
+                command: "cargo test".to_string(),
+                description: None,
+                working_dir: None,
+                exit_code: Some(0),
+                stdout: None,
+                stderr: None,
+                started_at: "2024-01-01T00:00:00Z".to_string(),
+                completed_at: None,
+                duration_ms: None,
+                decision_node_id,
+            })
+            .collect();
+
+        let ids: Vec<i32> = get_node_commands(2, &commands)
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec![1, 3]);
+        assert!(get_node_commands(1, &commands).is_empty());
+    }
 }
@@ -10,7 +10,7 @@ use syntect::highlighting::{Style as SyntectStyle, ThemeSet};
 use syntect::parsing::SyntaxSet;
 
 use super::app::{App, ModalContent, ModalSection, Mode, View};
-use super::views::{dag, detail, roadmap, timeline};
+use super::views::{dag, detail, roadmap, sessions, timeline};
 use super::widgets::file_picker;
 
 // Lazy static syntax highlighting resources
@@ -59,6 +59,9 @@ pub fn draw(frame: &mut Frame, app: &App) {
         View::Roadmap => {
             roadmap::draw(frame, &app.roadmap_state, main_layout[2]);
         }
+        View::Sessions => {
+            sessions::draw(frame, &app.sessions_state, main_layout[2]);
+        }
     }
 
     // Draw footer
@@ -83,6 +86,7 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
         View::Timeline => "Timeline",
         View::Dag => "DAG",
         View::Roadmap => "Roadmap",
+        View::Sessions => "Sessions",
     };
 
     let node_count = app.filtered_nodes.len();
@@ -212,7 +216,8 @@ fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
             }
         }
         View::Dag => "h/j/k/l:pan  +/-:zoom  0:reset  Tab:Timeline  ?:help  q:quit",
-        View::Roadmap => "j/k:move  r:refresh  Tab:Timeline  ?:help  q:quit",
+        View::Roadmap => "j/k:move  r:refresh  Tab:Sessions  ?:help  q:quit",
+        View::Sessions => "j/k:move  Enter:nodes  r:refresh  Tab:Timeline  ?:help  q:quit",
     };
 
     // Show status message if present, otherwise show keybinds
@@ -231,7 +236,7 @@ fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
 fn draw_help_overlay(frame: &mut Frame, area: Rect) {
     // Center the help popup
     let popup_width = 60.min(area.width.saturating_sub(4));
-    let popup_height = 32.min(area.height.saturating_sub(4));
+    let popup_height = 40.min(area.height.saturating_sub(4));
 
     let popup_area = Rect {
         x: (area.width - popup_width) / 2,
@@ -275,6 +280,12 @@ fn draw_help_overlay(frame: &mut Frame, area: Rect) {
   Shift+Tab    Toggle Active/Completed
   r            Refresh
 
+  Sessions View
+  ─────────────────────────────────
+  j/k, ↑/↓     Move up/down
+  Enter        Show session nodes
+  r            Refresh
+
   Press ? or Esc to close
 "#;
 
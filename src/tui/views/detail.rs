@@ -8,6 +8,12 @@ use ratatui::{
 use crate::tui::app::App;
 use crate::tui::ui::{node_type_color, node_type_style};
 
+/// Lines of each context item shown before it is cut off
+const CONTEXT_PREVIEW_LINES: usize = 8;
+
+/// Trailing output lines shown for a failed command
+const FAILED_OUTPUT_LINES: usize = 3;
+
 /// Draw the detail panel for the selected node
 pub fn draw(frame: &mut Frame, app: &App, area: Rect) {
     let block = Block::default()
@@ -32,6 +38,8 @@ pub fn draw(frame: &mut Frame, app: &App, area: Rect) {
     let files = App::get_files(node);
     let branch = App::get_branch(node);
     let prompt = App::get_prompt(node);
+    let tags = App::get_tags(node);
+    let attributes = App::get_attributes(node);
 
     // Build content lines
     let mut lines: Vec<Line> = vec![];
@@ -289,6 +297,95 @@ pub fn draw(frame: &mut Frame, app: &App, area: Rect) {
         lines.push(Line::from(""));
     }
 
+    // Context - captured error logs, benchmarks, snapshots
+    let context = app.get_node_context(node.id);
+    if !context.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("─── Context ({}) ───", context.len()),
+            Style::default().fg(Color::LightRed).bold(),
+        )));
+        for item in context {
+            let source = item.source().map(|s| format!(" {}", s)).unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("[{}]", item.context_type),
+                    Style::default().fg(Color::LightRed),
+                ),
+                Span::styled(source, Style::default().fg(Color::White)),
+                Span::styled(
+                    format!(" {}", format_date(&item.captured_at)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+            let content = item.content();
+            for line in content.lines().take(CONTEXT_PREVIEW_LINES) {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", line),
+                    Style::default().fg(Color::Gray),
+                )));
+            }
+            let hidden = content
+                .lines()
+                .count()
+                .saturating_sub(CONTEXT_PREVIEW_LINES);
+            if hidden > 0 {
+                lines.push(Line::from(Span::styled(
+                    format!(
+                        "  … {} more lines (deciduous context list {})",
+                        hidden, node.id
+                    ),
+                    Style::default().fg(Color::DarkGray).italic(),
+                )));
+            }
+        }
+        lines.push(Line::from(""));
+    }
+
+    // Commands - run through `deciduous run --node`
+    let commands = app.get_node_commands(node.id);
+    if !commands.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("─── Commands ({}) ───", commands.len()),
+            Style::default().fg(Color::LightBlue).bold(),
+        )));
+        for command in commands {
+            let (marker, color) = match command.exit_code {
+                Some(0) => ("✓".to_string(), Color::Green),
+                Some(code) => (format!("✗ {}", code), Color::Red),
+                None => ("…".to_string(), Color::Yellow),
+            };
+            let duration = command
+                .duration_ms
+                .map(|ms| format!(" {:.1}s", ms as f64 / 1000.0))
+                .unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(format!("{} ", marker), Style::default().fg(color)),
+                Span::styled(command.command.clone(), Style::default().fg(Color::White)),
+                Span::styled(
+                    format!("{} {}", duration, format_date(&command.started_at)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+            if command.exit_code.is_some() && !command.succeeded() {
+                // Errors usually end up on stderr; fall back to stdout
+                let output = command
+                    .stderr
+                    .as_deref()
+                    .filter(|s| !s.trim().is_empty())
+                    .or(command.stdout.as_deref())
+                    .unwrap_or("");
+                let tail: Vec<&str> = output.lines().rev().take(FAILED_OUTPUT_LINES).collect();
+                for line in tail.into_iter().rev() {
+                    lines.push(Line::from(Span::styled(
+                        format!("  {}", line),
+                        Style::default().fg(Color::Gray),
+                    )));
+                }
+            }
+        }
+        lines.push(Line::from(""));
+    }
+
     // Commit - show full info from git
     if let Some(ref hash) = commit {
         lines.push(Line::from(Span::styled(
@@ -327,6 +424,32 @@ pub fn draw(frame: &mut Frame, app: &App, area: Rect) {
         ]));
     }
 
+    // Author
+    if let Some(ref author) = node.author {
+        lines.push(Line::from(vec![
+            Span::styled("Author: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(author, Style::default().fg(Color::Cyan)),
+        ]));
+    }
+
+    // Tags and attributes
+    if !tags.is_empty() {
+        let mut spans = vec![Span::styled("Tags: ", Style::default().fg(Color::DarkGray))];
+        for tag in &tags {
+            spans.push(Span::styled(
+                format!("#{} ", tag),
+                Style::default().fg(Color::Magenta),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    for (key, value) in &attributes {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}: ", key), Style::default().fg(Color::DarkGray)),
+            Span::styled(value.clone(), Style::default().fg(Color::White)),
+        ]));
+    }
+
     lines.push(Line::from(""));
 
     // Action hints
@@ -3,4 +3,5 @@
 pub mod dag;
 pub mod detail;
 pub mod roadmap;
+pub mod sessions;
 pub mod timeline;
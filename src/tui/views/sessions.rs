@@ -0,0 +1,432 @@
+//! Sessions view - working sessions and the nodes created during each
+//!
+//! Follows TEA (The Elm Architecture):
+//! - Model: SessionsState (data)
+//! - Update: state mutation methods
+//! - View: draw() function
+//!
+//! Pure functions are separated for testability.
+
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+};
+
+use super::roadmap::{calculate_scroll, clamp_selection, move_down, move_up, truncate_str};
+use crate::db::{DecisionNode, DecisionSession};
+use crate::tui::ui::node_type_color;
+
+// =============================================================================
+// Model - State
+// =============================================================================
+
+/// A session together with the nodes attached to it
+#[derive(Debug, Clone)]
+pub struct SessionEntry {
+    pub session: DecisionSession,
+    pub nodes: Vec<DecisionNode>,
+}
+
+/// State for the sessions view
+#[derive(Debug, Clone, Default)]
+pub struct SessionsState {
+    /// Sessions from database, most recent first
+    entries: Vec<SessionEntry>,
+    /// Selected index in entries
+    pub selected_index: usize,
+    /// Scroll offset for viewport
+    pub scroll_offset: usize,
+    /// Whether detail panel is shown
+    pub show_detail: bool,
+}
+
+// =============================================================================
+// Pure Functions - Functional Core
+// =============================================================================
+
+/// "2025-12-10T12:00:00-05:00" -> "2025-12-10 12:00"
+pub fn short_timestamp(timestamp: &str) -> String {
+    timestamp
+        .get(..16)
+        .unwrap_or(timestamp)
+        .replacen('T', " ", 1)
+}
+
+/// Display name for a session ("Session 3" when unnamed)
+pub fn session_title(session: &DecisionSession) -> String {
+    match &session.name {
+        Some(name) => name.clone(),
+        None => format!("Session {}", session.id),
+    }
+}
+
+/// Count nodes per type, in order of first appearance
+pub fn count_by_type(nodes: &[DecisionNode]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for node in nodes {
+        match counts.iter_mut().find(|(t, _)| *t == node.node_type) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((node.node_type.clone(), 1)),
+        }
+    }
+    counts
+}
+
+// =============================================================================
+// Update - State Mutations (Methods)
+// =============================================================================
+
+impl SessionsState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the sessions shown
+    pub fn set_entries(&mut self, entries: Vec<SessionEntry>) {
+        self.entries = entries;
+        self.selected_index = clamp_selection(self.selected_index, self.entries.len());
+    }
+
+    /// Move selection up
+    pub fn move_up(&mut self) {
+        self.selected_index = move_up(self.selected_index);
+        self.ensure_visible(20);
+    }
+
+    /// Move selection down
+    pub fn move_down(&mut self) {
+        self.selected_index = move_down(self.selected_index, self.entries.len());
+        self.ensure_visible(20);
+    }
+
+    /// Jump to top (gg)
+    pub fn jump_to_top(&mut self) {
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+    }
+
+    /// Jump to bottom (G)
+    pub fn jump_to_bottom(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected_index = self.entries.len() - 1;
+            self.ensure_visible(20);
+        }
+    }
+
+    /// Toggle detail panel
+    pub fn toggle_detail(&mut self) {
+        self.show_detail = !self.show_detail;
+    }
+
+    /// Get currently selected session
+    pub fn selected_entry(&self) -> Option<&SessionEntry> {
+        self.entries.get(self.selected_index)
+    }
+
+    /// Get all sessions (for rendering)
+    pub fn entries(&self) -> &[SessionEntry] {
+        &self.entries
+    }
+
+    /// Ensure selection is visible in viewport
+    fn ensure_visible(&mut self, visible_items: usize) {
+        self.scroll_offset =
+            calculate_scroll(self.selected_index, self.scroll_offset, visible_items);
+    }
+}
+
+// =============================================================================
+// View - Rendering
+// =============================================================================
+
+/// Draw the sessions view (list, plus nodes of the selected session)
+pub fn draw(frame: &mut Frame, state: &SessionsState, area: Rect) {
+    if state.show_detail {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        draw_list(frame, state, chunks[0]);
+        draw_detail(frame, state, chunks[1]);
+    } else {
+        draw_list(frame, state, area);
+    }
+}
+
+/// Draw the session list, two lines per session
+fn draw_list(frame: &mut Frame, state: &SessionsState, area: Rect) {
+    let block = Block::default()
+        .title(format!(" Sessions ({}) ", state.entries.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    if state.entries.is_empty() {
+        let empty = Paragraph::new("No sessions. Start one with: deciduous session start")
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center);
+        frame.render_widget(empty, inner_area);
+        return;
+    }
+
+    let max_items = (inner_area.height as usize / 2).max(1);
+    let items: Vec<ListItem> = state
+        .entries
+        .iter()
+        .enumerate()
+        .skip(state.scroll_offset)
+        .take(max_items)
+        .map(|(idx, entry)| render_entry(entry, idx == state.selected_index, inner_area.width))
+        .collect();
+
+    frame.render_widget(List::new(items), inner_area);
+}
+
+/// Render one session as a ListItem
+fn render_entry(entry: &SessionEntry, is_selected: bool, width: u16) -> ListItem<'static> {
+    let session = &entry.session;
+
+    let (state_label, state_color) = if session.is_open() {
+        ("open ", Color::Green)
+    } else {
+        ("ended", Color::DarkGray)
+    };
+    let title_style = if is_selected {
+        Style::default().fg(Color::White).bold()
+    } else {
+        Style::default().fg(Color::White)
+    };
+    let max_title_len = (width as usize).saturating_sub(14);
+    let line1 = Line::from(vec![
+        Span::styled(
+            format!("{:>4} ", session.id),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(state_label, Style::default().fg(state_color)),
+        Span::raw(" "),
+        Span::styled(
+            truncate_str(&session_title(session), max_title_len),
+            title_style,
+        ),
+    ]);
+
+    let mut line2_spans = vec![
+        Span::raw("           "),
+        Span::styled(
+            short_timestamp(&session.started_at),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(
+            format!("  {} nodes", entry.nodes.len()),
+            Style::default().fg(Color::Cyan),
+        ),
+    ];
+    for (node_type, count) in count_by_type(&entry.nodes) {
+        line2_spans.push(Span::styled(
+            format!(" {}:{}", node_type, count),
+            Style::default().fg(node_type_color(&node_type)),
+        ));
+    }
+
+    let style = if is_selected {
+        Style::default().bg(Color::Rgb(40, 40, 50))
+    } else {
+        Style::default()
+    };
+
+    ListItem::new(vec![line1, Line::from(line2_spans)]).style(style)
+}
+
+/// Draw the summary and nodes of the selected session
+fn draw_detail(frame: &mut Frame, state: &SessionsState, area: Rect) {
+    let block = Block::default()
+        .title(" Session Detail ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Blue));
+
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(entry) = state.selected_entry() else {
+        let empty =
+            Paragraph::new("No session selected").style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(empty, inner_area);
+        return;
+    };
+    let session = &entry.session;
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            session_title(session),
+            Style::default().fg(Color::Cyan).bold(),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("Started: ", Style::default().fg(Color::DarkGray)),
+            Span::raw(short_timestamp(&session.started_at)),
+        ]),
+    ];
+    let ended = match &session.ended_at {
+        Some(ended) => Span::raw(short_timestamp(ended)),
+        None => Span::styled("still open", Style::default().fg(Color::Green)),
+    };
+    lines.push(Line::from(vec![
+        Span::styled("Ended:   ", Style::default().fg(Color::DarkGray)),
+        ended,
+    ]));
+
+    if let Some(summary) = &session.summary {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Summary:",
+            Style::default().fg(Color::DarkGray),
+        )));
+        for line in summary.lines() {
+            lines.push(Line::from(line.to_string()));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("--- Nodes ({}) ---", entry.nodes.len()),
+        Style::default().fg(Color::DarkGray),
+    )));
+    for node in &entry.nodes {
+        let root = if session.root_node_id == Some(node.id) {
+            " (root)"
+        } else {
+            ""
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:>4} ", node.id),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                format!("{:<12}", node.node_type),
+                Style::default().fg(node_type_color(&node.node_type)),
+            ),
+            Span::raw(node.title.clone()),
+            Span::styled(root, Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+
+    let detail = Paragraph::new(lines).wrap(Wrap { trim: false });
+    frame.render_widget(detail, inner_area);
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_session(id: i32, name: Option<&str>, ended: bool) -> DecisionSession {
+        DecisionSession {
+            id,
+            name: name.map(String::from),
+            started_at: "2025-12-10T12:34:56-05:00".to_string(),
+            ended_at: ended.then(|| "2025-12-10T14:00:00-05:00".to_string()),
+            root_node_id: None,
+            summary: None,
+        }
+    }
+
+    fn make_node(id: i32, node_type: &str) -> DecisionNode {
+        DecisionNode {
+            id,
+            change_id: format!("change-{}", id),
+            node_type: node_type.to_string(),
+            title: format!("Node {}", id),
+            description: None,
+            status: "pending".to_string(),
+            created_at: "2025-12-10T12:40:00-05:00".to_string(),
+            updated_at: "2025-12-10T12:40:00-05:00".to_string(),
+            metadata_json: None,
+            author: None,
+        }
+    }
+
+    fn make_entry(id: i32, nodes: Vec<DecisionNode>) -> SessionEntry {
+        SessionEntry {
+            session: make_session(id, None, true),
+            nodes,
+        }
+    }
+
+    #[test]
+    fn test_short_timestamp() {
+        assert_eq!(
+            short_timestamp("2025-12-10T12:34:56-05:00"),
+            "2025-12-10 12:34"
+        );
+        assert_eq!(short_timestamp("garbage"), "garbage");
+    }
+
+    #[test]
+    fn test_session_title() {
+        assert_eq!(
+            session_title(&make_session(1, Some("refactor"), false)),
+            "refactor"
+        );
+        assert_eq!(session_title(&make_session(7, None, false)), "Session 7");
+    }
+
+    #[test]
+    fn test_count_by_type() {
+        let nodes = vec![
+            make_node(1, "goal"),
+            make_node(2, "action"),
+            make_node(3, "action"),
+        ];
+        assert_eq!(
+            count_by_type(&nodes),
+            vec![("goal".to_string(), 1), ("action".to_string(), 2)]
+        );
+        assert!(count_by_type(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_session_open_state() {
+        assert!(make_session(1, None, false).is_open());
+        assert!(!make_session(1, None, true).is_open());
+    }
+
+    #[test]
+    fn test_sessions_state_navigation() {
+        let mut state = SessionsState::new();
+        state.set_entries(vec![
+            make_entry(3, vec![make_node(5, "goal")]),
+            make_entry(2, vec![]),
+            make_entry(1, vec![]),
+        ]);
+
+        state.move_down();
+        assert_eq!(state.selected_entry().unwrap().session.id, 2);
+        state.jump_to_bottom();
+        assert_eq!(state.selected_entry().unwrap().session.id, 1);
+        state.move_down();
+        assert_eq!(state.selected_index, 2);
+        state.jump_to_top();
+        assert_eq!(state.selected_entry().unwrap().nodes.len(), 1);
+
+        // Selection is clamped when sessions disappear
+        state.jump_to_bottom();
+        state.set_entries(vec![make_entry(1, vec![])]);
+        assert_eq!(state.selected_index, 0);
+        state.set_entries(vec![]);
+        assert!(state.selected_entry().is_none());
+    }
+
+    #[test]
+    fn test_toggle_detail() {
+        let mut state = SessionsState::new();
+        assert!(!state.show_detail);
+        state.toggle_detail();
+        assert!(state.show_detail);
+    }
+}
@@ -1,11 +1,27 @@
 // This file is auto-generated by bin/gen_types.rs
 // Do not edit manually.
 
-export type DecisionNode = { id: number, change_id: string, node_type: string, title: string, description: string | null, status: string, created_at: string, updated_at: string, metadata_json: string | null, };
-
-export type DecisionEdge = { id: number, from_node_id: number, to_node_id: number, from_change_id: string | null, to_change_id: string | null, edge_type: string, weight: number | null, rationale: string | null, created_at: string, };
-
-export type DecisionContext = { id: number, node_id: number, context_type: string, content_json: string, captured_at: string, };
+export type DecisionNode = { id: number, change_id: string, node_type: string, title: string, description: string | null, status: string, created_at: string, updated_at: string, metadata_json: string | null, 
+/**
+ * Who created the node ("Name <email>"), if known
+ */
+author: string | null, };
+
+export type DecisionEdge = { id: number, from_node_id: number, to_node_id: number, from_change_id: string | null, to_change_id: string | null, edge_type: string, weight: number | null, rationale: string | null, created_at: string, 
+/**
+ * Who created the edge ("Name <email>"), if known
+ */
+author: string | null, };
+
+export type DecisionContext = { id: number, node_id: number, 
+/**
+ * file-snapshot, error, benchmark, url or snippet
+ */
+context_type: string, 
+/**
+ * JSON object with the captured `content` and, for files, its `source` path
+ */
+content_json: string, captured_at: string, };
 
 export type DecisionSession = { id: number, name: string | null, started_at: string, ended_at: string | null, root_node_id: number | null, summary: string | null, };
 
@@ -17,3 +33,5 @@ export type RoadmapSyncState = { id: number, roadmap_path: string, roadmap_conte
 
 export type RoadmapConflict = { id: number, item_change_id: string, conflict_type: string, local_value: string | null, remote_value: string | null, resolution: string | null, detected_at: string, resolved_at: string | null, };
 
+export type NodeConflict = { id: number, node_change_id: string, base_hash: string | null, base_value: string | null, local_value: string, remote_value: string, resolution: string | null, detected_at: string, resolved_at: string | null, };
+
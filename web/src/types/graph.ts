@@ -31,11 +31,14 @@ export type EdgeType = typeof EDGE_TYPES[number];
 // =============================================================================
 
 export interface NodeMetadata {
+  metadata_version?: number;  // Format version (absent before versioning)
   confidence?: number;  // 0-100 confidence score
   commit?: string;      // Git commit hash (full 40 chars)
   prompt?: string;      // User prompt that triggered this decision
   files?: string[];     // Associated files
   branch?: string;      // Git branch this node was created on
+  tags?: string[];      // Free-form labels (security, perf, tech-debt, ...)
+  attributes?: Record<string, string>;  // Key/value attributes (ticket=ABC-123)
   [key: string]: unknown;  // Allow extension
 }
 
@@ -46,6 +49,7 @@ export interface NodeMetadata {
 import {
   DecisionNode as GeneratedDecisionNode,
   DecisionEdge as GeneratedDecisionEdge,
+  DecisionContext,
 } from './generated/schema';
 
 // Re-export generated types as the source of truth
@@ -92,6 +96,16 @@ export interface GraphData {
   nodes: DecisionNode[];
   edges: DecisionEdge[];
   config?: DeciduousConfig;  // Optional config for external repo links
+  context?: DecisionContext[];  // Only with /api/graph?include=context
+}
+
+/**
+ * One result from GET /api/search?q=
+ */
+export interface SearchHit {
+  node: DecisionNode;
+  score: number;    // Relevance, higher is better
+  snippet: string;  // Matched terms wrapped in [ ]
 }
 
 // =============================================================================
@@ -212,6 +226,14 @@ export function getFiles(node: DecisionNode): string[] | null {
   return meta?.files ?? null;
 }
 
+/**
+ * Extract tags from a node
+ */
+export function getTags(node: DecisionNode): string[] {
+  const meta = parseMetadata(node.metadata_json);
+  return meta?.tags ?? [];
+}
+
 /**
  * Get all unique branches from a list of nodes
  */